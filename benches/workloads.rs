@@ -0,0 +1,168 @@
+//! Benchmarks of the allocator under a few representative workloads
+//!
+//! Run with `cargo bench`. Every workload is driven by a fixed-seed generator,
+//! so results are comparable between runs & between strategies.
+
+use {
+  ::criterion::{criterion_group, criterion_main, BatchSize, Criterion},
+  ::orderly_allocator::{Allocation, Allocator},
+  ::std::hint::black_box,
+};
+
+const CAPACITY: u32 = 1 << 30;
+const OPS: usize = 10_000;
+
+/// A tiny deterministic generator, so that workloads are reproducible
+struct XorShift(u64);
+
+impl XorShift {
+  fn next(&mut self) -> u32 {
+    self.0 ^= self.0 << 13;
+    self.0 ^= self.0 >> 7;
+    self.0 ^= self.0 << 17;
+    (self.0 >> 32) as u32
+  }
+
+  /// A size in `1..=max`
+  fn size(&mut self, max: u32) -> u32 {
+    self.next() % max + 1
+  }
+}
+
+/// Allocations with a mix of GPU-style alignments
+fn aligned(c: &mut Criterion) {
+  c.bench_function("aligned", |b| {
+    b.iter_batched(
+      || (Allocator::new(CAPACITY), XorShift(1)),
+      |(mut allocator, mut rng)| {
+        for _ in 0..OPS {
+          let align = 1 << (rng.next() % 9);
+          black_box(allocator.alloc_with_align(rng.size(4_096), align));
+        }
+        allocator
+      },
+      BatchSize::LargeInput,
+    )
+  });
+}
+
+/// Churn through same-sized aligned allocations, interleaved with small odd
+/// sizes, with & without the aligned exact-fit search
+///
+/// The fragmentation left behind by each is printed, since that is the point
+/// of the policy; the timings show what it costs.
+fn exact_fit_first(c: &mut Criterion) {
+  fn churn(exact_fit_first: bool) -> Allocator {
+    let mut allocator = Allocator::new(OPS as u32 * 256);
+    allocator.set_exact_fit_first(exact_fit_first);
+    let mut live = Vec::new();
+    let mut rng = XorShift(4);
+    for _ in 0..OPS {
+      if rng.next().is_multiple_of(2) && !live.is_empty() {
+        let index = rng.next() as usize % live.len();
+        allocator.free(live.swap_remove(index));
+      } else if rng.next().is_multiple_of(4) {
+        live.extend(allocator.alloc(rng.size(63)));
+      } else {
+        let size = 64 * rng.size(3);
+        live.extend(allocator.alloc_with_align(size, 64));
+      }
+    }
+    allocator
+  }
+
+  let mut group = c.benchmark_group("exact_fit_first");
+  for enabled in [false, true] {
+    eprintln!(
+      "exact_fit_first({enabled}): {}",
+      churn(enabled).fragmentation_report(64)
+    );
+    group.bench_function(if enabled { "on" } else { "off" }, |b| {
+      b.iter(|| black_box(churn(enabled)))
+    });
+  }
+  group.finish();
+}
+
+/// Fill the pool, then free every other allocation, leaving thousands of
+/// small holes which are too small for the follow-up allocations
+fn fragmentation(c: &mut Criterion) {
+  c.bench_function("fragmentation", |b| {
+    b.iter_batched(
+      || {
+        let mut allocator = Allocator::new(OPS as u32 * 64);
+        let allocations: Vec<Allocation> =
+          (0..OPS).map(|_| allocator.alloc(64).unwrap()).collect();
+        for &allocation in allocations.iter().step_by(2) {
+          allocator.free(allocation);
+        }
+        (allocator, allocations)
+      },
+      |(mut allocator, allocations)| {
+        for _ in 0..OPS / 2 {
+          black_box(allocator.alloc(65));
+          black_box(allocator.alloc(32));
+        }
+        for &allocation in allocations.iter().skip(1).step_by(2) {
+          allocator.free(allocation);
+        }
+        allocator
+      },
+      BatchSize::LargeInput,
+    )
+  });
+}
+
+/// Allocations which are repeatedly grown & shrunk in-place, or moved when
+/// that fails
+fn realloc_heavy(c: &mut Criterion) {
+  c.bench_function("realloc_heavy", |b| {
+    b.iter_batched(
+      || {
+        let mut allocator = Allocator::new(CAPACITY);
+        let allocations: Vec<Allocation> =
+          (0..1_000).map(|_| allocator.alloc(256).unwrap()).collect();
+        (allocator, allocations, XorShift(2))
+      },
+      |(mut allocator, mut allocations, mut rng)| {
+        for _ in 0..OPS {
+          let index = rng.next() as usize % allocations.len();
+          let allocation = allocations[index];
+          let new_size = rng.size(1_024);
+          allocations[index] = allocator
+            .try_reallocate(allocation, new_size)
+            .unwrap_or_else(|_| {
+              allocator.free(allocation);
+              allocator.alloc(new_size).unwrap()
+            });
+        }
+        allocator
+      },
+      BatchSize::LargeInput,
+    )
+  });
+}
+
+/// Short-lived frames of allocations, discarded with `reset`
+fn reset_heavy(c: &mut Criterion) {
+  c.bench_function("reset_heavy", |b| {
+    let mut allocator = Allocator::new(CAPACITY);
+    let mut rng = XorShift(3);
+    b.iter(|| {
+      for _ in 0..100 {
+        black_box(allocator.alloc(rng.size(4_096)));
+      }
+      allocator.reset();
+    })
+  });
+}
+
+criterion_group!(
+  benches,
+  aligned,
+  exact_fit_first,
+  fragmentation,
+  realloc_heavy,
+  reset_heavy
+);
+criterion_main!(benches);
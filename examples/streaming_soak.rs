@@ -0,0 +1,172 @@
+//! A soak test simulating a GPU streaming workload
+//!
+//! Textures & meshes of mixed sizes and alignments are streamed into a 1 GiB
+//! pool and evicted again over a simulated session. When a load doesn't fit,
+//! the least-recently loaded assets are evicted until it does. A summary of
+//! occupancy, fragmentation & operation latency is printed every 10 simulated
+//! minutes.
+//!
+//! Run with:
+//! ```text
+//! cargo run --release --example streaming_soak -- [minutes] [seed]
+//! ```
+//! The defaults are 60 minutes & seed 1. The workload only depends on the
+//! seed, so the fragmentation figures are reproducible between runs.
+
+use {
+  ::orderly_allocator::{Allocation, Allocator},
+  ::std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BinaryHeap},
+    env,
+    time::{Duration, Instant},
+  },
+};
+
+const CAPACITY: u32 = 1 << 30;
+const TICKS_PER_MINUTE: u64 = 60 * 30;
+const REPORT_EVERY_MINUTES: u64 = 10;
+/// Free-regions smaller than this are counted as unusable
+const UNUSABLE_BELOW: u32 = 64 << 10;
+
+/// A tiny deterministic generator, so that runs are reproducible
+struct XorShift(u64);
+
+impl XorShift {
+  fn next(&mut self) -> u32 {
+    self.0 ^= self.0 << 13;
+    self.0 ^= self.0 >> 7;
+    self.0 ^= self.0 << 17;
+    (self.0 >> 32) as u32
+  }
+
+  /// A roughly log-uniform size in `min..max`
+  fn size(&mut self, min: u32, max: u32) -> u32 {
+    let magnitude = min.ilog2() + self.next() % (max.ilog2() - min.ilog2());
+    (1 << magnitude) + self.next() % (1 << magnitude)
+  }
+}
+
+/// Pick the size & alignment of the next asset to stream in
+fn next_asset(rng: &mut XorShift) -> (u32, u32) {
+  if rng.next().is_multiple_of(3) {
+    // a block-compressed texture, with its mip chain
+    let side = 1 << (6 + rng.next() % 7);
+    (side * side * 4 / 3, 64 << 10)
+  } else {
+    // a vertex or index buffer
+    (rng.size(4 << 10, 4 << 20), 256)
+  }
+}
+
+/// Latencies collected over one reporting window
+#[derive(Default)]
+struct Latencies(Vec<Duration>);
+
+impl Latencies {
+  fn time<T>(&mut self, op: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = op();
+    self.0.push(start.elapsed());
+    result
+  }
+
+  /// Summarise the window & start a new one
+  fn summary(&mut self) -> String {
+    self.0.sort_unstable();
+    let percentile = |p: usize| {
+      let index = (self.0.len() * p).div_ceil(1_000).saturating_sub(1);
+      self.0.get(index).copied().unwrap_or_default()
+    };
+    let summary = format!(
+      "p50 {:?}, p99 {:?}, p99.9 {:?}, max {:?}",
+      percentile(500),
+      percentile(990),
+      percentile(999),
+      self.0.last().copied().unwrap_or_default(),
+    );
+    self.0.clear();
+    summary
+  }
+}
+
+fn main() {
+  let mut args = env::args()
+    .skip(1)
+    .map(|arg| arg.parse::<u64>().expect("arguments must be whole numbers"));
+  let minutes = args.next().unwrap_or(60);
+  let seed = args.next().unwrap_or(1);
+
+  let mut allocator = Allocator::new(CAPACITY);
+  let mut rng = XorShift(seed.max(1));
+  // resident assets, by load order
+  let mut resident: BTreeMap<u64, Allocation> = BTreeMap::new();
+  let mut expiries: BinaryHeap<Reverse<(u64, u64)>> = BinaryHeap::new();
+  let mut next_id = 0;
+  let (mut loads, mut evictions, mut failures) = (0, 0, 0);
+  let mut alloc_latency = Latencies::default();
+  let mut free_latency = Latencies::default();
+
+  for tick in 0..minutes * TICKS_PER_MINUTE {
+    // unload the assets which are no longer needed
+    while let Some(&Reverse((expiry, id))) = expiries.peek() {
+      if expiry > tick {
+        break;
+      }
+      expiries.pop();
+      if let Some(allocation) = resident.remove(&id) {
+        free_latency.time(|| allocator.free(allocation));
+      }
+    }
+
+    // stream in a few new assets
+    for _ in 0..rng.next() % 4 {
+      let (size, align) = next_asset(&mut rng);
+      let allocation = loop {
+        let allocation =
+          alloc_latency.time(|| allocator.alloc_with_align(size, align));
+        if allocation.is_some() {
+          break allocation;
+        }
+        let Some((_, oldest)) = resident.pop_first() else {
+          break None;
+        };
+        free_latency.time(|| allocator.free(oldest));
+        evictions += 1;
+      };
+
+      let Some(allocation) = allocation else {
+        failures += 1;
+        continue;
+      };
+      loads += 1;
+      resident.insert(next_id, allocation);
+      let lifetime = rng.size(30, 20 * TICKS_PER_MINUTE as u32) as u64;
+      expiries.push(Reverse((tick + lifetime, next_id)));
+      next_id += 1;
+    }
+
+    let minute = (tick + 1) / TICKS_PER_MINUTE;
+    if (tick + 1).is_multiple_of(REPORT_EVERY_MINUTES * TICKS_PER_MINUTE) {
+      let used = CAPACITY - allocator.total_available();
+      println!(
+        "[{minute:>4} min] {} resident ({:.1} MiB, {:.1}% used); {loads} \
+         loads, {evictions} evictions, {failures} failures",
+        resident.len(),
+        used as f64 / (1 << 20) as f64,
+        allocator.usage_ratio() * 100.,
+      );
+      let report = allocator.fragmentation_report(UNUSABLE_BELOW);
+      println!("  free-regions: {report}");
+      println!("  alloc: {}", alloc_latency.summary());
+      println!("  free:  {}", free_latency.summary());
+    }
+  }
+
+  let stats = allocator.stats();
+  println!(
+    "done; peak {} free-regions, {} splits, {} bytes of alignment padding",
+    stats.peak_free_regions, stats.splits, stats.alignment_padding,
+  );
+  allocator.free_many(resident.into_values());
+}
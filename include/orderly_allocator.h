@@ -0,0 +1,134 @@
+#ifndef ORDERLY_ALLOCATOR_H
+#define ORDERLY_ALLOCATOR_H
+
+/* Generated by cbindgen; do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * An opaque handle to an allocator
+ *
+ * Created by [`orderly_allocator_create`], and destroyed by
+ * [`orderly_allocator_destroy`].
+ */
+typedef struct OrderlyAllocator OrderlyAllocator;
+
+/**
+ * An allocation, or the failure to allocate
+ *
+ * A `size` of 0 indicates failure.
+ */
+typedef struct OrderlyAllocation {
+  /**
+   * The location of this allocation within the pool
+   */
+  uint32_t offset;
+  /**
+   * The size of this allocation, or 0 on failure
+   */
+  uint32_t size;
+} OrderlyAllocation;
+
+/**
+ * A summary of the state of an allocator
+ */
+typedef struct OrderlyStats {
+  /**
+   * The total capacity of the pool
+   */
+  uint32_t capacity;
+  /**
+   * The total free space in the pool
+   */
+  uint32_t total_available;
+  /**
+   * The size of the largest free-region in the pool
+   */
+  uint32_t largest_available;
+} OrderlyStats;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Create a new allocator to manage a pool of `capacity` bytes
+ *
+ * Returns null if `capacity == 0`.
+ */
+struct OrderlyAllocator *orderly_allocator_create(uint32_t capacity);
+
+/**
+ * Destroy an allocator
+ *
+ * Does nothing if `allocator` is null.
+ *
+ * # Safety
+ *
+ * `allocator` must be null, or a pointer returned by
+ * [`orderly_allocator_create`] which has not already been destroyed.
+ */
+void orderly_allocator_destroy(struct OrderlyAllocator *allocator);
+
+/**
+ * Try to allocate a region with the provided size & alignment
+ *
+ * Returns an allocation with a `size` of 0 on failure.
+ *
+ * # Safety
+ *
+ * `allocator` must be a live pointer returned by [`orderly_allocator_create`].
+ */
+struct OrderlyAllocation orderly_allocator_alloc(struct OrderlyAllocator *allocator,
+                                                 uint32_t size,
+                                                 uint32_t align);
+
+/**
+ * Free the given allocation
+ *
+ * Does nothing if the allocation's `size` is 0.
+ *
+ * # Safety
+ *
+ * `allocator` must be a live pointer returned by [`orderly_allocator_create`].
+ */
+void orderly_allocator_free(struct OrderlyAllocator *allocator,
+                            struct OrderlyAllocation allocation);
+
+/**
+ * Re-size an allocation, moving it if it cannot be re-sized in-place
+ *
+ * If the returned allocation has a different offset, the caller is
+ * responsible for moving the contents; the old region has already been
+ * freed. The allocator never touches the pool's memory, so the contents of
+ * the old region remain intact until the next allocation.
+ *
+ * Returns an allocation with a `size` of 0 on failure, in which case the
+ * original allocation is left untouched.
+ *
+ * # Safety
+ *
+ * `allocator` must be a live pointer returned by [`orderly_allocator_create`].
+ */
+struct OrderlyAllocation orderly_allocator_realloc(struct OrderlyAllocator *allocator,
+                                                   struct OrderlyAllocation allocation,
+                                                   uint32_t new_size,
+                                                   uint32_t align);
+
+/**
+ * Get a summary of the state of the allocator
+ *
+ * # Safety
+ *
+ * `allocator` must be a live pointer returned by [`orderly_allocator_create`].
+ */
+struct OrderlyStats orderly_allocator_stats(const struct OrderlyAllocator *allocator);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* ORDERLY_ALLOCATOR_H */
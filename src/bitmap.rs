@@ -0,0 +1,290 @@
+use {
+  crate::{Allocation, Location, Overflow, Size},
+  ::alloc::{vec, vec::Vec},
+  ::core::{fmt, num::NonZero, ops::Range},
+};
+
+/// The number of blocks tracked by each word of the bitmap
+const BITS: usize = u64::BITS as usize;
+
+/// An allocator for small pools, tracking free & used blocks with a bitmap
+///
+/// The pool is divided into blocks of `block_size`, and every allocation is
+/// rounded up to a whole number of blocks. Searches scan the bitmap a word at
+/// a time, skipping 64 used blocks per step, and freeing just clears bits, so
+/// neighbouring free blocks are coalesced without any bookkeeping.
+///
+/// The bitmap takes one bit per block regardless of how fragmented the pool
+/// is, and searches are linear in the number of blocks, so this is best suited
+/// to pools of a few thousand blocks or fewer, e.g. descriptor or slot pools.
+/// Larger pools should use [`Allocator`](crate::Allocator).
+#[derive(Clone)]
+pub struct BitmapAllocator {
+  /// One bit per block, set if the block is allocated
+  bits: Vec<u64>,
+  /// The number of blocks in the pool
+  blocks: usize,
+  block_size: Size,
+  /// The number of free blocks
+  available: usize,
+}
+
+impl BitmapAllocator {
+  /// Create a new allocator to manage a pool of memory, divided into blocks
+  /// of `block_size`
+  ///
+  /// Any space at the end of the pool smaller than a whole block is not used.
+  ///
+  /// Panics:
+  /// - Panics if `capacity < block_size`
+  /// - Panics if `block_size == 0`
+  pub fn new(capacity: Size, block_size: Size) -> Self {
+    assert!(block_size != 0, "`block_size == 0`");
+    assert!(capacity >= block_size, "`capacity < block_size`");
+
+    let blocks = (capacity / block_size) as usize;
+    BitmapAllocator {
+      bits: vec![0; blocks.div_ceil(BITS)],
+      blocks,
+      block_size,
+      available: blocks,
+    }
+  }
+
+  /// Try to allocate a region with the provided size
+  ///
+  /// See [`alloc_with_align`](Self::alloc_with_align).
+  pub fn alloc(&mut self, size: Size) -> Option<Allocation> {
+    self.alloc_with_align(size, 1)
+  }
+
+  /// Try to allocate a region with the provided size & alignment
+  ///
+  /// Uses a *first-fit* strategy, returning the lowest suitable location. The
+  /// size of the returned allocation is rounded up to a whole number of
+  /// blocks.
+  ///
+  /// Returns `None` if:
+  /// - there is no run of free blocks which can fit the aligned allocation,
+  ///   or
+  /// - `size == 0`, or
+  /// - `align == 0`.
+  pub fn alloc_with_align(
+    &mut self,
+    size: Size,
+    align: Size,
+  ) -> Option<Allocation> {
+    if size == 0 || align == 0 {
+      return None;
+    }
+    let count = size.div_ceil(self.block_size) as usize;
+    if count > self.available {
+      return None;
+    }
+    // the allocation must start on a block which is a multiple of `align`
+    let step = (align / gcd(self.block_size, align)) as usize;
+
+    let mut from = 0;
+    while let Some(run) = self.next_free_run(from) {
+      let start = run.start.checked_next_multiple_of(step)?;
+      if start + count <= run.end {
+        self.set_range(start..start + count, true);
+        self.available -= count;
+        return Some(Allocation {
+          offset: start as Location * self.block_size,
+          size: NonZero::new(count as Size * self.block_size)?,
+        });
+      }
+      from = run.end;
+    }
+
+    None
+  }
+
+  /// Free the given allocation
+  ///
+  /// Panics:
+  /// - Panics if the allocation is not within the pool, or does not cover
+  ///   whole blocks
+  /// - Panics if any of the allocation's blocks are already free
+  pub fn free(&mut self, alloc: Allocation) {
+    let size = alloc.size.get();
+    assert!(
+      alloc.offset.is_multiple_of(self.block_size)
+        && size.is_multiple_of(self.block_size),
+      "{alloc:?} does not cover whole blocks"
+    );
+    let start = (alloc.offset / self.block_size) as usize;
+    let end = start + (size / self.block_size) as usize;
+    assert!(end <= self.blocks, "{alloc:?} is not within the pool");
+    assert!(
+      self.is_range_used(start..end),
+      "Double free. Tried to free {alloc:?}, which overlaps a free block"
+    );
+
+    self.set_range(start..end, false);
+    self.available += end - start;
+  }
+
+  /// Free ***all*** allocations
+  pub fn reset(&mut self) {
+    self.bits.fill(0);
+    self.available = self.blocks;
+  }
+
+  /// Add new free blocks at the end of the pool
+  ///
+  /// Only whole blocks are added; any remainder of `additional` smaller than
+  /// a block is not used.
+  ///
+  /// Returns `Err(Overflow)` if `self.capacity() + additional` would
+  /// overflow.
+  pub fn grow_capacity(&mut self, additional: Size) -> Result<(), Overflow> {
+    let Some(additional) = NonZero::new(additional) else {
+      return Ok(()); // `additional` is zero, so do nothing
+    };
+    let current_capacity =
+      NonZero::new(self.capacity()).unwrap_or_else(|| unreachable!());
+    if current_capacity.checked_add(additional.get()).is_none() {
+      return Err(Overflow {
+        current_capacity,
+        additional,
+      });
+    }
+
+    // note: the bits past the last block are always clear, so the new blocks
+    // start out free
+    let new_blocks = (additional.get() / self.block_size) as usize;
+    self.blocks += new_blocks;
+    self.available += new_blocks;
+    self.bits.resize(self.blocks.div_ceil(BITS), 0);
+    Ok(())
+  }
+
+  /// Get the total capacity of the pool, i.e. the space covered by whole
+  /// blocks
+  pub fn capacity(&self) -> Size {
+    self.blocks as Size * self.block_size
+  }
+
+  /// Get the size of each block
+  pub fn block_size(&self) -> Size {
+    self.block_size
+  }
+
+  /// Get the total available memory in the pool
+  ///
+  /// Note: The memory may be fragmented, so it may not be possible to
+  /// allocate an object of this size.
+  pub fn total_available(&self) -> Size {
+    self.available as Size * self.block_size
+  }
+
+  /// Get the size of the largest available run of free blocks
+  ///
+  /// Note: This scans the whole bitmap.
+  pub fn largest_available(&self) -> Size {
+    self
+      .report_free_regions()
+      .map(|region| region.size.get())
+      .max()
+      .unwrap_or(0)
+  }
+
+  /// Returns true if there are no allocations
+  pub fn is_empty(&self) -> bool {
+    self.available == self.blocks
+  }
+
+  /// Returns an iterator over the unallocated regions, in order of location
+  ///
+  /// Neighbouring free blocks are reported as a single region.
+  pub fn report_free_regions(
+    &self,
+  ) -> impl Iterator<Item = Allocation> + use<'_> {
+    let mut from = 0;
+    ::core::iter::from_fn(move || {
+      let run = self.next_free_run(from)?;
+      from = run.end;
+      Some(Allocation {
+        offset: run.start as Location * self.block_size,
+        size: NonZero::new((run.end - run.start) as Size * self.block_size)?,
+      })
+    })
+  }
+
+  /// Find the first block at or after `from` which is used, or free
+  ///
+  /// Returns the number of blocks if there is no such block.
+  fn find(&self, from: usize, used: bool) -> usize {
+    let mut index = from;
+    while index < self.blocks {
+      let word = self.bits[index / BITS];
+      let word = if used { word } else { !word };
+      let remaining = word >> (index % BITS);
+      if remaining != 0 {
+        return (index + remaining.trailing_zeros() as usize).min(self.blocks);
+      }
+      index = (index / BITS + 1) * BITS;
+    }
+    self.blocks
+  }
+
+  /// Find the first run of free blocks starting at or after `from`
+  fn next_free_run(&self, from: usize) -> Option<Range<usize>> {
+    let start = self.find(from, false);
+    (start < self.blocks).then(|| start..self.find(start, true))
+  }
+
+  /// Call `f` with the index of each word overlapping `range`, & a mask of
+  /// the bits within `range`
+  fn for_each_word(range: Range<usize>, mut f: impl FnMut(usize, u64)) {
+    let mut start = range.start;
+    while start < range.end {
+      let word = start / BITS;
+      let low = start % BITS;
+      let high = (range.end - word * BITS).min(BITS);
+      let mask = (u64::MAX >> (BITS - high)) & (u64::MAX << low);
+      f(word, mask);
+      start = word * BITS + high;
+    }
+  }
+
+  /// Mark every block in `range` as used, or free
+  fn set_range(&mut self, range: Range<usize>, used: bool) {
+    Self::for_each_word(range, |word, mask| {
+      if used {
+        self.bits[word] |= mask;
+      } else {
+        self.bits[word] &= !mask;
+      }
+    });
+  }
+
+  /// Returns true if every block in `range` is used
+  fn is_range_used(&self, range: Range<usize>) -> bool {
+    let mut used = true;
+    Self::for_each_word(range, |word, mask| {
+      used &= self.bits[word] & mask == mask;
+    });
+    used
+  }
+}
+
+/// The greatest common divisor of `a` & `b`
+fn gcd(mut a: Size, mut b: Size) -> Size {
+  while b != 0 {
+    (a, b) = (b, a % b);
+  }
+  a
+}
+
+impl fmt::Debug for BitmapAllocator {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("BitmapAllocator")
+      .field("capacity", &self.capacity())
+      .field("block_size", &self.block_size)
+      .field("total_available", &self.total_available())
+      .finish()
+  }
+}
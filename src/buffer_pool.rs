@@ -0,0 +1,117 @@
+use {
+  crate::{Allocation, Allocator, Size},
+  ::alloc::{boxed::Box, vec},
+  ::core::fmt,
+};
+
+/// An [`Allocator`] coupled with the byte buffer it manages
+///
+/// This is a convenience for using the allocator as a CPU-side arena; the
+/// contents of allocations are accessed through borrow-checked slices, so no
+/// `unsafe` is required.
+///
+/// Note: The buffer has no particular alignment in memory; the alignment of
+/// allocations is relative to the start of the buffer.
+#[derive(Clone)]
+pub struct BufferPool {
+  allocator: Allocator,
+  buffer: Box<[u8]>,
+}
+
+impl BufferPool {
+  /// Create a new zeroed buffer of `capacity` bytes, and an allocator to
+  /// manage it
+  ///
+  /// Panics:
+  /// - Panics if `capacity == 0`
+  pub fn new(capacity: Size) -> Self {
+    BufferPool {
+      allocator: Allocator::new(capacity),
+      buffer: vec![0; capacity as usize].into_boxed_slice(),
+    }
+  }
+
+  /// Try to allocate a region with the provided size
+  ///
+  /// See [`Allocator::alloc`].
+  pub fn alloc(&mut self, size: Size) -> Option<Allocation> {
+    self.allocator.alloc(size)
+  }
+
+  /// Try to allocate a region with the provided size & alignment
+  ///
+  /// See [`Allocator::alloc_with_align`].
+  pub fn alloc_with_align(
+    &mut self,
+    size: Size,
+    align: Size,
+  ) -> Option<Allocation> {
+    self.allocator.alloc_with_align(size, align)
+  }
+
+  /// Try to allocate a region with the provided size, and get the slice of the
+  /// buffer it occupies
+  pub fn alloc_slice(
+    &mut self,
+    size: Size,
+  ) -> Option<(Allocation, &mut [u8])> {
+    let allocation = self.allocator.alloc(size)?;
+    Some((allocation, &mut self.buffer[allocation.range()]))
+  }
+
+  /// Free the given allocation
+  ///
+  /// The contents of the buffer are left untouched.
+  ///
+  /// See [`Allocator::free`].
+  pub fn free(&mut self, alloc: Allocation) {
+    self.allocator.free(alloc)
+  }
+
+  /// Get the slice of the buffer occupied by `alloc`
+  ///
+  /// Panics:
+  /// - Panics if `alloc` is out of bounds of the buffer
+  pub fn get(&self, alloc: Allocation) -> &[u8] {
+    &self.buffer[alloc.range()]
+  }
+
+  /// Get the mutable slice of the buffer occupied by `alloc`
+  ///
+  /// Panics:
+  /// - Panics if `alloc` is out of bounds of the buffer
+  pub fn get_mut(&mut self, alloc: Allocation) -> &mut [u8] {
+    &mut self.buffer[alloc.range()]
+  }
+
+  /// Get mutable slices of the buffer for several allocations at once
+  ///
+  /// Returns `None` if any of the allocations overlap, or are out of bounds of
+  /// the buffer.
+  pub fn get_disjoint_mut<const N: usize>(
+    &mut self,
+    allocs: [Allocation; N],
+  ) -> Option<[&mut [u8]; N]> {
+    self.buffer.get_disjoint_mut(allocs.map(|a| a.range())).ok()
+  }
+
+  /// Get the underlying [`Allocator`]
+  ///
+  /// This can be used to query e.g. the available space in the pool.
+  pub fn allocator(&self) -> &Allocator {
+    &self.allocator
+  }
+
+  /// Get the entire buffer
+  pub fn buffer(&self) -> &[u8] {
+    &self.buffer
+  }
+}
+
+impl fmt::Debug for BufferPool {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("BufferPool")
+      .field("allocator", &self.allocator)
+      .finish_non_exhaustive()
+  }
+}
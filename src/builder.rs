@@ -0,0 +1,158 @@
+use crate::{Allocator, Coloring, DropCheck, Placement, Size};
+
+/// Collects the configuration of an [`Allocator`], before creating it
+///
+/// See [`Allocator::builder`]. Every option defaults to the same value as an
+/// allocator created with [`Allocator::new`], and corresponds to one of the
+/// allocator's setters, so an allocator can also be reconfigured after it is
+/// built.
+///
+/// For example:
+/// ```
+/// # use ::orderly_allocator::{Allocator, Placement};
+/// let allocator = Allocator::builder(1 << 20)
+///   .min_align(256)
+///   .granularity(256)
+///   .placement(Placement::PreserveLargest)
+///   .watermark(90)
+///   .build();
+/// assert_eq!(allocator.min_align(), 256);
+/// assert_eq!(allocator.size_class(1), 256);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct AllocatorBuilder {
+  capacity: Size,
+  min_align: Size,
+  granularity: Size,
+  placement: Placement,
+  watermark: u8,
+  drop_check: DropCheck,
+  size_classes: Option<Size>,
+  exact_fit_first: bool,
+  coloring: Option<Coloring>,
+  #[cfg(feature = "wear-stats")]
+  wear_stripe: Option<Size>,
+}
+
+impl Allocator {
+  /// Configure a new allocator to manage a pool of memory
+  ///
+  /// This is an alternative to [`new`](Self::new) &
+  /// [`with_min_align`](Self::with_min_align) for allocators which need
+  /// several options set up front.
+  pub fn builder(capacity: Size) -> AllocatorBuilder {
+    AllocatorBuilder {
+      capacity,
+      min_align: 1,
+      granularity: 1,
+      placement: Placement::default(),
+      watermark: 100,
+      drop_check: DropCheck::default(),
+      size_classes: None,
+      exact_fit_first: true,
+      coloring: None,
+      #[cfg(feature = "wear-stats")]
+      wear_stripe: None,
+    }
+  }
+}
+
+impl AllocatorBuilder {
+  /// Align every allocation to at least `min_align`
+  ///
+  /// See [`Allocator::with_min_align`].
+  pub fn min_align(mut self, min_align: Size) -> Self {
+    self.min_align = min_align;
+    self
+  }
+
+  /// Round the size of every allocation up to a multiple of `granularity`
+  ///
+  /// See [`Allocator::set_granularity`].
+  pub fn granularity(mut self, granularity: Size) -> Self {
+    self.granularity = granularity;
+    self
+  }
+
+  /// Choose how free-regions are chosen to satisfy allocations
+  ///
+  /// See [`Allocator::set_placement`].
+  pub fn placement(mut self, placement: Placement) -> Self {
+    self.placement = placement;
+    self
+  }
+
+  /// Make ordinary allocations fail once they would take the used space
+  /// beyond `percent` of the capacity
+  ///
+  /// See [`Allocator::set_watermark`].
+  pub fn watermark(mut self, percent: u8) -> Self {
+    self.watermark = percent;
+    self
+  }
+
+  /// Choose what happens if the allocator is dropped while allocations are
+  /// still outstanding
+  ///
+  /// See [`Allocator::set_drop_check`].
+  pub fn drop_check(mut self, drop_check: DropCheck) -> Self {
+    self.drop_check = drop_check;
+    self
+  }
+
+  /// Round the sizes of allocations up to one of `steps` size classes per
+  /// power of two
+  ///
+  /// See [`Allocator::set_size_classes`].
+  pub fn size_classes(mut self, steps: Option<Size>) -> Self {
+    self.size_classes = steps;
+    self
+  }
+
+  /// Choose whether aligned allocations first look for an aligned exact fit
+  ///
+  /// See [`Allocator::set_exact_fit_first`].
+  pub fn exact_fit_first(mut self, exact_fit_first: bool) -> Self {
+    self.exact_fit_first = exact_fit_first;
+    self
+  }
+
+  /// Offset successive allocations of the same size from one another
+  ///
+  /// See [`Allocator::set_coloring`].
+  pub fn coloring(mut self, coloring: Option<Coloring>) -> Self {
+    self.coloring = coloring;
+    self
+  }
+
+  /// Count how many times each `stripe`-sized stripe of the pool is allocated
+  ///
+  /// See [`Allocator::enable_wear_stats`].
+  #[cfg(feature = "wear-stats")]
+  pub fn wear_stats(mut self, stripe: Size) -> Self {
+    self.wear_stripe = Some(stripe);
+    self
+  }
+
+  /// Create the configured allocator
+  ///
+  /// Panics:
+  /// - Panics if any of the options is invalid, as documented by the
+  ///   corresponding [`Allocator`] method
+  pub fn build(self) -> Allocator {
+    let mut allocator =
+      Allocator::with_min_align(self.capacity, self.min_align);
+    allocator.set_granularity(self.granularity);
+    allocator.set_placement(self.placement);
+    allocator.set_watermark(self.watermark);
+    allocator.set_drop_check(self.drop_check);
+    allocator.set_size_classes(self.size_classes);
+    allocator.set_exact_fit_first(self.exact_fit_first);
+    allocator.set_coloring(self.coloring);
+    #[cfg(feature = "wear-stats")]
+    if let Some(stripe) = self.wear_stripe {
+      allocator.enable_wear_stats(stripe);
+    }
+    allocator
+  }
+}
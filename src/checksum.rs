@@ -0,0 +1,32 @@
+/// The lookup table for [`crc32`], for the reflected IEEE polynomial
+const TABLE: [u32; 256] = {
+  let mut table = [0; 256];
+  let mut index = 0;
+  while index < 256 {
+    let mut crc = index as u32;
+    let mut bit = 0;
+    while bit < 8 {
+      crc = if crc & 1 == 1 {
+        crc >> 1 ^ 0xEDB8_8320
+      } else {
+        crc >> 1
+      };
+      bit += 1;
+    }
+    table[index] = crc;
+    index += 1;
+  }
+  table
+};
+
+/// Compute the CRC-32 (as used by zlib, PNG, etc.) of the concatenation of
+/// `parts`
+///
+/// This is used to detect torn writes & bit flips in persisted state; it is
+/// not resistant to deliberate tampering.
+pub(crate) fn crc32<'a>(parts: impl IntoIterator<Item = &'a [u8]>) -> u32 {
+  let crc = parts.into_iter().flatten().fold(!0, |crc: u32, &byte| {
+    TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ crc >> 8
+  });
+  !crc
+}
@@ -0,0 +1,107 @@
+use {
+  crate::Allocator,
+  ::core::{
+    ops::Sub,
+    sync::atomic::{AtomicUsize, Ordering::Relaxed},
+  },
+};
+
+/// Cumulative measures of the work done by an [`Allocator`]'s operations
+///
+/// See [`Allocator::op_costs`]. Subtract the costs from before an operation
+/// from those after it to get the cost of that one operation:
+/// ```
+/// # use ::orderly_allocator::Allocator;
+/// let mut allocator = Allocator::new(1_000);
+/// let before = allocator.op_costs();
+/// let allocation = allocator.alloc(100).unwrap();
+/// let cost = allocator.op_costs() - before;
+/// assert_eq!(cost.splits, 1);
+/// # allocator.free(allocation);
+/// ```
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub struct OpCosts {
+  /// The number of searches of the free-lists
+  ///
+  /// Each search descends one B-tree from its root, visiting `O(log n)` nodes
+  /// for `n` free-regions.
+  pub lookups: usize,
+  /// The number of free-regions examined by searches
+  ///
+  /// This is usually about one per lookup; growth here means searches are
+  /// scanning past unsuitable regions, e.g. for large alignments.
+  pub regions_visited: usize,
+  /// The number of free-regions inserted into or removed from the free-lists
+  pub tree_updates: usize,
+  /// The number of allocations carved out of larger free-regions, see
+  /// [`Stats::splits`](crate::Stats::splits)
+  pub splits: u64,
+}
+
+impl Sub for OpCosts {
+  type Output = OpCosts;
+
+  fn sub(self, earlier: OpCosts) -> OpCosts {
+    OpCosts {
+      lookups: self.lookups.wrapping_sub(earlier.lookups),
+      regions_visited: self
+        .regions_visited
+        .wrapping_sub(earlier.regions_visited),
+      tree_updates: self.tree_updates.wrapping_sub(earlier.tree_updates),
+      splits: self.splits.wrapping_sub(earlier.splits),
+    }
+  }
+}
+
+/// The counters behind [`OpCosts`]
+///
+/// Searches only borrow the allocator immutably, so these are atomics.
+#[derive(Debug, Default)]
+pub(crate) struct CostCounters {
+  lookups: AtomicUsize,
+  regions_visited: AtomicUsize,
+  tree_updates: AtomicUsize,
+}
+
+impl Clone for CostCounters {
+  fn clone(&self) -> Self {
+    CostCounters {
+      lookups: self.lookups.load(Relaxed).into(),
+      regions_visited: self.regions_visited.load(Relaxed).into(),
+      tree_updates: self.tree_updates.load(Relaxed).into(),
+    }
+  }
+}
+
+impl Allocator {
+  /// Get the cumulative costs of the operations performed so far
+  ///
+  /// This is intended for profiling, e.g. to find out which operations
+  /// degrade as fragmentation grows when chasing tail latency. See
+  /// [`OpCosts`].
+  pub fn op_costs(&self) -> OpCosts {
+    OpCosts {
+      lookups: self.costs.lookups.load(Relaxed),
+      regions_visited: self.costs.regions_visited.load(Relaxed),
+      tree_updates: self.costs.tree_updates.load(Relaxed),
+      splits: self.stats().splits,
+    }
+  }
+
+  /// Record a search of the free-lists which examined `visited` free-regions
+  pub(crate) fn count_lookup(&self, visited: usize) {
+    self.costs.lookups.fetch_add(1, Relaxed);
+    self.count_visited(visited);
+  }
+
+  /// Record a search examining `visited` more free-regions
+  pub(crate) fn count_visited(&self, visited: usize) {
+    self.costs.regions_visited.fetch_add(visited, Relaxed);
+  }
+
+  /// Record a free-region being inserted into or removed from the free-lists
+  pub(crate) fn count_tree_update(&self) {
+    self.costs.tree_updates.fetch_add(1, Relaxed);
+  }
+}
@@ -0,0 +1,190 @@
+use {
+  crate::{Allocation, Allocator, Location, Placement, Size},
+  ::alloc::collections::BTreeMap,
+  ::core::{fmt, num::NonZero},
+};
+
+/// Which zone of a [`DualZoneAllocator`] an allocation belongs to
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Zone {
+  /// Long-lived data, packed upwards from the start of the pool
+  Persistent,
+  /// Short-lived data, packed downwards from the end of the pool
+  Transient,
+}
+
+/// An [`Allocator`] which manages the pool as two zones, with a movable
+/// boundary between them
+///
+/// Persistent allocations grow up from the start of the pool, and transient
+/// allocations grow down from the end, so the two never interleave &
+/// fragment each other's space. Every persistent allocation is below the
+/// boundary, and every transient allocation is at or above it.
+///
+/// The boundary moves automatically under pressure; when a zone can't fit an
+/// allocation on its own side, it takes free space across the boundary, as
+/// far as the other zone's nearest allocation.
+#[derive(Clone)]
+pub struct DualZoneAllocator {
+  allocator: Allocator,
+  boundary: Location,
+  /// The live persistent allocations, sorted by location
+  persistent: BTreeMap<Location, Size>,
+  /// The live transient allocations, sorted by location
+  transient: BTreeMap<Location, Size>,
+}
+
+impl DualZoneAllocator {
+  /// Create a new allocator to manage a pool of memory, initially split at
+  /// `boundary`
+  ///
+  /// Panics:
+  /// - Panics if `capacity == 0`
+  /// - Panics if `boundary > capacity`
+  pub fn new(capacity: Size, boundary: Location) -> Self {
+    assert!(boundary <= capacity, "`boundary > capacity`");
+
+    DualZoneAllocator {
+      allocator: Allocator::new(capacity),
+      boundary,
+      persistent: BTreeMap::new(),
+      transient: BTreeMap::new(),
+    }
+  }
+
+  /// Try to allocate a region in the given zone, with the provided size &
+  /// alignment
+  ///
+  /// Persistent allocations take the lowest suitable location, and transient
+  /// allocations the highest, see [`Placement::Hot`] & [`Placement::Cold`].
+  /// If that is across the boundary, the boundary is moved to make room.
+  ///
+  /// Returns `None` if:
+  /// - there is no free-region which can fit the aligned allocation without
+  ///   crossing an allocation of the other zone, or
+  /// - `size == 0`, or
+  /// - `align == 0`.
+  pub fn alloc(
+    &mut self,
+    size: Size,
+    align: Size,
+    zone: Zone,
+  ) -> Option<Allocation> {
+    let placement = match zone {
+      Zone::Persistent => Placement::Hot,
+      Zone::Transient => Placement::Cold,
+    };
+    let allocation = self
+      .allocator
+      .alloc_with_placement(size, align, placement)?;
+    let start = allocation.offset;
+    let end = start + allocation.size.get();
+
+    // note: the lowest (or highest) suitable location is already taken, so if
+    // it crosses the other zone there is no room anywhere
+    let fits = match zone {
+      Zone::Persistent => {
+        end <= self.boundary
+          || self
+            .transient
+            .first_key_value()
+            .is_none_or(|(&location, _)| end <= location)
+      },
+      Zone::Transient => {
+        start >= self.boundary
+          || self
+            .persistent
+            .last_key_value()
+            .is_none_or(|(&location, &size)| location + size <= start)
+      },
+    };
+    if !fits {
+      self.allocator.free(allocation);
+      return None;
+    }
+
+    match zone {
+      Zone::Persistent => {
+        self.boundary = self.boundary.max(end);
+        self.persistent.insert(start, allocation.size());
+      },
+      Zone::Transient => {
+        self.boundary = self.boundary.min(start);
+        self.transient.insert(start, allocation.size());
+      },
+    }
+    Some(allocation)
+  }
+
+  /// Free the given allocation
+  ///
+  /// Panics:
+  /// - Panics if the allocation was not allocated by this allocator, or has
+  ///   already been freed.
+  pub fn free(&mut self, alloc: Allocation) {
+    let zone = if alloc.offset < self.boundary {
+      &mut self.persistent
+    } else {
+      &mut self.transient
+    };
+    let tracked = zone.remove(&alloc.offset);
+    assert_eq!(tracked, Some(alloc.size()), "not a live allocation");
+    self.allocator.free(alloc);
+  }
+
+  /// Free ***all*** transient allocations, e.g. at the end of a frame
+  pub fn free_transient(&mut self) {
+    for (offset, size) in ::core::mem::take(&mut self.transient) {
+      let size = NonZero::new(size).unwrap_or_else(|| unreachable!());
+      self.allocator.free(Allocation { offset, size });
+    }
+  }
+
+  /// Get the zone of the live allocation at `offset`
+  ///
+  /// Returns `None` if there is no live allocation at `offset`.
+  pub fn zone(&self, offset: Location) -> Option<Zone> {
+    if self.persistent.contains_key(&offset) {
+      Some(Zone::Persistent)
+    } else if self.transient.contains_key(&offset) {
+      Some(Zone::Transient)
+    } else {
+      None
+    }
+  }
+
+  /// Get the location of the boundary between the zones
+  ///
+  /// Every persistent allocation is below this, and every transient
+  /// allocation is at or above it.
+  pub fn boundary(&self) -> Location {
+    self.boundary
+  }
+
+  /// Get the total size of the live allocations in the given zone
+  pub fn used(&self, zone: Zone) -> Size {
+    let allocations = match zone {
+      Zone::Persistent => &self.persistent,
+      Zone::Transient => &self.transient,
+    };
+    allocations.values().sum()
+  }
+
+  /// Get the underlying [`Allocator`]
+  ///
+  /// This can be used to query e.g. the available space in the pool.
+  pub fn allocator(&self) -> &Allocator {
+    &self.allocator
+  }
+}
+
+impl fmt::Debug for DualZoneAllocator {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("DualZoneAllocator")
+      .field("boundary", &self.boundary)
+      .field("persistent", &self.persistent.len())
+      .field("transient", &self.transient.len())
+      .field("allocator", &self.allocator)
+      .finish()
+  }
+}
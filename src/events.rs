@@ -0,0 +1,55 @@
+use {
+  crate::{Allocation, Allocator, Size},
+  ::std::sync::mpsc::Sender,
+};
+
+/// An operation carried out by an [`Allocator`], see
+/// [`Allocator::set_event_sender`]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum AllocatorEvent {
+  /// An allocation was made
+  Alloc(Allocation),
+  /// An allocation with the given size & alignment could not be satisfied
+  Failed { size: Size, align: Size },
+  /// An allocation was freed
+  Free(Allocation),
+  /// An allocation was re-sized in-place
+  Reallocate { old: Allocation, new: Allocation },
+  /// All allocations were freed
+  Reset,
+}
+
+impl Allocator {
+  /// Send an [`AllocatorEvent`] to `sender` for every subsequent operation,
+  /// or stop sending events if `sender` is `None`
+  ///
+  /// This allows a monitoring thread to consume allocator telemetry without
+  /// locking the allocator itself. Sending never blocks; events are dropped
+  /// if the receiver has hung up.
+  ///
+  /// For example:
+  /// ```
+  /// # use {
+  /// #   ::orderly_allocator::{Allocator, AllocatorEvent},
+  /// #   ::std::sync::mpsc::channel,
+  /// # };
+  /// let (sender, receiver) = channel();
+  /// let mut allocator = Allocator::new(1_000);
+  /// allocator.set_event_sender(Some(sender));
+  ///
+  /// let allocation = allocator.alloc(100).unwrap();
+  /// assert_eq!(receiver.try_recv(), Ok(AllocatorEvent::Alloc(allocation)));
+  /// # allocator.free(allocation);
+  /// ```
+  pub fn set_event_sender(&mut self, sender: Option<Sender<AllocatorEvent>>) {
+    self.events = sender;
+  }
+
+  pub(crate) fn emit(&self, event: AllocatorEvent) {
+    if let Some(sender) = &self.events {
+      // note: a hung-up receiver just means nobody is listening
+      let _ = sender.send(event);
+    }
+  }
+}
@@ -0,0 +1,194 @@
+//! A C interface to [`Allocator`]
+//!
+//! The matching header is at `include/orderly_allocator.h`, and is generated
+//! with [cbindgen](https://github.com/mozilla/cbindgen):
+//! ```text
+//! cbindgen --config cbindgen.toml --output include/orderly_allocator.h
+//! ```
+//!
+//! To link the allocator into a C/C++ project, build it as a static or dynamic
+//! library, e.g.:
+//! ```text
+//! cargo rustc --release --features ffi --crate-type staticlib
+//! ```
+//!
+//! Note: A panic cannot unwind across these functions, so anything which would
+//! panic in Rust (e.g. a double free) aborts the process instead.
+
+use {
+  crate::{Allocation, Allocator},
+  ::alloc::boxed::Box,
+  ::core::{num::NonZero, ptr},
+};
+
+/// An opaque handle to an allocator
+///
+/// Created by [`orderly_allocator_create`], and destroyed by
+/// [`orderly_allocator_destroy`].
+pub struct OrderlyAllocator(Allocator);
+
+/// An allocation, or the failure to allocate
+///
+/// A `size` of 0 indicates failure.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct OrderlyAllocation {
+  /// The location of this allocation within the pool
+  pub offset: u32,
+  /// The size of this allocation, or 0 on failure
+  pub size: u32,
+}
+
+/// A summary of the state of an allocator
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct OrderlyStats {
+  /// The total capacity of the pool
+  pub capacity: u32,
+  /// The total free space in the pool
+  pub total_available: u32,
+  /// The size of the largest free-region in the pool
+  pub largest_available: u32,
+}
+
+impl From<Option<Allocation>> for OrderlyAllocation {
+  fn from(allocation: Option<Allocation>) -> Self {
+    allocation.map_or(OrderlyAllocation::default(), |allocation| {
+      OrderlyAllocation {
+        offset: allocation.offset,
+        size: allocation.size(),
+      }
+    })
+  }
+}
+
+impl From<OrderlyAllocation> for Option<Allocation> {
+  fn from(allocation: OrderlyAllocation) -> Self {
+    Some(Allocation {
+      offset: allocation.offset,
+      size: NonZero::new(allocation.size)?,
+    })
+  }
+}
+
+/// Create a new allocator to manage a pool of `capacity` bytes
+///
+/// Returns null if `capacity == 0`.
+#[no_mangle]
+pub extern "C" fn orderly_allocator_create(
+  capacity: u32,
+) -> *mut OrderlyAllocator {
+  if capacity == 0 {
+    return ptr::null_mut();
+  }
+  Box::into_raw(Box::new(OrderlyAllocator(Allocator::new(capacity))))
+}
+
+/// Destroy an allocator
+///
+/// Does nothing if `allocator` is null.
+///
+/// # Safety
+///
+/// `allocator` must be null, or a pointer returned by
+/// [`orderly_allocator_create`] which has not already been destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn orderly_allocator_destroy(
+  allocator: *mut OrderlyAllocator,
+) {
+  if !allocator.is_null() {
+    // SAFETY: Upheld by the caller
+    drop(unsafe { Box::from_raw(allocator) });
+  }
+}
+
+/// Try to allocate a region with the provided size & alignment
+///
+/// Returns an allocation with a `size` of 0 on failure.
+///
+/// # Safety
+///
+/// `allocator` must be a live pointer returned by [`orderly_allocator_create`].
+#[no_mangle]
+pub unsafe extern "C" fn orderly_allocator_alloc(
+  allocator: *mut OrderlyAllocator,
+  size: u32,
+  align: u32,
+) -> OrderlyAllocation {
+  // SAFETY: Upheld by the caller
+  let allocator = unsafe { &mut (*allocator).0 };
+  allocator.alloc_with_align(size, align).into()
+}
+
+/// Free the given allocation
+///
+/// Does nothing if the allocation's `size` is 0.
+///
+/// # Safety
+///
+/// `allocator` must be a live pointer returned by [`orderly_allocator_create`].
+#[no_mangle]
+pub unsafe extern "C" fn orderly_allocator_free(
+  allocator: *mut OrderlyAllocator,
+  allocation: OrderlyAllocation,
+) {
+  // SAFETY: Upheld by the caller
+  let allocator = unsafe { &mut (*allocator).0 };
+  if let Some(allocation) = allocation.into() {
+    allocator.free(allocation);
+  }
+}
+
+/// Re-size an allocation, moving it if it cannot be re-sized in-place
+///
+/// If the returned allocation has a different offset, the caller is
+/// responsible for moving the contents; the old region has already been
+/// freed. The allocator never touches the pool's memory, so the contents of
+/// the old region remain intact until the next allocation.
+///
+/// Returns an allocation with a `size` of 0 on failure, in which case the
+/// original allocation is left untouched.
+///
+/// # Safety
+///
+/// `allocator` must be a live pointer returned by [`orderly_allocator_create`].
+#[no_mangle]
+pub unsafe extern "C" fn orderly_allocator_realloc(
+  allocator: *mut OrderlyAllocator,
+  allocation: OrderlyAllocation,
+  new_size: u32,
+  align: u32,
+) -> OrderlyAllocation {
+  // SAFETY: Upheld by the caller
+  let allocator = unsafe { &mut (*allocator).0 };
+  let Some(old) = Option::<Allocation>::from(allocation) else {
+    return allocator.alloc_with_align(new_size, align).into();
+  };
+
+  if let Ok(new) = allocator.try_reallocate(old, new_size) {
+    return Some(new).into();
+  }
+  let new = allocator.alloc_with_align(new_size, align);
+  if new.is_some() {
+    allocator.free(old);
+  }
+  new.into()
+}
+
+/// Get a summary of the state of the allocator
+///
+/// # Safety
+///
+/// `allocator` must be a live pointer returned by [`orderly_allocator_create`].
+#[no_mangle]
+pub unsafe extern "C" fn orderly_allocator_stats(
+  allocator: *const OrderlyAllocator,
+) -> OrderlyStats {
+  // SAFETY: Upheld by the caller
+  let allocator = unsafe { &(*allocator).0 };
+  OrderlyStats {
+    capacity: allocator.capacity(),
+    total_available: allocator.total_available(),
+    largest_available: allocator.largest_available(),
+  }
+}
@@ -0,0 +1,203 @@
+use {
+  crate::{checksum::crc32, Allocator, FreeRegion, Location, Size},
+  ::alloc::vec::Vec,
+  ::core::{error::Error, fmt, num::NonZero},
+};
+
+/// Identifies a file header; "OAFH" in little-endian
+const MAGIC: u32 = u32::from_le_bytes(*b"OAFH");
+/// The version of the header's layout
+const VERSION: u32 = 2;
+/// The size of the header's fixed fields; magic, version, capacity, the
+/// number of free-extents & a checksum
+const FIXED_LEN: usize = 20;
+/// The location of the checksum within the header
+const CHECKSUM: usize = 16;
+/// The size of each free-extent; its offset & size
+const EXTENT_LEN: usize = 8;
+
+impl Allocator {
+  /// Get the size of a file header holding `free_regions` free-extents
+  ///
+  /// See [`write_file_header`](Self::write_file_header).
+  pub const fn file_header_len(free_regions: usize) -> usize {
+    FIXED_LEN.saturating_add(free_regions.saturating_mul(EXTENT_LEN))
+  }
+
+  /// Write the free-extent table into a fixed-size header region, e.g. at the
+  /// start of a database or asset-pack file
+  ///
+  /// The table is written as little-endian `u32`s; a magic number, a version,
+  /// the capacity, the number of free-extents, a CRC-32 of the rest of the
+  /// table, & then the offset & size of each free-extent in order of
+  /// location. The rest of `header` is zeroed. Returns the number of bytes
+  /// used.
+  ///
+  /// Note: Frees deferred by
+  /// [`set_deferred_coalescing`](Self::set_deferred_coalescing) are not
+  /// included; call [`coalesce_all`](Self::coalesce_all) first.
+  ///
+  /// Returns `Err(FileHeaderError::TooSmall)` if `header` can't fit the
+  /// table, see [`file_header_len`](Self::file_header_len).
+  pub fn write_file_header(
+    &self,
+    header: &mut [u8],
+  ) -> Result<usize, FileHeaderError> {
+    let len = Self::file_header_len(self.location_map.len());
+    if header.len() < len {
+      return Err(FileHeaderError::TooSmall { required: len });
+    }
+
+    let fixed = [
+      MAGIC,
+      VERSION,
+      self.capacity.get(),
+      self.location_map.len() as u32,
+      0,
+    ];
+    let extents = self
+      .location_map
+      .iter()
+      .flat_map(|(&location, &size)| [location, size.get()]);
+    for (chunk, word) in header
+      .chunks_exact_mut(4)
+      .zip(fixed.into_iter().chain(extents))
+    {
+      chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    header[len..].fill(0);
+    let checksum = table_checksum(&header[..len]);
+    header[CHECKSUM..CHECKSUM + 4].copy_from_slice(&checksum.to_le_bytes());
+
+    Ok(len)
+  }
+
+  /// Read an allocator back from a header written by
+  /// [`write_file_header`](Self::write_file_header)
+  ///
+  /// `file_len` is the current length of the file; a capacity beyond the end
+  /// of the file means it has been truncated. The table's checksum is
+  /// verified, to catch torn writes & bit flips, & the free-extents are
+  /// checked to be in order, within the capacity, & fully coalesced. Other
+  /// settings, e.g. the [placement](Self::set_placement), are not stored, so
+  /// the allocator starts with the defaults.
+  ///
+  /// Returns:
+  /// - `Err(FileHeaderError::TooSmall)` if `header` ends part-way through the
+  ///   table.
+  /// - `Err(FileHeaderError::Unrecognized)` if `header` doesn't start with a
+  ///   header of a supported version.
+  /// - `Err(FileHeaderError::Truncated)` if the capacity is beyond
+  ///   `file_len`.
+  /// - `Err(FileHeaderError::ChecksumMismatch)` if the table has been
+  ///   damaged.
+  /// - `Err(FileHeaderError::Corrupt)` if the free-extents are invalid.
+  pub fn read_file_header(
+    header: &[u8],
+    file_len: u64,
+  ) -> Result<Allocator, FileHeaderError> {
+    if header.len() < FIXED_LEN {
+      return Err(FileHeaderError::TooSmall {
+        required: FIXED_LEN,
+      });
+    }
+    // note: the length of `header` is checked before reading each word
+    let word = |index: usize| {
+      let bytes = &header[index * 4..index * 4 + 4];
+      Size::from_le_bytes(bytes.try_into().unwrap_or_else(|_| unreachable!()))
+    };
+    if word(0) != MAGIC || word(1) != VERSION {
+      return Err(FileHeaderError::Unrecognized);
+    }
+    let count = word(3) as usize;
+    let len = Self::file_header_len(count);
+    if header.len() < len {
+      return Err(FileHeaderError::TooSmall { required: len });
+    }
+    if word(CHECKSUM / 4) != table_checksum(&header[..len]) {
+      return Err(FileHeaderError::ChecksumMismatch);
+    }
+    let capacity = NonZero::new(word(2)).ok_or(FileHeaderError::Corrupt)?;
+    if capacity.get() as u64 > file_len {
+      return Err(FileHeaderError::Truncated {
+        capacity: capacity.get(),
+        file_len,
+      });
+    }
+
+    let mut regions: Vec<FreeRegion> = Vec::with_capacity(count);
+    let mut available: Size = 0;
+    for index in 0..count {
+      let location: Location = word(5 + 2 * index);
+      let size =
+        NonZero::new(word(6 + 2 * index)).ok_or(FileHeaderError::Corrupt)?;
+      location
+        .checked_add(size.get())
+        .filter(|&end| end <= capacity.get())
+        .ok_or(FileHeaderError::Corrupt)?;
+      // note: neighbouring free-regions are always coalesced, so must be
+      // separated by at least one allocated byte
+      if regions.last().is_some_and(|previous| {
+        previous.location + previous.size.get() >= location
+      }) {
+        return Err(FileHeaderError::Corrupt);
+      }
+      regions.push(FreeRegion { location, size });
+      available += size.get();
+    }
+
+    let mut allocator = Allocator::new(capacity.get());
+    allocator.available = available;
+    allocator.replace_free_regions(regions);
+    Ok(allocator)
+  }
+}
+
+/// Compute the checksum of a header's table, skipping the checksum itself
+fn table_checksum(table: &[u8]) -> u32 {
+  crc32([&table[..CHECKSUM], &table[CHECKSUM + 4..]])
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum FileHeaderError {
+  /// The header region is too small for the table
+  TooSmall {
+    /// The number of bytes needed
+    required: usize,
+  },
+  /// The header region doesn't hold a supported file header
+  Unrecognized,
+  /// The file is shorter than the capacity stored in its header
+  Truncated { capacity: Size, file_len: u64 },
+  /// The table's checksum doesn't match, e.g. after a torn write
+  ChecksumMismatch,
+  /// The free-extent table is invalid
+  Corrupt,
+}
+
+impl Error for FileHeaderError {}
+impl fmt::Display for FileHeaderError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      FileHeaderError::TooSmall { required } => f.write_fmt(format_args!(
+        "TooSmall Error: The header region must be at least {required} bytes"
+      )),
+      FileHeaderError::Unrecognized => {
+        f.write_str("Unrecognized Error: Not a supported file header")
+      },
+      FileHeaderError::Truncated { capacity, file_len } => {
+        f.write_fmt(format_args!(
+          "Truncated Error: The file is {file_len} bytes, but the header \
+            describes {capacity} bytes"
+        ))
+      },
+      FileHeaderError::ChecksumMismatch => f.write_str(
+        "ChecksumMismatch Error: The header has been damaged, e.g. by a torn \
+          write",
+      ),
+      FileHeaderError::Corrupt => {
+        f.write_str("Corrupt Error: The free-extent table is invalid")
+      },
+    }
+  }
+}
@@ -0,0 +1,427 @@
+use {
+  crate::{Allocation, Location, Size},
+  ::core::{error::Error, fmt, num::NonZero},
+};
+
+/// A heap-free, panic-free variant of [`Allocator`](crate::Allocator)
+///
+/// Rather than BTrees, the free-regions are kept in fixed-size arrays sorted by
+/// location, so at most `N` separate free-regions can exist at once. Requests
+/// which would need more are refused rather than touching the heap.
+///
+/// Searches are linear in the number of free-regions, so this is best suited
+/// to small `N`. This type can be constructed in a `const` context, which makes
+/// it suitable for statically-declared pools.
+#[derive(Clone)]
+pub struct FixedAllocator<const N: usize> {
+  /// The locations of the free-regions, sorted in ascending order
+  ///
+  /// Only the first `len` entries are meaningful.
+  locations: [Location; N],
+  /// The sizes of the free-regions, corresponding to `locations`
+  sizes: [Size; N],
+  /// The number of free-regions
+  len: usize,
+  /// The total capacity
+  capacity: Size,
+  /// The amount of free memory
+  available: Size,
+}
+
+impl<const N: usize> FixedAllocator<N> {
+  /// Create a new allocator to manage a pool of memory
+  ///
+  /// A `capacity` of 0 results in an allocator which can never allocate.
+  pub const fn new(capacity: Size) -> Self {
+    let mut allocator = FixedAllocator {
+      locations: [0; N],
+      sizes: [0; N],
+      len: 0,
+      capacity,
+      available: 0,
+    };
+    if capacity > 0 && N > 0 {
+      allocator.sizes[0] = capacity;
+      allocator.len = 1;
+      allocator.available = capacity;
+    }
+    allocator
+  }
+
+  /// Try to allocate a region with the provided size
+  ///
+  /// Uses a *best-fit* strategy.
+  ///
+  /// Returns `None` if:
+  /// - there is no free-region large enough, or
+  /// - the allocation would need to split a free-region, but there is no room
+  ///   left to track the extra free-region, or
+  /// - `size == 0`.
+  pub fn alloc(&mut self, size: Size) -> Option<Allocation> {
+    self.alloc_with_align(size, 1)
+  }
+
+  /// Try to allocate a region with the provided size & alignment
+  ///
+  /// Uses a *best-fit* strategy, choosing the smallest free-region which can
+  /// fit the aligned allocation.
+  ///
+  /// Returns `None` if:
+  /// - there is no free-region large enough, or
+  /// - the allocation would need to split a free-region, but there is no room
+  ///   left to track the extra free-region, or
+  /// - `size == 0`, or
+  /// - `align == 0`.
+  pub fn alloc_with_align(
+    &mut self,
+    size: Size,
+    align: Size,
+  ) -> Option<Allocation> {
+    let size = NonZero::new(size)?;
+    let align = NonZero::new(align)?;
+    let (index, offset) = self.find_free_region(size, align)?;
+
+    self.carve(index, offset, size)?;
+    self.available -= size.get();
+
+    Some(Allocation { offset, size })
+  }
+
+  /// Free the given allocation
+  ///
+  /// Returns:
+  /// - `Err(FixedFreeError::Invalid)` if the allocation is out of bounds, or
+  ///   overlaps a free-region; i.e. a double free.
+  /// - `Err(FixedFreeError::TableFull)` if the allocation cannot be coalesced
+  ///   with any neighbouring free-region, and there is no room left to track
+  ///   another free-region. In this case the allocation is leaked.
+  pub fn free(&mut self, alloc: Allocation) -> Result<(), FixedFreeError> {
+    let offset = alloc.offset;
+    let size = alloc.size.get();
+    let end = offset
+      .checked_add(size)
+      .filter(|&end| end <= self.capacity)
+      .ok_or(FixedFreeError::Invalid)?;
+
+    let index = self.locations[..self.len].partition_point(|&l| l < offset);
+    let previous = index
+      .checked_sub(1)
+      .map(|i| (self.locations[i], self.sizes[i]));
+    let following =
+      (index < self.len).then(|| (self.locations[index], self.sizes[index]));
+
+    let coalesce_previous = match previous {
+      Some((location, size)) if location + size > offset => {
+        return Err(FixedFreeError::Invalid);
+      },
+      Some((location, size)) => location + size == offset,
+      None => false,
+    };
+    let coalesce_following = match following {
+      Some((location, _)) if location < end => {
+        return Err(FixedFreeError::Invalid);
+      },
+      Some((location, _)) => location == end,
+      None => false,
+    };
+
+    match (coalesce_previous, coalesce_following) {
+      (true, true) => {
+        self.sizes[index - 1] += size + self.sizes[index];
+        self.remove(index);
+      },
+      (true, false) => self.sizes[index - 1] += size,
+      (false, true) => {
+        self.locations[index] = offset;
+        self.sizes[index] += size;
+      },
+      (false, false) => {
+        if !self.insert(index, offset, size) {
+          return Err(FixedFreeError::TableFull);
+        }
+      },
+    }
+    self.available += size;
+
+    Ok(())
+  }
+
+  /// Add new free space at the end of the allocator
+  ///
+  /// Returns:
+  /// - `Err(FixedFreeError::Invalid)` if `self.capacity + additional` would
+  ///   overflow.
+  /// - `Err(FixedFreeError::TableFull)` if the new space cannot be coalesced
+  ///   with the last free-region, and there is no room left to track another
+  ///   free-region.
+  ///
+  /// In either error case the allocator is left unchanged.
+  pub fn grow_capacity(
+    &mut self,
+    additional: Size,
+  ) -> Result<(), FixedFreeError> {
+    let Some(additional) = NonZero::new(additional) else {
+      return Ok(()); // `additional` is zero, so do nothing
+    };
+    let offset = self.capacity;
+    self.capacity = offset
+      .checked_add(additional.get())
+      .ok_or(FixedFreeError::Invalid)?;
+
+    self
+      .free(Allocation {
+        offset,
+        size: additional,
+      })
+      .inspect_err(|_| self.capacity = offset)
+  }
+
+  /// Free ***all*** allocations
+  pub fn reset(&mut self) {
+    *self = Self::new(self.capacity);
+  }
+
+  /// Get the total capacity of the pool
+  pub fn capacity(&self) -> Size {
+    self.capacity
+  }
+
+  /// Get the total available memory in this pool
+  ///
+  /// Note: The memory may be fragmented, so it may not be possible to allocate
+  /// an object of this size.
+  pub fn total_available(&self) -> Size {
+    self.available
+  }
+
+  /// Get the size of the largest available memory region in this pool
+  pub fn largest_available(&self) -> Size {
+    self.sizes[..self.len].iter().copied().max().unwrap_or(0)
+  }
+
+  /// Returns true if there are no allocations
+  pub fn is_empty(&self) -> bool {
+    self.capacity == self.available
+  }
+
+  /// Returns an iterator over the unallocated regions, in order of location
+  ///
+  /// This should be used **only** for gathering metadata about the internal
+  /// state of the allocator for debugging purposes.
+  pub fn report_free_regions(
+    &self,
+  ) -> impl DoubleEndedIterator<Item = Allocation> + ExactSizeIterator + use<'_, N>
+  {
+    self.locations[..self.len]
+      .iter()
+      .zip(&self.sizes[..self.len])
+      .map(|(&offset, &size)| Allocation {
+        offset,
+        size: NonZero::new(size).unwrap_or_else(|| unreachable!()),
+      })
+  }
+
+  /// Find the smallest free-region which can fit an allocation with the given
+  /// size & alignment
+  ///
+  /// Returns the index of the free-region, and the aligned offset of the
+  /// allocation within it.
+  fn find_free_region(
+    &self,
+    size: NonZero<Size>,
+    align: NonZero<Size>,
+  ) -> Option<(usize, Location)> {
+    best_fit(
+      &self.locations[..self.len],
+      &self.sizes[..self.len],
+      size,
+      align,
+    )
+  }
+
+  /// Remove `offset..offset + size` from the free-region at `index`
+  ///
+  /// Returns `None`, without modifying anything, if the remaining free-space
+  /// would need more free-regions than there is room for.
+  fn carve(
+    &mut self,
+    index: usize,
+    offset: Location,
+    size: NonZero<Size>,
+  ) -> Option<()> {
+    let location = self.locations[index];
+    let end = location + self.sizes[index];
+    let leading = offset - location;
+    let trailing = end - (offset + size.get());
+
+    match (leading > 0, trailing > 0) {
+      (false, false) => self.remove(index),
+      (true, false) => self.sizes[index] = leading,
+      (false, true) => {
+        self.locations[index] = offset + size.get();
+        self.sizes[index] = trailing;
+      },
+      (true, true) => {
+        if !self.insert(index + 1, offset + size.get(), trailing) {
+          return None;
+        }
+        self.sizes[index] = leading;
+      },
+    }
+
+    Some(())
+  }
+
+  /// Insert a free-region at `index`, shifting later free-regions along
+  ///
+  /// Returns false if there is no room for another free-region.
+  fn insert(&mut self, index: usize, location: Location, size: Size) -> bool {
+    if self.len == N {
+      return false;
+    }
+    self.locations.copy_within(index..self.len, index + 1);
+    self.sizes.copy_within(index..self.len, index + 1);
+    self.locations[index] = location;
+    self.sizes[index] = size;
+    self.len += 1;
+    true
+  }
+
+  /// Remove the free-region at `index`, shifting later free-regions back
+  fn remove(&mut self, index: usize) {
+    self.locations.copy_within(index + 1..self.len, index);
+    self.sizes.copy_within(index + 1..self.len, index);
+    self.len -= 1;
+  }
+}
+
+/// Find the smallest of the free-regions, sorted by location, which can fit
+/// an allocation with the given size & alignment
+///
+/// Returns the index of the free-region, and the aligned offset of the
+/// allocation within it.
+///
+/// The sizes are scanned [`LANES`] at a time, so that only the free-regions
+/// which are large enough, & smaller than the best so far, are inspected
+/// individually.
+pub(crate) fn best_fit(
+  locations: &[Location],
+  sizes: &[Size],
+  size: NonZero<Size>,
+  align: NonZero<Size>,
+) -> Option<(usize, Location)> {
+  let mut best: Option<(usize, Location, Size)> = None;
+  let inspect = |index: usize, best: &mut Option<(usize, Location, Size)>| {
+    let (location, region_size) = (locations[index], sizes[index]);
+    if region_size < size.get()
+      || best.is_some_and(|(.., best_size)| region_size >= best_size)
+    {
+      return;
+    }
+    let misalignment = (align.get() - (location % align)) % align;
+    if misalignment as u64 + size.get() as u64 <= region_size as u64 {
+      *best = Some((index, location + misalignment, region_size));
+    }
+  };
+
+  let (chunks, tail) = sizes.as_chunks::<LANES>();
+  for (chunk_index, chunk) in chunks.iter().enumerate() {
+    // note: `best_size >= size >= 1`, so this can't underflow
+    let smaller = best.map_or(Size::MAX, |(.., best_size)| best_size - 1);
+    let mut mask = lanes_within(chunk, size.get(), smaller);
+    while mask != 0 {
+      inspect(
+        chunk_index * LANES + mask.trailing_zeros() as usize,
+        &mut best,
+      );
+      mask &= mask - 1;
+    }
+  }
+  for index in sizes.len() - tail.len()..sizes.len() {
+    inspect(index, &mut best);
+  }
+
+  best.map(|(index, offset, _)| (index, offset))
+}
+
+/// The number of free-region sizes compared at once
+const LANES: usize = 4;
+
+/// Returns a bitmask of the lanes of `sizes` in the range `low..=high`
+#[cfg(target_arch = "x86_64")]
+fn lanes_within(sizes: &[Size; LANES], low: Size, high: Size) -> u32 {
+  use ::core::arch::x86_64::*;
+
+  // SAFETY: SSE2 is part of the x86_64 baseline, and `sizes` is valid for an
+  // unaligned 128-bit load
+  unsafe {
+    // note: SSE2 only has signed comparisons, so flip the sign bits to
+    // compare as unsigned
+    let bias = _mm_set1_epi32(i32::MIN);
+    let sizes = _mm_xor_si128(_mm_loadu_si128(sizes.as_ptr().cast()), bias);
+    let low = _mm_xor_si128(_mm_set1_epi32(low as i32), bias);
+    let high = _mm_xor_si128(_mm_set1_epi32(high as i32), bias);
+    let outside =
+      _mm_or_si128(_mm_cmpgt_epi32(low, sizes), _mm_cmpgt_epi32(sizes, high));
+    !(_mm_movemask_ps(_mm_castsi128_ps(outside)) as u32) & 0b1111
+  }
+}
+
+/// Returns a bitmask of the lanes of `sizes` in the range `low..=high`
+#[cfg(target_arch = "aarch64")]
+fn lanes_within(sizes: &[Size; LANES], low: Size, high: Size) -> u32 {
+  use ::core::arch::aarch64::*;
+
+  // SAFETY: NEON is part of the aarch64 baseline, and `sizes` & `WEIGHTS` are
+  // valid for a 128-bit load
+  unsafe {
+    const WEIGHTS: [u32; LANES] = [1, 2, 4, 8];
+    let sizes = vld1q_u32(sizes.as_ptr());
+    let within = vandq_u32(
+      vcgeq_u32(sizes, vdupq_n_u32(low)),
+      vcleq_u32(sizes, vdupq_n_u32(high)),
+    );
+    vaddvq_u32(vandq_u32(within, vld1q_u32(WEIGHTS.as_ptr())))
+  }
+}
+
+/// Returns a bitmask of the lanes of `sizes` in the range `low..=high`
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn lanes_within(sizes: &[Size; LANES], low: Size, high: Size) -> u32 {
+  sizes.iter().enumerate().fold(0, |mask, (lane, &size)| {
+    mask | (u32::from(low <= size && size <= high) << lane)
+  })
+}
+
+impl<const N: usize> fmt::Debug for FixedAllocator<N> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("FixedAllocator")
+      .field("capacity", &self.capacity)
+      .field("total_available", &self.available)
+      .field("free_regions", &self.len)
+      .field("max_free_regions", &N)
+      .finish()
+  }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum FixedFreeError {
+  /// The allocation was out of bounds, or overlapped a free-region
+  Invalid,
+  /// There was no room left to track another free-region
+  TableFull,
+}
+
+impl Error for FixedFreeError {}
+impl fmt::Display for FixedFreeError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      FixedFreeError::Invalid => {
+        f.write_str("Invalid allocation, or double free")
+      },
+      FixedFreeError::TableFull => f.write_str(
+        "TableFull Error: No room left to track another free-region",
+      ),
+    }
+  }
+}
@@ -0,0 +1,158 @@
+use {
+  crate::{Allocation, Allocator, Size, StackAllocator},
+  ::alloc::vec::Vec,
+  ::core::fmt,
+};
+
+/// An [`Allocator`] with a linear region carved out of it, for per-frame
+/// scratch allocations
+///
+/// Frame allocations are pushed onto the linear region, which is reset in
+/// one step at the end of each frame. Once the linear region is exhausted,
+/// they fall back to the general allocator, and are freed along with the
+/// rest of the frame. Long-lived allocations can be made from the general
+/// allocator directly, see [`allocator_mut`](Self::allocator_mut).
+///
+/// For example:
+/// ```
+/// # use ::orderly_allocator::FrameAllocator;
+/// let mut allocator = FrameAllocator::new(1_000, 100);
+/// let mesh = allocator.allocator_mut().alloc(200).unwrap();
+/// for _frame in 0..3 {
+///   let scratch = allocator.alloc(80).unwrap();
+///   assert_eq!(scratch.offset(), 0);
+///   let overflow = allocator.alloc(80).unwrap();
+///   assert_eq!(overflow.offset(), 300);
+///   allocator.reset_frame();
+/// }
+/// allocator.allocator_mut().free(mesh);
+/// ```
+#[derive(Clone)]
+pub struct FrameAllocator {
+  allocator: Allocator,
+  /// The region carved out of `allocator` for the linear allocations
+  linear: Allocation,
+  stack: StackAllocator,
+  /// The frame allocations which fell back to `allocator`
+  overflow: Vec<Allocation>,
+}
+
+impl FrameAllocator {
+  /// Create a new allocator to manage a pool of memory, with the first
+  /// `linear_capacity` bytes used for the linear region
+  ///
+  /// Panics:
+  /// - Panics if `linear_capacity == 0`
+  /// - Panics if `linear_capacity > capacity`
+  pub fn new(capacity: Size, linear_capacity: Size) -> Self {
+    assert!(linear_capacity != 0, "`linear_capacity == 0`");
+    assert!(linear_capacity <= capacity, "`linear_capacity > capacity`");
+
+    let mut allocator = Allocator::new(capacity);
+    let linear = allocator
+      .alloc(linear_capacity)
+      .unwrap_or_else(|| unreachable!());
+    FrameAllocator {
+      allocator,
+      linear,
+      stack: StackAllocator::new(linear_capacity),
+      overflow: Vec::new(),
+    }
+  }
+
+  /// Try to allocate a region for the current frame, with the provided size
+  ///
+  /// See [`alloc_with_align`](Self::alloc_with_align).
+  pub fn alloc(&mut self, size: Size) -> Option<Allocation> {
+    self.alloc_with_align(size, 1)
+  }
+
+  /// Try to allocate a region for the current frame, with the provided size
+  /// & alignment
+  ///
+  /// The allocation is taken from the linear region if there is room, and
+  /// otherwise from the general allocator. Either way it lasts until
+  /// [`reset_frame`](Self::reset_frame).
+  ///
+  /// Returns `None` if:
+  /// - there is no room in either the linear region or the general allocator,
+  ///   or
+  /// - `size == 0`, or
+  /// - `align == 0`.
+  pub fn alloc_with_align(
+    &mut self,
+    size: Size,
+    align: Size,
+  ) -> Option<Allocation> {
+    if let Some(allocation) = self.stack.alloc_with_align(size, align) {
+      // note: the linear region is at the start of the pool, so offsets
+      // within it are already aligned
+      return Some(allocation);
+    }
+    let allocation = self.allocator.alloc_with_align(size, align)?;
+    self.overflow.push(allocation);
+    Some(allocation)
+  }
+
+  /// Free every allocation made for the current frame
+  ///
+  /// Allocations made directly from the general allocator are left alone.
+  pub fn reset_frame(&mut self) {
+    self.stack.reset();
+    for allocation in self.overflow.drain(..) {
+      self.allocator.free(allocation);
+    }
+  }
+
+  /// Get the size of the linear region
+  pub fn linear_capacity(&self) -> Size {
+    self.linear.size()
+  }
+
+  /// Get the space used in the linear region by the current frame, including
+  /// padding
+  pub fn linear_used(&self) -> Size {
+    self.stack.used()
+  }
+
+  /// Get the total size of the current frame's allocations which fell back
+  /// to the general allocator
+  ///
+  /// This is useful for tuning the size of the linear region.
+  pub fn overflowed(&self) -> Size {
+    self.overflow.iter().map(Allocation::size).sum()
+  }
+
+  /// Get the general allocator
+  pub fn allocator(&self) -> &Allocator {
+    &self.allocator
+  }
+
+  /// Get the general allocator, e.g. to make long-lived allocations
+  ///
+  /// The linear region is a live allocation of the general allocator, and
+  /// must not be freed through it.
+  pub fn allocator_mut(&mut self) -> &mut Allocator {
+    &mut self.allocator
+  }
+}
+
+impl Drop for FrameAllocator {
+  fn drop(&mut self) {
+    // note: the linear region & the current frame belong to this allocator,
+    // so only long-lived allocations are left for the drop check
+    self.reset_frame();
+    self.allocator.free(self.linear);
+  }
+}
+
+impl fmt::Debug for FrameAllocator {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("FrameAllocator")
+      .field("linear", &self.linear)
+      .field("linear_used", &self.linear_used())
+      .field("overflow", &self.overflow.len())
+      .field("allocator", &self.allocator)
+      .finish()
+  }
+}
@@ -0,0 +1,144 @@
+use {
+  crate::{Allocation, FixedAllocator, Size},
+  ::core::{
+    alloc::{GlobalAlloc, Layout},
+    cell::UnsafeCell,
+    hint,
+    num::NonZero,
+    ptr,
+    sync::atomic::{AtomicBool, Ordering},
+  },
+};
+
+/// A statically-declared pool of memory which can be used as the
+/// `#[global_allocator]`
+///
+/// This is built on [`FixedAllocator`], so it never touches the heap and never
+/// panics; requests which can't be satisfied return a null pointer as usual.
+/// Access to the allocator is serialised with a spin-lock.
+///
+/// `SIZE` is the size of the pool in bytes, and `REGIONS` is the maximum number
+/// of separate free-regions that can be tracked at once.
+///
+/// ```
+/// use ::orderly_allocator::StaticPool;
+///
+/// #[global_allocator]
+/// static GLOBAL: StaticPool<{ 1 << 20 }> = StaticPool::new();
+///
+/// let greeting = String::from("hello, orderly!");
+/// assert!(GLOBAL.total_available() < 1 << 20);
+/// ```
+pub struct StaticPool<const SIZE: usize, const REGIONS: usize = 256> {
+  memory: UnsafeCell<Memory<SIZE>>,
+  allocator: UnsafeCell<FixedAllocator<REGIONS>>,
+  locked: AtomicBool,
+}
+
+#[repr(C, align(64))]
+struct Memory<const SIZE: usize>([u8; SIZE]);
+
+// SAFETY: All access to the allocator is serialised by `locked`, and the pool
+// only hands out pointers to disjoint parts of the memory.
+unsafe impl<const SIZE: usize, const REGIONS: usize> Sync
+  for StaticPool<SIZE, REGIONS>
+{
+}
+
+impl<const SIZE: usize, const REGIONS: usize> StaticPool<SIZE, REGIONS> {
+  /// The largest alignment the pool can satisfy
+  pub const MAX_ALIGN: usize = 64;
+
+  /// Create a new pool
+  ///
+  /// This is intended to be used to initialise a `static`.
+  pub const fn new() -> Self {
+    const {
+      assert!(SIZE <= Size::MAX as usize, "`SIZE` must fit in a `u32`");
+    }
+
+    StaticPool {
+      memory: UnsafeCell::new(Memory([0; SIZE])),
+      allocator: UnsafeCell::new(FixedAllocator::new(SIZE as Size)),
+      locked: AtomicBool::new(false),
+    }
+  }
+
+  /// Get the total available memory in this pool
+  pub fn total_available(&self) -> Size {
+    self.with_allocator(|allocator| allocator.total_available())
+  }
+
+  /// Get the size of the largest available memory region in this pool
+  pub fn largest_available(&self) -> Size {
+    self.with_allocator(|allocator| allocator.largest_available())
+  }
+
+  /// Get exclusive access to the allocator
+  fn with_allocator<R>(
+    &self,
+    f: impl FnOnce(&mut FixedAllocator<REGIONS>) -> R,
+  ) -> R {
+    while self
+      .locked
+      .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+      .is_err()
+    {
+      hint::spin_loop();
+    }
+
+    // SAFETY: We hold the lock, so nothing else can access the allocator
+    let result = f(unsafe { &mut *self.allocator.get() });
+
+    self.locked.store(false, Ordering::Release);
+    result
+  }
+
+  fn base(&self) -> *mut u8 {
+    self.memory.get().cast()
+  }
+}
+
+impl<const SIZE: usize, const REGIONS: usize> Default
+  for StaticPool<SIZE, REGIONS>
+{
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+// SAFETY: Blocks are handed out from disjoint allocations of the memory, which
+// lives as long as the pool.
+unsafe impl<const SIZE: usize, const REGIONS: usize> GlobalAlloc
+  for StaticPool<SIZE, REGIONS>
+{
+  unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    if layout.align() > Self::MAX_ALIGN {
+      return ptr::null_mut();
+    }
+    let Ok(size) = Size::try_from(layout.size()) else {
+      return ptr::null_mut();
+    };
+
+    match self.with_allocator(|allocator| {
+      allocator.alloc_with_align(size, layout.align() as Size)
+    }) {
+      // SAFETY: The allocation lies within the memory
+      Some(allocation) => unsafe {
+        self.base().add(allocation.offset as usize)
+      },
+      None => ptr::null_mut(),
+    }
+  }
+
+  unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    let Some(size) = NonZero::new(layout.size() as Size) else {
+      return;
+    };
+    let offset = (ptr.addr() - self.base().addr()) as Size;
+
+    // There's nothing useful to do if this fails; the region is leaked.
+    let _ = self
+      .with_allocator(|allocator| allocator.free(Allocation { offset, size }));
+  }
+}
@@ -0,0 +1,163 @@
+use {
+  crate::{Allocation, Allocator, Location, Size},
+  ::alloc::collections::BTreeMap,
+  ::core::{fmt, ops::Bound},
+};
+
+/// The kind of resource an allocation will be bound to
+///
+/// See [`GranularityAllocator`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ResourceKind {
+  /// Buffers, and images with linear tiling
+  Linear,
+  /// Images with optimal (non-linear) tiling
+  NonLinear,
+}
+
+/// An [`Allocator`] which applies Vulkan's `bufferImageGranularity` rule
+///
+/// Vulkan requires that linear and non-linear resources bound to the same
+/// `VkDeviceMemory` do not share a "page" of `granularity` bytes. Each
+/// allocation is tagged with a [`ResourceKind`], and whenever the normal
+/// placement would put it on the same page as a neighbour of the other kind,
+/// the allocation is instead aligned & padded to whole pages.
+///
+/// Allocations of the same kind are packed together as usual, so this only
+/// costs extra space where the kinds actually meet.
+#[derive(Clone)]
+pub struct GranularityAllocator {
+  allocator: Allocator,
+  granularity: Size,
+  /// The live allocations, & the kind of resource each is bound to, sorted by
+  /// location
+  allocations: BTreeMap<Location, (Size, ResourceKind)>,
+}
+
+impl GranularityAllocator {
+  /// Create a new allocator to manage a pool of memory
+  ///
+  /// `granularity` should be the device's `bufferImageGranularity` limit.
+  ///
+  /// Panics:
+  /// - Panics if `capacity == 0`
+  /// - Panics if `granularity` is not a power of two
+  pub fn new(capacity: Size, granularity: Size) -> Self {
+    assert!(
+      granularity.is_power_of_two(),
+      "granularity must be a power of two"
+    );
+
+    GranularityAllocator {
+      allocator: Allocator::new(capacity),
+      granularity,
+      allocations: BTreeMap::new(),
+    }
+  }
+
+  /// Try to allocate a region for a resource of the given kind, with the
+  /// provided size & alignment
+  ///
+  /// If the allocation had to be moved onto its own pages, the returned size
+  /// is rounded up to a multiple of the granularity; the whole allocation must
+  /// be passed back to [`free`](Self::free).
+  ///
+  /// Returns `None` if:
+  /// - there are no free-regions with enough space, or
+  /// - `size == 0`, or
+  /// - `align == 0`, or
+  /// - padding the allocation overflows.
+  pub fn alloc(
+    &mut self,
+    size: Size,
+    align: Size,
+    kind: ResourceKind,
+  ) -> Option<Allocation> {
+    let region = self.allocator.peek_best_fit(size, align)?;
+    let offset = region.offset.checked_next_multiple_of(align)?;
+
+    let allocation = if self.conflicts(offset, size, kind) {
+      let padded_size = size.checked_next_multiple_of(self.granularity)?;
+      self
+        .allocator
+        .alloc_with_align(padded_size, align.max(self.granularity))?
+    } else {
+      self.allocator.alloc_with_align(size, align)?
+    };
+    self
+      .allocations
+      .insert(allocation.offset, (allocation.size(), kind));
+
+    Some(allocation)
+  }
+
+  /// Free the given allocation
+  ///
+  /// Panics:
+  /// - Panics if the allocation was not allocated by this allocator, or has
+  ///   already been freed.
+  pub fn free(&mut self, alloc: Allocation) {
+    let tracked = self.allocations.remove(&alloc.offset);
+    assert_eq!(
+      tracked.map(|(size, _)| size),
+      Some(alloc.size()),
+      "not a live allocation"
+    );
+    self.allocator.free(alloc);
+  }
+
+  /// Get the kind of resource the allocation at `offset` was tagged with
+  ///
+  /// Returns `None` if there is no live allocation at `offset`.
+  pub fn kind(&self, offset: Location) -> Option<ResourceKind> {
+    self.allocations.get(&offset).map(|&(_, kind)| kind)
+  }
+
+  /// Get the granularity, in bytes
+  pub fn granularity(&self) -> Size {
+    self.granularity
+  }
+
+  /// Get the underlying [`Allocator`]
+  ///
+  /// This can be used to query e.g. the available space in the pool.
+  pub fn allocator(&self) -> &Allocator {
+    &self.allocator
+  }
+
+  /// Returns true if placing a resource of the given kind at
+  /// `offset..offset + size` would share a page with a neighbouring resource of
+  /// the other kind
+  fn conflicts(
+    &self,
+    offset: Location,
+    size: Size,
+    kind: ResourceKind,
+  ) -> bool {
+    let page = |location: Location| location / self.granularity;
+    let first_page = page(offset);
+    let last_page = page(offset + (size - 1));
+
+    let previous = self.allocations.range(..offset).next_back();
+    let following = self
+      .allocations
+      .range((Bound::Excluded(offset), Bound::Unbounded))
+      .next();
+
+    previous.is_some_and(|(&location, &(size, other))| {
+      other != kind && page(location + (size - 1)) == first_page
+    }) || following.is_some_and(|(&location, &(_, other))| {
+      other != kind && page(location) == last_page
+    })
+  }
+}
+
+impl fmt::Debug for GranularityAllocator {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("GranularityAllocator")
+      .field("granularity", &self.granularity)
+      .field("allocations", &self.allocations.len())
+      .field("allocator", &self.allocator)
+      .finish()
+  }
+}
@@ -0,0 +1,175 @@
+#[cfg(target_has_atomic = "ptr")]
+use crate::Hooks;
+use {
+  crate::{Allocation, Allocator, Location, Overflow, Size},
+  ::alloc::collections::BTreeMap,
+  ::core::{fmt, num::NonZero, ops::Range},
+};
+
+/// An [`Allocator`] which surrounds every allocation with reserved guard
+/// bands
+///
+/// The guard bands are never handed out, so an out-of-bounds write in the
+/// external buffer lands in a dead zone rather than in a neighbouring
+/// allocation. Fill the bands returned by [`guard_bands`](Self::guard_bands)
+/// with a known pattern after allocating, and check that it is intact later
+/// (e.g. before freeing) to detect such writes.
+///
+/// This is intended as an opt-in debugging aid; it costs `2 * guard` bytes
+/// per allocation, plus bookkeeping.
+#[derive(Clone)]
+pub struct GuardedAllocator {
+  allocator: Allocator,
+  guard: Size,
+  /// The live allocations, mapped to the underlying allocations which also
+  /// cover their guard bands
+  allocations: BTreeMap<Location, Allocation>,
+}
+
+impl GuardedAllocator {
+  /// Create a new allocator to manage a pool of memory, reserving at least
+  /// `guard` bytes on each side of every allocation
+  ///
+  /// Panics:
+  /// - Panics if `capacity == 0`
+  pub fn new(capacity: Size, guard: Size) -> Self {
+    GuardedAllocator {
+      allocator: Allocator::new(capacity),
+      guard,
+      allocations: BTreeMap::new(),
+    }
+  }
+
+  /// Try to allocate a region with the provided size
+  ///
+  /// See [`alloc_with_align`](Self::alloc_with_align).
+  pub fn alloc(&mut self, size: Size) -> Option<Allocation> {
+    self.alloc_with_align(size, 1)
+  }
+
+  /// Try to allocate a region with the provided size & alignment, surrounded
+  /// by guard bands
+  ///
+  /// The leading guard band is rounded up to a multiple of `align`, so it may
+  /// be larger than the configured size.
+  ///
+  /// Returns `None` if:
+  /// - there are no free-regions with enough space for the allocation & its
+  ///   guard bands, or
+  /// - `size == 0`, or
+  /// - `align == 0`, or
+  /// - the size including guard bands overflows.
+  pub fn alloc_with_align(
+    &mut self,
+    size: Size,
+    align: Size,
+  ) -> Option<Allocation> {
+    let size = NonZero::new(size)?;
+    let leading = self.guard.checked_next_multiple_of(align)?;
+    let outer_size =
+      leading.checked_add(size.get())?.checked_add(self.guard)?;
+
+    let outer = self.allocator.alloc_with_align(outer_size, align)?;
+    let allocation = Allocation {
+      offset: outer.offset + leading,
+      size,
+    };
+    self.allocations.insert(allocation.offset, outer);
+
+    Some(allocation)
+  }
+
+  /// Free the given allocation, along with its guard bands
+  ///
+  /// Any [hooks](Self::set_hooks) are invoked first.
+  ///
+  /// Panics:
+  /// - Panics if the allocation was not allocated by this allocator, or has
+  ///   already been freed.
+  pub fn free(&mut self, alloc: Allocation) {
+    let outer = self
+      .allocations
+      .remove(&alloc.offset)
+      .expect("not a live allocation");
+    assert_eq!(
+      self.inner_size(alloc.offset, outer),
+      alloc.size(),
+      "not a live allocation"
+    );
+    #[cfg(target_has_atomic = "ptr")]
+    if let Some(hooks) = &self.allocator.hooks {
+      let (outer, inner) = (outer.range(), alloc.range());
+      hooks.check_guard_bands(outer.start..inner.start, inner.end..outer.end);
+    }
+    self.allocator.free(outer);
+  }
+
+  /// Set hooks to invoke whenever an allocation is freed
+  ///
+  /// [`Hooks::check_guard_bands`] is called before [`Hooks::on_free`], so the
+  /// guard bands can be verified before anything is poisoned. `on_free` is
+  /// called with the range of the allocation including its guard bands.
+  ///
+  /// See [`Allocator::set_hooks`].
+  #[cfg(target_has_atomic = "ptr")]
+  pub fn set_hooks(&mut self, hooks: impl Hooks + 'static) {
+    self.allocator.set_hooks(hooks);
+  }
+
+  /// Remove the hooks
+  ///
+  /// See [`set_hooks`](Self::set_hooks).
+  #[cfg(target_has_atomic = "ptr")]
+  pub fn clear_hooks(&mut self) {
+    self.allocator.clear_hooks();
+  }
+
+  /// Add new free space at the end of the pool
+  ///
+  /// See [`Allocator::grow_capacity`].
+  pub fn grow_capacity(&mut self, additional: Size) -> Result<(), Overflow> {
+    self.allocator.grow_capacity(additional)
+  }
+
+  /// Get the guard bands surrounding a live allocation, as ranges that can be
+  /// used to index the external buffer
+  ///
+  /// Returns `[leading, trailing]`, or `None` if `alloc` is not live.
+  pub fn guard_bands(&self, alloc: Allocation) -> Option<[Range<usize>; 2]> {
+    let outer = *self.allocations.get(&alloc.offset)?;
+    if self.inner_size(alloc.offset, outer) != alloc.size() {
+      return None;
+    }
+
+    let outer = outer.range();
+    let inner = alloc.range();
+    Some([outer.start..inner.start, inner.end..outer.end])
+  }
+
+  /// Get the minimum size of each guard band
+  pub fn guard(&self) -> Size {
+    self.guard
+  }
+
+  /// Get the underlying [`Allocator`]
+  ///
+  /// Note: The allocations it manages include the guard bands.
+  pub fn allocator(&self) -> &Allocator {
+    &self.allocator
+  }
+
+  /// Get the size of the allocation at `offset` within `outer`
+  fn inner_size(&self, offset: Location, outer: Allocation) -> Size {
+    outer.offset + outer.size() - self.guard - offset
+  }
+}
+
+impl fmt::Debug for GuardedAllocator {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("GuardedAllocator")
+      .field("guard", &self.guard)
+      .field("allocations", &self.allocations.len())
+      .field("allocator", &self.allocator)
+      .finish()
+  }
+}
@@ -0,0 +1,87 @@
+use {
+  crate::{Allocation, Allocator},
+  ::alloc::sync::Arc,
+  ::core::ops::Range,
+};
+
+/// Callbacks invoked when an allocation is freed, see
+/// [`Allocator::set_hooks`]
+///
+/// These are used to make use-after-free & out-of-bounds writes in the
+/// external buffer observable; e.g. by filling freed memory with a poison
+/// pattern, and checking that guard bands are still intact.
+///
+/// This is implemented for any `Fn(Range<usize>)`, which is treated as
+/// [`on_free`](Self::on_free).
+pub trait Hooks: Send + Sync {
+  /// Called with the range of the external buffer occupied by an allocation
+  /// which is about to be freed
+  fn on_free(&self, range: Range<usize>);
+
+  /// Called with the guard bands of an allocation which is about to be freed
+  ///
+  /// Only [`GuardedAllocator`](crate::GuardedAllocator) calls this. Does nothing by default.
+  fn check_guard_bands(&self, leading: Range<usize>, trailing: Range<usize>) {
+    let _ = (leading, trailing);
+  }
+}
+
+/// The hooks stored by an [`Allocator`], see [`Allocator::set_hooks`]
+pub(crate) type SharedHooks = Arc<dyn Hooks>;
+
+impl<F: Fn(Range<usize>) + Send + Sync> Hooks for F {
+  fn on_free(&self, range: Range<usize>) {
+    self(range)
+  }
+}
+
+impl Allocator {
+  /// Set hooks to invoke with the range of every allocation which is freed
+  ///
+  /// The hooks are invoked by [`free`](Self::free),
+  /// [`free_many`](Self::free_many), [`free_subrange`](Self::free_subrange)
+  /// & [`try_reallocate`](Self::try_reallocate) when it shrinks an
+  /// allocation, before the space is returned to the free-lists. Frees which
+  /// are [deferred](Self::set_deferred_coalescing) invoke them straight away.
+  /// [`reset`](Self::reset) doesn't invoke them.
+  ///
+  /// For example, to poison freed memory:
+  /// ```
+  /// # use {
+  /// #   ::core::ops::Range,
+  /// #   ::orderly_allocator::Allocator,
+  /// #   ::std::sync::{Arc, Mutex},
+  /// # };
+  /// let buffer = Arc::new(Mutex::new(vec![0_u8; 1_000]));
+  /// let mut allocator = Allocator::new(1_000);
+  /// let poisoned = Arc::clone(&buffer);
+  /// allocator.set_hooks(move |range: Range<usize>| {
+  ///   poisoned.lock().unwrap()[range].fill(0xDD);
+  /// });
+  ///
+  /// let allocation = allocator.alloc(100).unwrap();
+  /// allocator.free(allocation);
+  /// assert_eq!(buffer.lock().unwrap()[allocation.range()], [0xDD; 100]);
+  /// ```
+  ///
+  /// Any previous hooks are replaced.
+  ///
+  /// Note: The hooks are shared with clones of the allocator.
+  pub fn set_hooks(&mut self, hooks: impl Hooks + 'static) {
+    self.hooks = Some(Arc::new(hooks));
+  }
+
+  /// Remove the hooks
+  ///
+  /// See [`set_hooks`](Self::set_hooks).
+  pub fn clear_hooks(&mut self) {
+    self.hooks = None;
+  }
+
+  /// Invoke the hooks, if any, for an allocation which is being freed
+  pub(crate) fn run_free_hooks(&self, alloc: Allocation) {
+    if let Some(hooks) = &self.hooks {
+      hooks.on_free(alloc.range());
+    }
+  }
+}
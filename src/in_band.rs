@@ -0,0 +1,563 @@
+use {
+  crate::{checksum::crc32, Allocation, FixedFreeError, Location, Size},
+  ::core::{
+    error::Error, fmt, marker::PhantomData, num::NonZero, ops::Range,
+    ptr::NonNull, slice,
+  },
+};
+
+/// Identifies a formatted pool; "OAIB" in little-endian
+const MAGIC: u32 = u32::from_le_bytes(*b"OAIB");
+/// The number of words in the header, before the free-region table
+const HEADER_WORDS: usize = 6;
+/// The indices of the header's words, after the magic number
+const CAPACITY: usize = 1;
+const MAX_REGIONS: usize = 2;
+const LEN: usize = 3;
+const AVAILABLE: usize = 4;
+/// The CRC-32 of the bookkeeping, written by [`InBandPool::seal`], or `0`
+const CHECKSUM: usize = 5;
+/// The alignment of the start of the managed space, relative to the pool
+const DATA_ALIGN: usize = 16;
+
+/// A pool which keeps its own bookkeeping inside the memory it manages
+///
+/// The start of the memory is reserved for a small header & a table of up to
+/// `max_free_regions` free-regions, sorted by location, like
+/// [`FixedAllocator`](crate::FixedAllocator)'s. Everything is stored as
+/// little-endian offsets relative to the start of the memory, so the pool &
+/// its bookkeeping form a single position-independent blob; it can be
+/// written to disk or mapped into another process, and reopened with
+/// [`open`](Self::open).
+///
+/// Allocations' offsets are also relative to the start of the memory, and
+/// never overlap the reserved space. Alignments are relative to the start of
+/// the memory too, so it should itself be suitably aligned.
+///
+/// Before persisting the blob, it can be [sealed](Self::seal) with a
+/// checksum of the bookkeeping, so that a torn write or bit flip is detected
+/// when it is reopened.
+pub struct InBandPool<'a> {
+  /// The start of the memory
+  ///
+  /// This is a pointer rather than a `&mut [u8]`, so that a
+  /// [`SharedPool`](crate::SharedPool) can use the bookkeeping without
+  /// borrowing the live allocations, which other processes may be using.
+  /// Every access goes through [`bytes`](Self::bytes) &
+  /// [`bytes_mut`](Self::bytes_mut), which borrow only the range they need.
+  memory: NonNull<u8>,
+  len: usize,
+  _memory: PhantomData<&'a mut [u8]>,
+}
+
+// SAFETY: the pool has the same access to the memory as a `&'a mut [u8]`
+unsafe impl Send for InBandPool<'_> {}
+unsafe impl Sync for InBandPool<'_> {}
+
+impl<'a> InBandPool<'a> {
+  /// Format `memory` as an empty pool, with room to track up to
+  /// `max_free_regions` separate free-regions
+  ///
+  /// Returns:
+  /// - `Err(InBandError::TooSmall)` if `max_free_regions == 0`, or there is
+  ///   no space left after reserving room for the bookkeeping.
+  /// - `Err(InBandError::TooLarge)` if `memory` is larger than `Size::MAX`.
+  pub fn format(
+    memory: &'a mut [u8],
+    max_free_regions: Size,
+  ) -> Result<Self, InBandError> {
+    let capacity =
+      Size::try_from(memory.len()).map_err(|_| InBandError::TooLarge)?;
+    let reserved = reserved_size(max_free_regions);
+    if max_free_regions == 0 || reserved >= memory.len() {
+      return Err(InBandError::TooSmall);
+    }
+
+    let mut pool = InBandPool::from_slice(memory);
+    pool.set_word(0, MAGIC);
+    pool.set_word(CAPACITY, capacity);
+    pool.set_word(MAX_REGIONS, max_free_regions);
+    pool.clear();
+    Ok(pool)
+  }
+
+  /// Reopen a pool previously formatted with [`format`](Self::format)
+  ///
+  /// The bookkeeping is checked for consistency, & against its checksum if
+  /// the pool was [sealed](Self::seal), but the contents of the allocations
+  /// are not.
+  ///
+  /// Returns:
+  /// - `Err(InBandError::Corrupt)` if `memory` doesn't hold a valid pool of
+  ///   its length.
+  /// - `Err(InBandError::ChecksumMismatch)` if the pool was sealed, but the
+  ///   bookkeeping has since been damaged.
+  pub fn open(memory: &'a mut [u8]) -> Result<Self, InBandError> {
+    let pool = InBandPool::from_slice(memory);
+    pool.validate()?;
+    Ok(pool)
+  }
+
+  /// Use the pool at `memory` without checking its bookkeeping
+  ///
+  /// Only the bookkeeping at the start of the memory is accessed, unless the
+  /// caller uses [`get`](Self::get), [`get_mut`](Self::get_mut),
+  /// [`as_bytes`](Self::as_bytes) or [`into_inner`](Self::into_inner).
+  ///
+  /// # Safety
+  ///
+  /// - `memory` must be valid for reads & writes of `len` bytes for `'a`.
+  /// - Nothing else may access the bookkeeping for `'a`.
+  pub(crate) unsafe fn from_raw(memory: NonNull<u8>, len: usize) -> Self {
+    InBandPool {
+      memory,
+      len,
+      _memory: PhantomData,
+    }
+  }
+
+  fn from_slice(memory: &'a mut [u8]) -> Self {
+    let len = memory.len();
+    // SAFETY: the slice is valid & borrowed exclusively for `'a`
+    unsafe { InBandPool::from_raw(NonNull::from(memory).cast(), len) }
+  }
+
+  /// Check the whole of the bookkeeping, & the checksum if the pool was
+  /// sealed
+  pub(crate) fn validate(&self) -> Result<(), InBandError> {
+    if !self.is_consistent() {
+      return Err(InBandError::Corrupt);
+    }
+    if self.is_sealed() && self.word(CHECKSUM) != self.checksum() {
+      return Err(InBandError::ChecksumMismatch);
+    }
+    Ok(())
+  }
+
+  /// Check just the header, which is enough to keep every access to the
+  /// free-region table within the reserved space
+  pub(crate) fn validate_header(&self) -> Result<(), InBandError> {
+    let valid = self.len >= HEADER_WORDS * 4
+      && self.word(0) == MAGIC
+      && self.word(CAPACITY) as usize == self.len
+      && self.word(MAX_REGIONS) != 0
+      && self.reserved() < self.len
+      && self.word(LEN) <= self.word(MAX_REGIONS);
+    if valid {
+      Ok(())
+    } else {
+      Err(InBandError::Corrupt)
+    }
+  }
+
+  /// Write a checksum of the bookkeeping, e.g. before persisting the pool
+  ///
+  /// [`open`](Self::open) verifies the checksum of a sealed pool. Any
+  /// allocation or free unseals it again, since the checksum is only
+  /// computed on demand.
+  pub fn seal(&mut self) {
+    let checksum = self.checksum();
+    self
+      .bytes_mut(CHECKSUM * 4..CHECKSUM * 4 + 4)
+      .copy_from_slice(&checksum.to_le_bytes());
+  }
+
+  /// Returns true if the pool has been [sealed](Self::seal) since it was
+  /// last modified
+  pub fn is_sealed(&self) -> bool {
+    self.word(CHECKSUM) != 0
+  }
+
+  /// Try to allocate a region with the provided size
+  ///
+  /// See [`alloc_with_align`](Self::alloc_with_align).
+  pub fn alloc(&mut self, size: Size) -> Option<Allocation> {
+    self.alloc_with_align(size, 1)
+  }
+
+  /// Try to allocate a region with the provided size & alignment
+  ///
+  /// Uses a *best-fit* strategy, choosing the smallest free-region which can
+  /// fit the aligned allocation.
+  ///
+  /// Returns `None` if:
+  /// - there is no free-region large enough, or
+  /// - the allocation would need to split a free-region, but there is no room
+  ///   left in the table to track the extra free-region, or
+  /// - `size == 0`, or
+  /// - `align == 0`.
+  pub fn alloc_with_align(
+    &mut self,
+    size: Size,
+    align: Size,
+  ) -> Option<Allocation> {
+    let size = NonZero::new(size)?;
+    let align = NonZero::new(align)?;
+
+    let mut best: Option<(usize, Location, Size)> = None;
+    for index in 0..self.len() {
+      let (location, region_size) = self.region(index);
+      if region_size < size.get()
+        || best.is_some_and(|(.., best_size)| region_size >= best_size)
+      {
+        continue;
+      }
+      let misalignment = (align.get() - (location % align)) % align;
+      if misalignment as u64 + size.get() as u64 <= region_size as u64 {
+        best = Some((index, location + misalignment, region_size));
+      }
+    }
+    let (index, offset, _) = best?;
+
+    let (location, region_size) = self.region(index);
+    let leading = offset - location;
+    let trailing = location + region_size - (offset + size.get());
+    match (leading > 0, trailing > 0) {
+      (false, false) => self.remove(index),
+      (true, false) => self.set_region(index, location, leading),
+      (false, true) => {
+        self.set_region(index, offset + size.get(), trailing);
+      },
+      (true, true) => {
+        if !self.insert(index + 1, offset + size.get(), trailing) {
+          return None;
+        }
+        self.set_region(index, location, leading);
+      },
+    }
+    self.set_word(AVAILABLE, self.total_available() - size.get());
+
+    Some(Allocation { offset, size })
+  }
+
+  /// Free the given allocation
+  ///
+  /// Returns:
+  /// - `Err(FixedFreeError::Invalid)` if the allocation is out of bounds,
+  ///   overlaps the reserved space, or overlaps a free-region; i.e. a double
+  ///   free.
+  /// - `Err(FixedFreeError::TableFull)` if the allocation cannot be coalesced
+  ///   with any neighbouring free-region, and there is no room left in the
+  ///   table to track another free-region. In this case the allocation is
+  ///   leaked.
+  pub fn free(&mut self, alloc: Allocation) -> Result<(), FixedFreeError> {
+    let offset = alloc.offset;
+    let size = alloc.size.get();
+    let end = offset
+      .checked_add(size)
+      .filter(|&end| end <= self.capacity())
+      .filter(|_| offset as usize >= self.reserved())
+      .ok_or(FixedFreeError::Invalid)?;
+
+    let len = self.len();
+    let index = (0..len)
+      .find(|&index| self.region(index).0 >= offset)
+      .unwrap_or(len);
+    let previous = index.checked_sub(1).map(|i| self.region(i));
+    let following = (index < len).then(|| self.region(index));
+
+    let coalesce_previous = match previous {
+      Some((location, size)) if location + size > offset => {
+        return Err(FixedFreeError::Invalid);
+      },
+      Some((location, size)) => location + size == offset,
+      None => false,
+    };
+    let coalesce_following = match following {
+      Some((location, _)) if location < end => {
+        return Err(FixedFreeError::Invalid);
+      },
+      Some((location, _)) => location == end,
+      None => false,
+    };
+
+    match (previous, following) {
+      (Some((location, previous_size)), Some((_, following_size)))
+        if coalesce_previous && coalesce_following =>
+      {
+        self.set_region(
+          index - 1,
+          location,
+          previous_size + size + following_size,
+        );
+        self.remove(index);
+      },
+      (Some((location, previous_size)), _) if coalesce_previous => {
+        self.set_region(index - 1, location, previous_size + size);
+      },
+      (_, Some((_, following_size))) if coalesce_following => {
+        self.set_region(index, offset, size + following_size);
+      },
+      _ => {
+        if !self.insert(index, offset, size) {
+          return Err(FixedFreeError::TableFull);
+        }
+      },
+    }
+    self.set_word(AVAILABLE, self.total_available() + size);
+
+    Ok(())
+  }
+
+  /// Free ***all*** allocations
+  pub fn reset(&mut self) {
+    self.clear();
+  }
+
+  /// Get the contents of a live allocation
+  ///
+  /// Panics:
+  /// - Panics if the allocation is not within the pool
+  pub fn get(&self, alloc: Allocation) -> &[u8] {
+    self.bytes(alloc.range())
+  }
+
+  /// Mutably get the contents of a live allocation
+  ///
+  /// Panics:
+  /// - Panics if the allocation is not within the pool
+  pub fn get_mut(&mut self, alloc: Allocation) -> &mut [u8] {
+    self.bytes_mut(alloc.range())
+  }
+
+  /// Get the whole pool, including its bookkeeping, e.g. to persist it
+  pub fn as_bytes(&self) -> &[u8] {
+    self.bytes(0..self.len)
+  }
+
+  /// Release the memory, which keeps the pool's state
+  pub fn into_inner(self) -> &'a mut [u8] {
+    // SAFETY: the pool had exclusive access to the memory for `'a`
+    unsafe { slice::from_raw_parts_mut(self.memory.as_ptr(), self.len) }
+  }
+
+  /// Get the total size of the memory, including the reserved space
+  pub fn capacity(&self) -> Size {
+    self.word(CAPACITY)
+  }
+
+  /// Get the size of the space reserved for the bookkeeping, at the start of
+  /// the memory
+  pub fn reserved(&self) -> usize {
+    reserved_size(self.word(MAX_REGIONS))
+  }
+
+  /// Get the total available memory in this pool
+  ///
+  /// Note: The memory may be fragmented, so it may not be possible to allocate
+  /// an object of this size.
+  pub fn total_available(&self) -> Size {
+    self.word(AVAILABLE)
+  }
+
+  /// Get the size of the largest available memory region in this pool
+  pub fn largest_available(&self) -> Size {
+    (0..self.len())
+      .map(|index| self.region(index).1)
+      .max()
+      .unwrap_or(0)
+  }
+
+  /// Returns true if there are no allocations
+  pub fn is_empty(&self) -> bool {
+    self.total_available() as usize == self.len - self.reserved()
+  }
+
+  /// Returns an iterator over the unallocated regions, in order of location
+  ///
+  /// This should be used **only** for gathering metadata about the internal
+  /// state of the allocator for debugging purposes.
+  pub fn report_free_regions(
+    &self,
+  ) -> impl DoubleEndedIterator<Item = Allocation> + ExactSizeIterator + use<'_>
+  {
+    (0..self.len()).map(|index| {
+      let (offset, size) = self.region(index);
+      Allocation {
+        offset,
+        size: NonZero::new(size).unwrap_or_else(|| unreachable!()),
+      }
+    })
+  }
+
+  /// Free everything, leaving a single free-region after the reserved space
+  fn clear(&mut self) {
+    let reserved = self.reserved() as Size;
+    let available = self.capacity() - reserved;
+    self.set_word(LEN, 1);
+    self.set_word(AVAILABLE, available);
+    self.set_region(0, reserved, available);
+  }
+
+  /// Check that the header & free-region table describe a valid pool
+  fn is_consistent(&self) -> bool {
+    if self.validate_header().is_err() {
+      return false;
+    }
+
+    let mut end = self.reserved() as u64;
+    let mut available = 0;
+    for index in 0..self.len() {
+      let (location, size) = self.region(index);
+      // note: neighbouring free-regions are always coalesced
+      let location = location as u64;
+      if size == 0 || location < end || (index > 0 && location == end) {
+        return false;
+      }
+      end = location + size as u64;
+      available += size as u64;
+    }
+    end <= self.len as u64 && available == self.total_available() as u64
+  }
+
+  /// Compute the checksum of the header & the live part of the table
+  fn checksum(&self) -> u32 {
+    let header = self.bytes(0..CHECKSUM * 4);
+    let table = self.bytes(HEADER_WORDS * 4..entry_offset(self.len()));
+    // note: `0` means unsealed
+    crc32([header, table]).max(1)
+  }
+
+  /// Get the number of free-regions
+  fn len(&self) -> usize {
+    self.word(LEN) as usize
+  }
+
+  /// Get the location & size of the free-region at `index`
+  fn region(&self, index: usize) -> (Location, Size) {
+    let word = HEADER_WORDS + 2 * index;
+    (self.word(word), self.word(word + 1))
+  }
+
+  fn set_region(&mut self, index: usize, location: Location, size: Size) {
+    let word = HEADER_WORDS + 2 * index;
+    self.set_word(word, location);
+    self.set_word(word + 1, size);
+  }
+
+  /// Insert a free-region at `index`, shifting later free-regions along
+  ///
+  /// Returns false if there is no room for another free-region.
+  fn insert(&mut self, index: usize, location: Location, size: Size) -> bool {
+    let len = self.len();
+    if len == self.word(MAX_REGIONS) as usize {
+      return false;
+    }
+    let (start, end) = (entry_offset(index), entry_offset(len));
+    self.bytes_mut(start..end + 8).copy_within(..end - start, 8);
+    self.set_region(index, location, size);
+    self.set_word(LEN, len as Size + 1);
+    true
+  }
+
+  /// Remove the free-region at `index`, shifting later free-regions back
+  fn remove(&mut self, index: usize) {
+    let len = self.len();
+    let (start, end) = (entry_offset(index), entry_offset(len));
+    self.bytes_mut(start..end).copy_within(8.., 0);
+    self.set_word(LEN, len as Size - 1);
+  }
+
+  fn word(&self, index: usize) -> Size {
+    let bytes = self.bytes(index * 4..index * 4 + 4);
+    Size::from_le_bytes(bytes.try_into().unwrap_or_else(|_| unreachable!()))
+  }
+
+  /// Write a word of the bookkeeping, which unseals the pool
+  fn set_word(&mut self, index: usize, value: Size) {
+    self
+      .bytes_mut(index * 4..index * 4 + 4)
+      .copy_from_slice(&value.to_le_bytes());
+    self.bytes_mut(CHECKSUM * 4..CHECKSUM * 4 + 4).fill(0);
+  }
+
+  /// Borrow a range of the memory
+  ///
+  /// Panics:
+  /// - Panics if the range is not within the memory
+  fn bytes(&self, range: Range<usize>) -> &[u8] {
+    assert!(
+      range.start <= range.end && range.end <= self.len,
+      "{range:?} is not within the pool"
+    );
+    // SAFETY: the range is within the memory, which is valid for `'a`
+    unsafe {
+      slice::from_raw_parts(self.memory.as_ptr().add(range.start), range.len())
+    }
+  }
+
+  /// Mutably borrow a range of the memory
+  ///
+  /// Panics:
+  /// - Panics if the range is not within the memory
+  fn bytes_mut(&mut self, range: Range<usize>) -> &mut [u8] {
+    assert!(
+      range.start <= range.end && range.end <= self.len,
+      "{range:?} is not within the pool"
+    );
+    // SAFETY: the range is within the memory, which is valid for `'a`
+    unsafe {
+      slice::from_raw_parts_mut(
+        self.memory.as_ptr().add(range.start),
+        range.len(),
+      )
+    }
+  }
+}
+
+/// Get the offset of the free-region table's entry at `index`
+fn entry_offset(index: usize) -> usize {
+  (HEADER_WORDS + 2 * index) * 4
+}
+
+/// Get the size of the bookkeeping for a pool tracking up to `max_regions`
+/// free-regions
+fn reserved_size(max_regions: Size) -> usize {
+  let size = (HEADER_WORDS as u64 + 2 * max_regions as u64) * 4;
+  // note: saturating is ok, since no pool is large enough anyway
+  usize::try_from(size.next_multiple_of(DATA_ALIGN as u64))
+    .unwrap_or(usize::MAX)
+}
+
+impl fmt::Debug for InBandPool<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("InBandPool")
+      .field("capacity", &self.capacity())
+      .field("reserved", &self.reserved())
+      .field("total_available", &self.total_available())
+      .field("free_regions", &self.len())
+      .finish()
+  }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum InBandError {
+  /// The memory is too small to hold the bookkeeping & any allocations
+  TooSmall,
+  /// The memory is larger than can be addressed by a `Size`
+  TooLarge,
+  /// The memory doesn't hold a valid pool
+  Corrupt,
+  /// The pool was sealed, but its bookkeeping has since been damaged
+  ChecksumMismatch,
+}
+
+impl Error for InBandError {}
+impl fmt::Display for InBandError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      InBandError::TooSmall => {
+        f.write_str("TooSmall Error: No space left after the bookkeeping")
+      },
+      InBandError::TooLarge => {
+        f.write_str("TooLarge Error: The memory is larger than `Size::MAX`")
+      },
+      InBandError::Corrupt => {
+        f.write_str("Corrupt Error: The memory doesn't hold a valid pool")
+      },
+      InBandError::ChecksumMismatch => f.write_str(
+        "ChecksumMismatch Error: The pool's bookkeeping has been damaged",
+      ),
+    }
+  }
+}
@@ -1,8 +1,110 @@
 #![doc = include_str!("../README.md")]
 #![no_std]
+#![cfg_attr(feature = "unstable", feature(allocator_api))]
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+mod bitmap;
+mod buffer_pool;
+mod builder;
+mod checksum;
+#[cfg(feature = "op-costs")]
+mod costs;
+mod dual_zone;
+#[cfg(feature = "std")]
+mod events;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod file_header;
+mod fixed;
+mod frame;
+#[cfg(feature = "global-alloc")]
+mod global;
+mod granularity;
+mod guarded;
+#[cfg(target_has_atomic = "ptr")]
+mod hooks;
+mod in_band;
+mod migration;
+#[cfg(feature = "offset-allocator")]
+mod offset_allocator_compat;
+mod owned_pool;
+mod pool_vec;
+mod range_alloc;
+mod realtime;
+mod sealed;
+mod segmented;
+#[cfg(target_has_atomic = "32")]
+mod shared;
+#[cfg(target_has_atomic = "ptr")]
+mod snapshot;
+mod stack;
+mod suballocator;
+#[cfg(target_has_atomic = "ptr")]
+mod thresholds;
+mod trace;
+mod tracked;
+mod typed;
+#[cfg(kani)]
+mod verification;
+#[cfg(feature = "wasm")]
+mod wasm;
+#[cfg(feature = "wear-stats")]
+mod wear;
+#[cfg(feature = "wgpu")]
+mod wgpu_suballocator;
+mod worst_case;
+
+#[cfg(feature = "op-costs")]
+pub use costs::OpCosts;
+#[cfg(feature = "std")]
+pub use events::AllocatorEvent;
+#[cfg(feature = "global-alloc")]
+pub use global::StaticPool;
+#[cfg(target_has_atomic = "32")]
+pub use shared::SharedPool;
+#[cfg(feature = "wasm")]
+pub use wasm::{WasmAllocation, WasmAllocator};
+#[cfg(feature = "wear-stats")]
+pub use wear::WearStats;
+#[cfg(feature = "wgpu")]
+pub use wgpu_suballocator::{BufferAllocation, BufferSuballocator};
+pub use {
+  bitmap::BitmapAllocator,
+  buffer_pool::BufferPool,
+  builder::AllocatorBuilder,
+  dual_zone::{DualZoneAllocator, Zone},
+  file_header::FileHeaderError,
+  fixed::{FixedAllocator, FixedFreeError},
+  frame::FrameAllocator,
+  granularity::{GranularityAllocator, ResourceKind},
+  guarded::GuardedAllocator,
+  in_band::{InBandError, InBandPool},
+  migration::{MigrationCopy, MigrationError, MigrationPlan},
+  owned_pool::OwnedPool,
+  pool_vec::PoolVec,
+  range_alloc::{RangeAllocationError, RangeAllocator},
+  realtime::{LockedAllocator, RealtimeError},
+  sealed::SealedAllocator,
+  segmented::SegmentedAllocator,
+  stack::{StackAllocator, StackMarker},
+  suballocator::Suballocator,
+  trace::{ParseTraceError, Trace, TraceOp, TraceSample},
+  tracked::{AllocationId, Leak, LeakReport, TrackedAllocator},
+  typed::{Plain, PoolBox},
+  worst_case::{OpBound, WorstCase},
+};
+#[cfg(target_has_atomic = "ptr")]
+pub use {
+  hooks::Hooks, snapshot::SnapshotAllocator, thresholds::ThresholdCrossing,
+};
+
 use {
-  ::alloc::collections::{BTreeMap, BTreeSet},
+  ::alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+  },
   ::core::{cmp::Ordering, error::Error, fmt, num::NonZero, ops::Range},
 };
 
@@ -59,13 +161,15 @@ impl Allocation {
   /// assert_eq!(region, &[25, 26, 27, 28]);
   /// ```
   pub fn range(&self) -> Range<usize> {
-    (self.offset as usize)..((self.offset + self.size.get()) as usize)
+    // note: widen before adding, so an allocation touching the top of the
+    // `u32` range can't overflow
+    let start = self.offset as usize;
+    start..start + self.size.get() as usize
   }
 }
 
 /// A super-simple soft-realtime allocator for managing an external pool of
 /// memory
-#[derive(Clone)]
 pub struct Allocator {
   /// An ordered collection of free-regions, sorted primarily by size, then by
   /// location
@@ -76,6 +180,132 @@ pub struct Allocator {
   capacity: NonZero<Size>,
   /// The amount of free memory
   available: Size,
+  /// The minimum alignment applied to every allocation
+  min_align: NonZero<Size>,
+  /// Cumulative counters, see [`Stats`]
+  counters: Counters,
+  /// How free-regions are chosen, see [`Placement`]
+  placement: Placement,
+  /// The state of the pseudo-random generator used by
+  /// [`Placement::Random`]
+  rng: u64,
+  /// Per-stripe allocation counters, see [`WearStats`]
+  #[cfg(feature = "wear-stats")]
+  wear: Option<WearStats>,
+  /// The percentage of the capacity beyond which ordinary allocations fail
+  watermark: u8,
+  /// Usage thresholds which trigger a callback when crossed
+  #[cfg(target_has_atomic = "ptr")]
+  thresholds: Option<thresholds::Thresholds>,
+  /// Callbacks invoked with every freed allocation, see
+  /// [`set_hooks`](Allocator::set_hooks)
+  #[cfg(target_has_atomic = "ptr")]
+  hooks: Option<hooks::SharedHooks>,
+  /// Where to send an event for every operation
+  #[cfg(feature = "std")]
+  events: Option<::std::sync::mpsc::Sender<AllocatorEvent>>,
+  /// What to do if dropped with outstanding allocations, in debug builds
+  drop_check: DropCheck,
+  /// The number of size classes per power of two, if sizes are rounded up
+  size_classes: Option<NonZero<Size>>,
+  /// Every allocation's size is rounded up to a multiple of this
+  granularity: NonZero<Size>,
+  /// Whether aligned allocations first look for an aligned exact fit
+  exact_fit_first: bool,
+  /// How successive allocations of the same size are offset, if they are
+  coloring: Option<Coloring>,
+  /// The size & color of the most recent colored allocation
+  color_cursor: (Size, Size),
+  /// The allocations freed since coalescing was deferred, if it is, see
+  /// [`set_deferred_coalescing`](Allocator::set_deferred_coalescing)
+  deferred: Option<Vec<Allocation>>,
+  /// Measures of the work done by operations, see [`OpCosts`]
+  #[cfg(feature = "op-costs")]
+  costs: costs::CostCounters,
+  /// The most recently inserted free-regions, see [`Hints`]
+  hints: Hints,
+  /// The free-regions which start at a location aligned to at least
+  /// `1 << ALIGNED_BINS[i]`, in bin `i` for the largest such `i`; sorted as in
+  /// `free`
+  aligned_bins: [BTreeSet<FreeRegion>; ALIGNED_BINS.len()],
+}
+
+/// The alignments (as powers of two) of the free-regions tracked in
+/// `Allocator::aligned_bins`; 256, 4K & 64K
+const ALIGNED_BINS: [u32; 3] = [8, 12, 16];
+
+/// The number of free-regions of exactly the requested size which are checked
+/// for alignment, before falling back to the padded search
+const EXACT_FIT_PROBES: usize = 8;
+
+/// Get the index of the aligned bin a free-region at `location` belongs in
+fn aligned_bin(location: Location) -> Option<usize> {
+  let alignment = location.trailing_zeros();
+  ALIGNED_BINS.iter().rposition(|&bin| alignment >= bin)
+}
+
+/// A tiny cache of the most recently freed or split-off free-regions
+///
+/// Freeing then allocating the same size is extremely common in streaming
+/// workloads, so unaligned allocations check these for an exact fit before
+/// searching the free-lists. Every entry is still a free-region; entries are
+/// cleared as soon as their region is removed from the free-lists.
+#[derive(Copy, Clone, Debug, Default)]
+struct Hints {
+  regions: [Option<FreeRegion>; 4],
+  /// The slot to overwrite next
+  next: usize,
+}
+
+impl Hints {
+  fn insert(&mut self, region: FreeRegion) {
+    self.regions[self.next] = Some(region);
+    self.next = (self.next + 1) % self.regions.len();
+  }
+
+  fn remove(&mut self, region: FreeRegion) {
+    for hint in &mut self.regions {
+      if *hint == Some(region) {
+        *hint = None;
+      }
+    }
+  }
+
+  /// Find a region with exactly `size`
+  fn exact_fit(&self, size: NonZero<Size>) -> Option<FreeRegion> {
+    self
+      .regions
+      .iter()
+      .flatten()
+      .copied()
+      .find(|region| region.size == size)
+  }
+}
+
+/// Cumulative counters maintained by an [`Allocator`]
+#[derive(Copy, Clone, Debug, Default)]
+struct Counters {
+  /// The total number of bytes split off free-regions to reach alignment
+  alignment_padding: u64,
+  /// The largest number of free-regions there have been at once
+  peak_free_regions: usize,
+  /// The number of free-regions left behind by carving out allocations
+  splits: u64,
+  /// The number of free-regions created by padding to reach alignment
+  alignment_fragments: u64,
+  /// The number of released regions merged with only the preceding
+  /// free-region
+  coalesced_left: u64,
+  /// The number of released regions merged with only the following
+  /// free-region
+  coalesced_right: u64,
+  /// The number of released regions merged with free-regions on both sides
+  coalesced_both: u64,
+  /// The number of allocation requests which could not be satisfied
+  failed_allocs: u64,
+  /// The outcomes of the most recent allocation requests, one bit each with
+  /// the newest in the lowest bit, set if the request failed
+  recent_allocs: u64,
 }
 
 // This type has an explicit implementation of Ord, since we rely on properties
@@ -109,16 +339,64 @@ impl Ord for FreeRegion {
 impl Allocator {
   /// Create a new allocator to manage a pool of memory
   ///
+  /// The full `u32` range is supported, so the largest pool is `u32::MAX`
+  /// bytes; one byte short of 4 GiB.
+  ///
   /// Panics:
   /// - Panics if `capacity == 0`
   pub fn new(capacity: Size) -> Self {
+    Self::with_min_align(capacity, 1)
+  }
+
+  /// Create a new allocator which aligns every allocation to at least
+  /// `min_align`
+  ///
+  /// This allows call sites using plain [`alloc`](Self::alloc) to meet e.g.
+  /// GPU offset alignment requirements. Requests with a larger alignment are
+  /// still honoured.
+  ///
+  /// Note: [`take_largest_free_region`](Self::take_largest_free_region)
+  /// returns whole free-regions, so it does not apply `min_align`.
+  ///
+  /// Panics:
+  /// - Panics if `capacity == 0`
+  /// - Panics if `min_align` is not a power of two
+  pub fn with_min_align(capacity: Size, min_align: Size) -> Self {
     let capacity = NonZero::new(capacity).expect("`capacity == 0`");
+    assert!(
+      min_align.is_power_of_two(),
+      "`min_align` must be a power of two"
+    );
 
     let mut allocator = Allocator {
       free: BTreeSet::new(),
       location_map: BTreeMap::new(),
       capacity,
       available: capacity.get(),
+      min_align: NonZero::new(min_align).unwrap_or_else(|| unreachable!()),
+      counters: Counters::default(),
+      placement: Placement::BestFit,
+      rng: 0,
+      #[cfg(feature = "wear-stats")]
+      wear: None,
+      watermark: 100,
+      #[cfg(target_has_atomic = "ptr")]
+      thresholds: None,
+      #[cfg(target_has_atomic = "ptr")]
+      hooks: None,
+      #[cfg(feature = "std")]
+      events: None,
+      drop_check: DropCheck::default(),
+      size_classes: None,
+      granularity: NonZero::<Size>::MIN,
+      exact_fit_first: true,
+      coloring: None,
+      color_cursor: (0, 0),
+      deferred: None,
+      #[cfg(feature = "op-costs")]
+      costs: Default::default(),
+      hints: Hints::default(),
+      aligned_bins: Default::default(),
     };
 
     allocator.reset();
@@ -128,154 +406,1140 @@ impl Allocator {
 
   /// Try to allocate a region with the provided size
   ///
-  /// Uses a *best-fit* strategy, and returns [`Allocation`]s with arbitrary
-  /// alignment.
+  /// Uses a *best-fit* strategy by default (see
+  /// [`set_placement`](Self::set_placement)), and returns [`Allocation`]s with
+  /// arbitrary alignment.
   ///
   /// Returns `None` if:
-  /// - `size == 0`, or
-  /// - `size + 1` overflows.
+  /// - there are no free-regions with `size` available space, or
+  /// - `size == 0`.
   pub fn alloc(&mut self, size: Size) -> Option<Allocation> {
     self.alloc_with_align(size, 1)
   }
 
+  /// Try to allocate a region of at most `max_size`, but no less than
+  /// `min_size`
+  ///
+  /// Allocates `max_size` if possible, otherwise falls back to allocating the
+  /// entire largest free-region, less any padding needed to reach the
+  /// [`min_align`](Self::min_align). With
+  /// [size classes](Self::set_size_classes), the size is first rounded down
+  /// to the largest class which fits. The returned [`Allocation`] reports the
+  /// size that was actually obtained.
+  ///
+  /// Returns `None` if:
+  /// - the largest free-region is smaller than `min_size`, once aligned &
+  ///   rounded down to a size class, or
+  /// - `min_size > max_size`, or
+  /// - `max_size == 0`.
+  pub fn alloc_up_to(
+    &mut self,
+    max_size: Size,
+    min_size: Size,
+  ) -> Option<Allocation> {
+    if min_size > max_size {
+      return None;
+    }
+
+    let (region, offset, room) = self.roomiest_free_region()?;
+    let size = self.size_class_at_most(max_size.min(room));
+    if size < min_size {
+      return None;
+    }
+
+    // note: the search for an aligned fit needs room for the worst-case
+    // padding, so may miss a region with just enough room; in that case
+    // take the region directly
+    let padding = self.min_align.get() - 1;
+    let found = size
+      .checked_add(padding)
+      .is_some_and(|padded| padded <= self.largest_available());
+    if found {
+      return self.alloc(size);
+    }
+    let size = NonZero::new(size)?;
+    let result = self
+      .within_watermark(size.get())
+      .then(|| self.alloc_at(region, offset, size));
+    self.record_attempt(result.is_some());
+    #[cfg(feature = "std")]
+    self.emit_alloc(size.get(), 1, result);
+    result
+  }
+
+  /// Allocate the entirety of the largest free-region
+  ///
+  /// This is useful for claiming "whatever is left" as scratch space. It
+  /// counts towards the [statistics](Self::stats) like any other allocation
+  /// request; a failure is reported as a request for 0 bytes.
+  ///
+  /// Returns `None` if there are no free-regions.
+  pub fn take_largest_free_region(&mut self) -> Option<Allocation> {
+    let largest = self.free.last().copied();
+    self.record_attempt(largest.is_some());
+    let Some(FreeRegion { location, size }) = largest else {
+      #[cfg(feature = "std")]
+      self.emit_alloc(0, 1, None);
+      return None;
+    };
+    let usage = self.usage();
+
+    self.remove_free_region(location, size);
+    self.available -= size.get();
+    self.notify_thresholds(usage);
+
+    let allocation = Allocation {
+      offset: location,
+      size,
+    };
+    #[cfg(feature = "wear-stats")]
+    self.record_wear(allocation);
+    #[cfg(feature = "std")]
+    self.emit(AllocatorEvent::Alloc(allocation));
+    Some(allocation)
+  }
+
+  /// Allocate every remaining free-region, e.g. to claim all of the leftover
+  /// space explicitly during an orderly shutdown or handoff
+  ///
+  /// The allocations are returned in order of location, and can each be freed
+  /// as usual. Like [`take_largest_free_region`], this returns whole
+  /// free-regions, so `min_align` is not applied. Frees deferred by
+  /// [`set_deferred_coalescing`](Self::set_deferred_coalescing) are coalesced
+  /// first.
+  ///
+  /// [`take_largest_free_region`]: Self::take_largest_free_region
+  pub fn allocate_all_remaining(&mut self) -> Vec<Allocation> {
+    self.coalesce_all();
+    let usage = self.usage();
+    let allocations: Vec<Allocation> = self
+      .location_map
+      .iter()
+      .map(|(&offset, &size)| Allocation { offset, size })
+      .collect();
+
+    self.replace_free_regions(Vec::new());
+    self.available = 0;
+    self.notify_thresholds(usage);
+
+    #[cfg(any(feature = "wear-stats", feature = "std"))]
+    for &allocation in &allocations {
+      #[cfg(feature = "wear-stats")]
+      self.record_wear(allocation);
+      #[cfg(feature = "std")]
+      self.emit(AllocatorEvent::Alloc(allocation));
+    }
+    allocations
+  }
+
+  /// Consume the allocator, returning its free-regions in order of location
+  ///
+  /// This is for handing the leftover space to another owner. Outstanding
+  /// allocations now belong to the caller, so the
+  /// [drop check](Self::set_drop_check) is skipped. Frees deferred by
+  /// [`set_deferred_coalescing`](Self::set_deferred_coalescing) are coalesced
+  /// first.
+  pub fn into_free_regions(mut self) -> Vec<Allocation> {
+    self.coalesce_all();
+    self.drop_check = DropCheck::Off;
+    self
+      .location_map
+      .iter()
+      .map(|(&offset, &size)| Allocation { offset, size })
+      .collect()
+  }
+
   /// Try to allocate a region with the provided size & alignment
   ///
   /// Implements the following strategy (not quite *best-fit*):
   /// - Search for a region with at least `size + align - 1`, and then truncate
   ///   the start of the region such that alignment is reached.
+  /// - If `size + align - 1` overflows (i.e. for huge alignments) instead
+  ///   search the regions with at least `size` for one which can actually fit
+  ///   the aligned allocation.
   ///
   /// This is more prone to causing fragmentation compared to an unaligned
   /// [`alloc`](Self::alloc).
   ///
   /// Returns `None` if:
-  /// - there are no free-regions with `size + align - 1` available space, or
+  /// - there are no free-regions which can fit the aligned allocation, or
   /// - `size == 0`, or
-  /// - `align == 0`, or
-  /// - `size + align` overflows.
+  /// - `align == 0`.
   pub fn alloc_with_align(
     &mut self,
     size: Size,
     align: Size,
   ) -> Option<Allocation> {
+    self
+      .alloc_with_align_report(size, align)
+      .map(|(allocation, _)| allocation)
+  }
+
+  /// Try to allocate a region with a `usize` size, e.g. the length of a host
+  /// `Vec`
+  ///
+  /// Sizes & offsets are `u32` throughout the crate, so that an
+  /// [`Allocation`] is 8 bytes with a niche; this just saves a cast at the
+  /// call site. Use [`Allocation::range`] to index the buffer with `usize`s.
+  ///
+  /// Returns `None` if `size` doesn't fit in a `u32`, otherwise see
+  /// [`alloc`](Self::alloc).
+  pub fn alloc_usize(&mut self, size: usize) -> Option<Allocation> {
+    self.alloc(Size::try_from(size).ok()?)
+  }
+
+  /// Try to allocate a region with a `usize` size & alignment
+  ///
+  /// See [`alloc_usize`](Self::alloc_usize), &
+  /// [`alloc_layout`](Self::alloc_layout) for allocating from a
+  /// [`Layout`](::core::alloc::Layout).
+  ///
+  /// Returns `None` if `size` or `align` doesn't fit in a `u32`, otherwise
+  /// see [`alloc_with_align`](Self::alloc_with_align).
+  pub fn alloc_with_align_usize(
+    &mut self,
+    size: usize,
+    align: usize,
+  ) -> Option<Allocation> {
+    self.alloc_with_align(
+      Size::try_from(size).ok()?,
+      Size::try_from(align).ok()?,
+    )
+  }
+
+  /// Try to allocate a region with the provided size & alignment, and report
+  /// how the chosen free-region was split
+  ///
+  /// This behaves exactly like [`alloc_with_align`](Self::alloc_with_align),
+  /// but also returns an [`AlignReport`] which can be used to quantify the
+  /// overhead of alignment at a particular call site.
+  pub fn alloc_with_align_report(
+    &mut self,
+    size: Size,
+    align: Size,
+  ) -> Option<(Allocation, AlignReport)> {
+    let class = self.size_class(size);
+    let result = self
+      .within_watermark(class)
+      .then(|| self.alloc_colored(class, align, self.placement))
+      .flatten();
+    self.record_attempt(result.is_some());
+    #[cfg(feature = "std")]
+    self.emit_alloc(size, align, result.map(|(allocation, _)| allocation));
+    result
+  }
+
+  /// Try to allocate a region with the provided size & alignment, ignoring the
+  /// soft watermark
+  ///
+  /// This is intended for critical allocations which must not be starved by
+  /// e.g. background streaming; see [`set_watermark`](Self::set_watermark).
+  /// Otherwise this behaves exactly like
+  /// [`alloc_with_align`](Self::alloc_with_align).
+  pub fn alloc_privileged(
+    &mut self,
+    size: Size,
+    align: Size,
+  ) -> Option<Allocation> {
+    let result = self
+      .alloc_colored(self.size_class(size), align, self.placement)
+      .map(|(allocation, _)| allocation);
+    self.record_attempt(result.is_some());
+    #[cfg(feature = "std")]
+    self.emit_alloc(size, align, result);
+    result
+  }
+
+  /// Try to allocate a region with the provided size & alignment, using
+  /// `placement` for this request only
+  ///
+  /// This is intended for hinting where an allocation should go, e.g.
+  /// [`Placement::Hot`] for frequently rewritten data & [`Placement::Cold`]
+  /// for long-lived data. A [`Placement::Random`] request draws from the
+  /// allocator's generator, & its seed is ignored. Otherwise this behaves
+  /// exactly like [`alloc_with_align`](Self::alloc_with_align).
+  pub fn alloc_with_placement(
+    &mut self,
+    size: Size,
+    align: Size,
+    placement: Placement,
+  ) -> Option<Allocation> {
+    let class = self.size_class(size);
+    let result = self
+      .within_watermark(class)
+      .then(|| self.alloc_colored(class, align, placement))
+      .flatten()
+      .map(|(allocation, _)| allocation);
+    self.record_attempt(result.is_some());
+    #[cfg(feature = "std")]
+    self.emit_alloc(size, align, result);
+    result
+  }
+
+  /// Try to allocate `count` whole extents of `extent_size` bytes, e.g. for
+  /// page-based storage
+  ///
+  /// Both the offset & size of the returned [`Allocation`] are multiples of
+  /// `extent_size`, so it can be converted to & from page numbers directly.
+  /// Sizes are not rounded by [size classes](Self::set_size_classes) or
+  /// [granularity](Self::set_granularity), since the size is already exact.
+  /// See also [`available_extents`](Self::available_extents).
+  ///
+  /// Returns `None` if:
+  /// - there are no free-regions which can fit the extents, or
+  /// - `count == 0` or `extent_size == 0`, or
+  /// - `count * extent_size` overflows.
+  pub fn alloc_extent(
+    &mut self,
+    count: Size,
+    extent_size: Size,
+  ) -> Option<Allocation> {
+    let size = count.checked_mul(extent_size)?;
+    let result = self
+      .within_watermark(size)
+      .then(|| {
+        self.alloc_unchecked_watermark(size, extent_size, self.placement)
+      })
+      .flatten()
+      .map(|(allocation, _)| allocation);
+    self.record_attempt(result.is_some());
+    #[cfg(feature = "std")]
+    self.emit_alloc(size, extent_size, result);
+    result
+  }
+
+  /// Try to allocate `count` pages of `page_size` bytes, which need not be
+  /// contiguous, e.g. for sparse-binding resources or virtual memory
+  ///
+  /// Each page is aligned to `page_size`, and returned as its own
+  /// [`Allocation`], in order of location. Pages are taken from the smallest
+  /// free-regions first, so fragmented space is used up before the large
+  /// free-regions are split. This is all-or-nothing; if there are not enough
+  /// pages in total, the allocator is left unchanged. Pages can be freed
+  /// individually, or together with [`free_many`](Self::free_many), which
+  /// coalesces neighbouring pages in one pass.
+  ///
+  /// Like [`alloc_extent`](Self::alloc_extent), sizes are not rounded, and
+  /// [`available_extents`](Self::available_extents) counts the pages which
+  /// could be allocated. Frees deferred by
+  /// [`set_deferred_coalescing`](Self::set_deferred_coalescing) are coalesced
+  /// first.
+  ///
+  /// Returns `None` if:
+  /// - there are fewer than `count` aligned pages free in total, or
+  /// - `count == 0` or `page_size == 0`, or
+  /// - `count * page_size` overflows.
+  pub fn alloc_pages(
+    &mut self,
+    count: Size,
+    page_size: Size,
+  ) -> Option<Vec<Allocation>> {
+    let size = count.checked_mul(page_size)?;
+    let result = self
+      .within_watermark(size)
+      .then(|| self.alloc_pages_unchecked_watermark(count, page_size))
+      .flatten();
+    self.record_attempt(result.is_some());
+    #[cfg(feature = "std")]
+    match &result {
+      Some(pages) => {
+        for &page in pages {
+          self.emit(AllocatorEvent::Alloc(page));
+        }
+      },
+      None => self.emit_alloc(size, page_size, None),
+    }
+    result
+  }
+
+  /// The implementation of [`alloc_pages`](Self::alloc_pages), without
+  /// checking the watermark
+  fn alloc_pages_unchecked_watermark(
+    &mut self,
+    count: Size,
+    page_size: Size,
+  ) -> Option<Vec<Allocation>> {
+    let page_size = NonZero::new(page_size)?;
+    let align = self.effective_align(page_size)?.get() as u64;
+    if count == 0 {
+      return None;
+    }
+    self.coalesce_all();
+
+    // plan the runs of pages before touching the free-lists, so nothing
+    // changes if there are not enough
+    let mut runs = Vec::new();
+    let mut remaining = count;
+    let mut visited = 0;
+    for &region in &self.free {
+      if remaining == 0 {
+        break;
+      }
+      visited += 1;
+      let start = (region.location as u64).next_multiple_of(align);
+      let end = region.location as u64 + region.size.get() as u64;
+      let pages = (end.saturating_sub(start) / page_size.get() as u64)
+        .min(remaining as u64) as Size;
+      if pages != 0 {
+        runs.push((region, start as Location, pages));
+        remaining -= pages;
+      }
+    }
+    self.count_lookup(visited);
+    if remaining != 0 {
+      return None;
+    }
+
+    let usage = self.usage();
+    let mut pages = Vec::with_capacity(count as usize);
+    for (region, start, count) in runs {
+      let size = page_size
+        .checked_mul(NonZero::new(count).unwrap_or_else(|| unreachable!()))
+        .unwrap_or_else(|| unreachable!());
+      self.claim(region, start, size);
+      pages.extend((0..count).map(|page| Allocation {
+        offset: start + page * page_size.get(),
+        size: page_size,
+      }));
+    }
+    self.notify_thresholds(usage);
+
+    pages.sort_unstable_by_key(|page| page.offset);
+    #[cfg(feature = "wear-stats")]
+    for &page in &pages {
+      self.record_wear(page);
+    }
+    Some(pages)
+  }
+
+  /// The implementation of [`alloc_with_align_report`], without checking the
+  /// watermark, offsetting the allocation by its color if
+  /// [coloring](Self::set_coloring) is enabled
+  ///
+  /// [`alloc_with_align_report`]: Self::alloc_with_align_report
+  fn alloc_colored(
+    &mut self,
+    size: Size,
+    align: Size,
+    placement: Placement,
+  ) -> Option<(Allocation, AlignReport)> {
+    let Some(Coloring { stride, colors }) = self.coloring else {
+      return self.alloc_unchecked_watermark(size, align, placement);
+    };
+    let size = NonZero::new(size)?;
+    let align = self.effective_align(NonZero::new(align)?)?;
+    if !stride.is_multiple_of(align.get()) {
+      return self.alloc_unchecked_watermark(
+        size.get(),
+        align.get(),
+        placement,
+      );
+    }
+
+    // note: the allocation is placed `jitter` bytes past a location aligned
+    // to the whole cycle of colors
+    let (last_size, last_color) = self.color_cursor;
+    let color = if last_size == size.get() {
+      (last_color + 1) % colors
+    } else {
+      0
+    };
+    let result = self.alloc_jittered(
+      size.get(),
+      color * stride,
+      stride * colors,
+      placement,
+    )?;
+    self.color_cursor = (size.get(), color);
+    Some(result)
+  }
+
+  /// The implementation of [`alloc_with_align_report`], without checking the
+  /// watermark or applying [coloring](Self::set_coloring)
+  ///
+  /// [`alloc_with_align_report`]: Self::alloc_with_align_report
+  fn alloc_unchecked_watermark(
+    &mut self,
+    size: Size,
+    align: Size,
+    placement: Placement,
+  ) -> Option<(Allocation, AlignReport)> {
+    self.alloc_jittered(size, 0, align, placement)
+  }
+
+  /// Allocate `size` bytes, `jitter` bytes past an aligned location, without
+  /// checking the watermark
+  ///
+  /// The jitter is left in the free-lists, & reported as padding.
+  fn alloc_jittered(
+    &mut self,
+    size: Size,
+    jitter: Size,
+    align: Size,
+    placement: Placement,
+  ) -> Option<(Allocation, AlignReport)> {
+    let extended = NonZero::new(size.checked_add(jitter)?)?;
     let size = NonZero::new(size)?;
-    let align = NonZero::new(align)?;
+    let align = self.effective_align(NonZero::new(align)?)?;
 
     let FreeRegion {
-      location: mut free_region_location,
+      location: free_region_location,
       size: free_region_size,
-    } = self.find_free_region(size.checked_add(align.get() - 1)?)?;
+    } = self.select_free_region(extended, align, placement)?;
 
+    if let Placement::Random { .. } = placement {
+      self.rng = self.rng.wrapping_add(SPLITMIX_GAMMA);
+    }
     self.remove_free_region(free_region_location, free_region_size);
 
-    let mut free_region_size = free_region_size.get();
-
-    if let Some(misalignment) =
-      NonZero::new((align.get() - (free_region_location % align)) % align)
-    {
-      self.insert_free_region(free_region_location, misalignment);
-      free_region_location += misalignment.get();
-      free_region_size -= misalignment.get();
+    let end = free_region_location + free_region_size.get();
+    let start = match placement {
+      // note: cold allocations are carved from the end of the free-region
+      Placement::Cold => {
+        let last = end - extended.get();
+        last - last % align
+      },
+      _ => {
+        free_region_location
+          + (align.get() - (free_region_location % align)) % align
+      },
+    };
+    let offset = start + jitter;
+    let before = NonZero::new(offset - free_region_location);
+    let after = NonZero::new(end - (offset + size.get()));
+    if let Some(before) = before {
+      self.insert_free_region(free_region_location, before);
+    }
+    if let Some(after) = after {
+      self.insert_free_region(offset + size.get(), after);
     }
 
-    if let Some(size_leftover) = NonZero::new(free_region_size - size.get()) {
-      self
-        .insert_free_region(free_region_location + size.get(), size_leftover);
+    // note: the jitter is part of the padding; for cold allocations it
+    // borders the leftover, which is before the allocation
+    let mut report = AlignReport::default();
+    let (padding, leftover) = match placement {
+      Placement::Cold => (
+        after.map_or(0, NonZero::get) + jitter,
+        before.map_or(0, NonZero::get) - jitter,
+      ),
+      _ => (
+        before.map_or(0, NonZero::get),
+        after.map_or(0, NonZero::get),
+      ),
+    };
+    if padding != 0 {
+      report.padding = padding;
+      self.counters.alignment_padding += padding as u64;
+      self.counters.alignment_fragments += 1;
+    }
+    if leftover != 0 {
+      report.leftover = leftover;
+      self.counters.splits += 1;
     }
 
+    let usage = self.usage();
     self.available -= size.get();
+    self.notify_thresholds(usage);
 
-    Some(Allocation {
-      size,
-      offset: free_region_location,
-    })
+    let allocation = Allocation { size, offset };
+    #[cfg(feature = "wear-stats")]
+    self.record_wear(allocation);
+    Some((allocation, report))
   }
 
-  /// Free the given allocation
+  /// Try to allocate several regions at once
   ///
-  /// # Panics
-  ///
-  /// - May panic if the allocation's location gets freed twice, without first
-  ///   being re-allocated.
+  /// Takes a slice of `(size, align)` requests, and returns the corresponding
+  /// [`Allocation`]s in the same order. This is all-or-nothing; either every
+  /// request is satisfied, or none are.
   ///
-  ///   Note: This panic will not catch all double frees.
-  pub fn free(&mut self, alloc: Allocation) {
-    let mut free_region = FreeRegion {
-      location: alloc.offset,
-      size: alloc.size,
-    };
+  /// Returns `None` if any of the requests could not be satisfied by
+  /// [`alloc_with_align`](Self::alloc_with_align). In this case the allocator
+  /// is left unchanged.
+  pub fn alloc_many(
+    &mut self,
+    requests: &[(Size, Size)],
+  ) -> Option<Vec<Allocation>> {
+    let mut allocations = Vec::with_capacity(requests.len());
 
-    // coalesce
-    {
-      if let Some(FreeRegion { location, size }) =
-        self.previous_free_region(alloc.offset)
-      {
-        if location + size.get() == free_region.location {
-          self.remove_free_region(location, size);
-          free_region.location = location;
-          // note: this unwrap is ok because the sum of all free-regions cannot
-          // be larger than the total size of the allocator; which we know is
-          // some `Size`.
-          free_region.size = free_region.size.checked_add(size.get()).unwrap();
+    for &(size, align) in requests {
+      let Some(allocation) = self.alloc_with_align(size, align) else {
+        for allocation in allocations.into_iter().rev() {
+          self.free_now(allocation);
         }
+        return None;
       };
-
-      if let Some(FreeRegion { location, size }) =
-        self.following_free_region(alloc.offset)
-      {
-        if free_region.location + free_region.size.get() == location {
-          self.remove_free_region(location, size);
-          // note: this unwrap is ok because the sum of all free-regions cannot
-          // be larger than the total size of the allocator; which we know is
-          // some `Size`.
-          free_region.size = free_region.size.checked_add(size.get()).unwrap();
-        }
-      }
+      allocations.push(allocation);
     }
 
-    self.insert_free_region(free_region.location, free_region.size);
-    self.available += alloc.size.get();
+    Some(allocations)
   }
 
-  /// Free ***all*** allocations
-  pub fn reset(&mut self) {
-    self.free.clear();
-    self.location_map.clear();
-    self.available = self.capacity.get();
-    self.insert_free_region(0, self.capacity);
+  /// Try to allocate a region with the provided size & alignment, giving the
+  /// caller one chance to free up space if the pool is too fragmented
+  ///
+  /// If the allocation fails, but there is at least `size` bytes available in
+  /// total, `emergency` is called with the allocator; e.g. to process pending
+  /// deferred frees, or to carry out a compaction plan. The allocation is then
+  /// retried once.
+  ///
+  /// `emergency` is not called if there is simply not enough space, since no
+  /// amount of defragmentation could help.
+  ///
+  /// Returns `None` if the allocation fails, including after the retry.
+  pub fn alloc_with_retry(
+    &mut self,
+    size: Size,
+    align: Size,
+    emergency: impl FnOnce(&mut Allocator),
+  ) -> Option<Allocation> {
+    if let Some(allocation) = self.alloc_with_align(size, align) {
+      return Some(allocation);
+    }
+    if size == 0 || self.available < size {
+      return None;
+    }
+
+    emergency(self);
+    self.alloc_with_align(size, align)
   }
 
-  /// Add new free space at the end of the allocator
+  /// Try to allocate a region with the provided size, as close as possible
+  /// to `offset`
   ///
-  /// Returns `Err(Overflow)` if `self.capacity + additional` would overflow.
-  pub fn grow_capacity(&mut self, additional: Size) -> Result<(), Overflow> {
-    let Some(additional) = NonZero::new(additional) else {
-      return Ok(()); // `additional` is zero, so do nothing
-    };
+  /// This prefers the free-regions nearest to `offset` over the best-fitting
+  /// one, so related resources end up adjacent; e.g. for partial buffer
+  /// uploads, or cache locality in the external memory. Within the chosen
+  /// free-region, the allocation is placed as close to `offset` as it fits.
+  ///
+  /// Finding the nearest free-region is linear in the number of free-regions
+  /// between it & `offset`.
+  ///
+  /// The [size classes](Self::set_size_classes) &
+  /// [watermark](Self::set_watermark) apply, but the
+  /// [placement](Self::set_placement) & [coloring](Self::set_coloring) are
+  /// ignored, since `offset` decides where the allocation goes.
+  ///
+  /// Returns `None` if:
+  /// - there are no free-regions with `size` available space, or
+  /// - `size == 0`.
+  pub fn alloc_near(
+    &mut self,
+    offset: Location,
+    size: Size,
+  ) -> Option<Allocation> {
+    let class = NonZero::new(self.size_class(size))
+      .filter(|class| self.within_watermark(class.get()));
+    let near = class.and_then(|class| self.find_near(offset, class));
+    self.record_attempt(near.is_some());
 
-    let current_capacity = self.capacity;
-    let Some(new_capacity) = current_capacity.checked_add(additional.get())
-    else {
-      return Err(Overflow {
-        current_capacity,
-        additional,
-      });
+    let (Some(size), Some((region, location))) = (class, near) else {
+      #[cfg(feature = "std")]
+      self.emit_alloc(size, 1, None);
+      return None;
     };
+    self.remove_free_region(region.location, region.size);
+    if let Some(leading) = NonZero::new(location - region.location) {
+      self.insert_free_region(region.location, leading);
+      self.counters.splits += 1;
+    }
+    let end = location + size.get();
+    let region_end = region.location + region.size.get();
+    if let Some(trailing) = NonZero::new(region_end - end) {
+      self.insert_free_region(end, trailing);
+      self.counters.splits += 1;
+    }
 
-    self.capacity = new_capacity;
-    self.free(Allocation {
-      offset: current_capacity.get(),
-      size: additional,
-    });
-    Ok(())
+    let usage = self.usage();
+    self.available -= size.get();
+    self.notify_thresholds(usage);
+
+    let allocation = Allocation {
+      offset: location,
+      size,
+    };
+    #[cfg(feature = "wear-stats")]
+    self.record_wear(allocation);
+    #[cfg(feature = "std")]
+    self.emit(AllocatorEvent::Alloc(allocation));
+    Some(allocation)
   }
 
-  /// Try to re-size an existing allocation in-place
+  /// Free the given allocation
   ///
-  /// Will not change the offset of the allocation and tries to expand the
-  /// allocation to the right if there is sufficient free space.
+  /// # Panics
   ///
-  /// Returns:
-  /// - `Ok(Allocation)` on success.
+  /// - May panic if the allocation's location gets freed twice, without first
+  ///   being re-allocated.
+  ///
+  ///   Note: This panic will not catch all double frees.
+  pub fn free(&mut self, alloc: Allocation) {
+    self.free_report(alloc);
+  }
+
+  /// Free the given allocation, and report the free-region it became part of
+  ///
+  /// The returned region includes any neighbouring free-regions the
+  /// allocation was coalesced with. This can be used to decide e.g. whether a
+  /// pending large request can now be satisfied, without re-querying. If
+  /// [coalescing is deferred](Self::set_deferred_coalescing), the allocation
+  /// is returned unchanged.
+  ///
+  /// # Panics
+  ///
+  /// - May panic if the allocation's location gets freed twice, see
+  ///   [`free`](Self::free).
+  pub fn free_report(&mut self, alloc: Allocation) -> Allocation {
+    self.run_free_hooks(alloc);
+    if let Some(deferred) = &mut self.deferred {
+      deferred.push(alloc);
+      #[cfg(feature = "std")]
+      self.emit(AllocatorEvent::Free(alloc));
+      return alloc;
+    }
+    self.free_now(alloc)
+  }
+
+  /// Free the given allocation straight away, even if
+  /// [coalescing is deferred](Self::set_deferred_coalescing)
+  ///
+  /// This is for rolling back allocations made earlier in the same
+  /// operation. The free-lists are always fully coalesced, since deferred
+  /// frees stay out of them until [`coalesce_all`](Self::coalesce_all), so
+  /// freeing in reverse order restores exactly the state before the
+  /// allocations.
+  pub(crate) fn free_now(&mut self, alloc: Allocation) -> Allocation {
+    let usage = self.usage();
+    let FreeRegion { location, size } = self.release(alloc.offset, alloc.size);
+    self.notify_thresholds(usage);
+    #[cfg(feature = "std")]
+    self.emit(AllocatorEvent::Free(alloc));
+
+    Allocation {
+      offset: location,
+      size,
+    }
+  }
+
+  /// Free part of an allocation, from `offset` to `offset + size`
+  ///
+  /// `offset` is a location in the pool, not relative to the allocation.
+  /// Returns the parts of `alloc` before & after the freed range which remain
+  /// allocated; each of these can then be freed or reallocated separately.
+  /// This is useful when part of a large block becomes obsolete, e.g. trimming
+  /// the mip tail of a streamed texture.
+  ///
+  /// # Panics
+  ///
+  /// - Panics if `size == 0`.
+  /// - Panics if the range is not entirely within `alloc`.
+  /// - May panic if the allocation's location gets freed twice, see
+  ///   [`free`](Self::free).
+  pub fn free_subrange(
+    &mut self,
+    alloc: Allocation,
+    offset: Location,
+    size: Size,
+  ) -> (Option<Allocation>, Option<Allocation>) {
+    let freed = NonZero::new(size)
+      .map(|size| Allocation { offset, size })
+      .expect("`size` must be non-zero");
+    let Range { start, end } = freed.range();
+    assert!(
+      alloc.range().start <= start && end <= alloc.range().end,
+      "{freed:?} is not within {alloc:?}"
+    );
+
+    self.run_free_hooks(freed);
+    let usage = self.usage();
+    self.release(freed.offset, freed.size);
+    self.notify_thresholds(usage);
+    #[cfg(feature = "std")]
+    self.emit(AllocatorEvent::Free(freed));
+
+    let freed_end = freed.offset + freed.size();
+    let before =
+      NonZero::new(freed.offset - alloc.offset).map(|size| Allocation {
+        offset: alloc.offset,
+        size,
+      });
+    let after =
+      NonZero::new(alloc.offset + alloc.size() - freed_end).map(|size| {
+        Allocation {
+          offset: freed_end,
+          size,
+        }
+      });
+    (before, after)
+  }
+
+  /// Split an allocation into two parts, which can then be freed or
+  /// reallocated independently
+  ///
+  /// The first part is the first `at` bytes of `alloc`, and the second part
+  /// is the rest. This is useful when a bulk allocation is later divided
+  /// among several owners.
+  ///
+  /// The allocator doesn't record the sizes of its allocations, so this
+  /// doesn't change its state; see
+  /// [`TrackedAllocator::split_allocation`] for a version which keeps track
+  /// of both parts.
+  ///
+  /// # Panics
+  ///
+  /// - Panics if `at == 0` or `at >= alloc.size()`.
+  pub fn split_allocation(
+    &self,
+    alloc: Allocation,
+    at: Size,
+  ) -> (Allocation, Allocation) {
+    let (Some(first), Some(second)) = (
+      NonZero::new(at),
+      alloc.size().checked_sub(at).and_then(NonZero::new),
+    ) else {
+      panic!("cannot split {alloc:?} at {at}");
+    };
+    (
+      Allocation {
+        offset: alloc.offset,
+        size: first,
+      },
+      Allocation {
+        offset: alloc.offset + at,
+        size: second,
+      },
+    )
+  }
+
+  /// Merge two exactly adjacent allocations into one
+  ///
+  /// This is the inverse of [`split_allocation`](Self::split_allocation),
+  /// and can be used to consolidate ownership without freeing &
+  /// re-allocating, which could lose the space to another allocation. The
+  /// allocations may be given in either order.
+  ///
+  /// Like `split_allocation`, this doesn't change the allocator's state; see
+  /// [`TrackedAllocator::merge_adjacent`] for a version which keeps track of
+  /// the merged allocation.
+  ///
+  /// Returns `None` if the allocations are not exactly adjacent.
+  pub fn merge_adjacent(
+    &self,
+    a: Allocation,
+    b: Allocation,
+  ) -> Option<Allocation> {
+    let (first, second) = if a.offset <= b.offset { (a, b) } else { (b, a) };
+    (first.range().end == second.range().start).then(|| Allocation {
+      offset: first.offset,
+      // note: this unwrap is ok because both allocations lie within the pool,
+      // whose size is some `Size`
+      size: first.size.checked_add(second.size()).unwrap(),
+    })
+  }
+
+  /// Free many allocations at once
+  ///
+  /// The allocations are sorted and neighbouring allocations are merged with
+  /// each other before being returned to the free-lists, so only a single
+  /// coalescing pass is needed. This is much cheaper than calling
+  /// [`free`](Self::free) for each allocation when tearing down large numbers
+  /// of allocations at once.
+  ///
+  /// # Panics
+  ///
+  /// - Panics if any of the given allocations overlap.
+  /// - May panic if any of the allocations' locations are already free.
+  pub fn free_many(&mut self, allocs: impl IntoIterator<Item = Allocation>) {
+    if let Some(deferred) = &mut self.deferred {
+      let start = deferred.len();
+      deferred.extend(allocs);
+      for &alloc in self.deferred.iter().flatten().skip(start) {
+        self.run_free_hooks(alloc);
+        #[cfg(feature = "std")]
+        self.emit(AllocatorEvent::Free(alloc));
+      }
+      return;
+    }
+    let mut allocs: Vec<Allocation> = allocs.into_iter().collect();
+    allocs.sort_unstable_by_key(|alloc| alloc.offset);
+    for &alloc in &allocs {
+      self.run_free_hooks(alloc);
+      #[cfg(feature = "std")]
+      self.emit(AllocatorEvent::Free(alloc));
+    }
+
+    let mut allocs = allocs.into_iter();
+    let Some(first) = allocs.next() else {
+      return; // nothing to free
+    };
+    let usage = self.usage();
+
+    let mut run = FreeRegion {
+      location: first.offset,
+      size: first.size,
+    };
+    for alloc in allocs {
+      let run_end = run.location + run.size.get();
+      assert!(
+        run_end <= alloc.offset,
+        "Double free. Tried to free {alloc:?}, which overlaps another \
+          allocation in the same batch"
+      );
+
+      if run_end == alloc.offset {
+        // note: this unwrap is ok because the sum of all allocations cannot
+        // be larger than the total size of the allocator; which we know is
+        // some `Size`.
+        run.size = run.size.checked_add(alloc.size.get()).unwrap();
+      } else {
+        self.release(run.location, run.size);
+        run = FreeRegion {
+          location: alloc.offset,
+          size: alloc.size,
+        };
+      }
+    }
+    self.release(run.location, run.size);
+    self.notify_thresholds(usage);
+  }
+
+  /// Free ***all*** allocations
+  pub fn reset(&mut self) {
+    self.clear_and_resize(self.capacity.get());
+  }
+
+  /// Free ***all*** allocations, and change the capacity to `new_capacity`
+  ///
+  /// The new capacity can be larger or smaller than the current one. This is
+  /// intended for pools whose backing buffer is reallocated, e.g. between
+  /// levels; unlike creating a new allocator, any configuration (such as
+  /// [`min_align`](Self::min_align)) is kept.
+  ///
+  /// Panics:
+  /// - Panics if `new_capacity == 0`
+  pub fn clear_and_resize(&mut self, new_capacity: Size) {
+    let new_capacity =
+      NonZero::new(new_capacity).expect("`new_capacity == 0`");
+    let usage = self.usage();
+    self.capacity = new_capacity;
+    #[cfg(feature = "wear-stats")]
+    self.resize_wear();
+    self.free.clear();
+    self.location_map.clear();
+    self.hints = Hints::default();
+    self.aligned_bins = Default::default();
+    if let Some(deferred) = &mut self.deferred {
+      deferred.clear();
+    }
+    self.available = self.capacity.get();
+    self.insert_free_region(0, self.capacity);
+    self.notify_thresholds(usage);
+    #[cfg(feature = "std")]
+    self.emit(AllocatorEvent::Reset);
+  }
+
+  /// Stop coalescing freed allocations with their neighbours immediately, or
+  /// go back to doing so
+  ///
+  /// While coalescing is deferred, [`free`](Self::free) &
+  /// [`free_many`](Self::free_many) just record the allocation, and the space
+  /// only becomes available again once [`coalesce_all`](Self::coalesce_all)
+  /// is called. This turns a massive batch of frees, e.g. a scene teardown,
+  /// into a single linear pass over the free-regions, rather than a pair of
+  /// neighbour queries per free. Other operations, like
+  /// [`try_reallocate`](Self::try_reallocate), still release space
+  /// immediately.
+  ///
+  /// Switching deferral off coalesces any pending frees first. Double frees
+  /// are only detected when the pending frees are coalesced.
+  pub fn set_deferred_coalescing(&mut self, deferred: bool) {
+    if deferred {
+      self.deferred.get_or_insert_with(Vec::new);
+    } else {
+      self.coalesce_all();
+      self.deferred = None;
+    }
+  }
+
+  /// Get the number of frees waiting to be coalesced
+  ///
+  /// See [`set_deferred_coalescing`](Self::set_deferred_coalescing).
+  pub fn pending_frees(&self) -> usize {
+    self.deferred.as_ref().map_or(0, Vec::len)
+  }
+
+  /// Return the pending frees to the free-lists, in a single linear pass
+  ///
+  /// The pending frees are merged with each other & the existing
+  /// free-regions in order of location, and the free-lists are then rebuilt
+  /// in bulk. Does nothing if there are no pending frees. See
+  /// [`set_deferred_coalescing`](Self::set_deferred_coalescing).
+  ///
+  /// # Panics
+  ///
+  /// - Panics if any of the pending frees overlap each other or a
+  ///   free-region.
+  pub fn coalesce_all(&mut self) {
+    let Some(mut pending) = self.deferred.take() else {
+      return;
+    };
+    if pending.is_empty() {
+      self.deferred = Some(pending);
+      return;
+    }
+    pending.sort_unstable_by_key(|alloc| alloc.offset);
+    let usage = self.usage();
+
+    // merge the pending frees with the free-regions, in order of location
+    let mut spans =
+      Vec::with_capacity(self.location_map.len() + pending.len());
+    let mut existing = self.location_map.iter().peekable();
+    let mut freed = pending.iter().peekable();
+    loop {
+      let span = match (existing.peek(), freed.peek()) {
+        (Some(&(&location, _)), Some(alloc)) if location < alloc.offset => {
+          existing
+            .next()
+            .map(|(&location, &size)| (location, size, false))
+        },
+        (_, Some(_)) => {
+          freed.next().map(|alloc| (alloc.offset, alloc.size, true))
+        },
+        (Some(_), None) => existing
+          .next()
+          .map(|(&location, &size)| (location, size, false)),
+        (None, None) => break,
+      };
+      spans.extend(span);
+    }
+
+    let end = |(location, size, _): (Location, NonZero<Size>, bool)| {
+      location + size.get()
+    };
+    let mut regions: Vec<FreeRegion> = Vec::with_capacity(spans.len());
+    let mut freed = 0;
+    let mut counters = self.counters;
+    for (index, &span) in spans.iter().enumerate() {
+      let (location, size, is_pending) = span;
+      if is_pending {
+        // count the merges as if the pending frees happened in order of
+        // location, so a pending free only merges right with a free-region
+        freed += size.get();
+        let left = index > 0 && end(spans[index - 1]) == location;
+        let right = spans
+          .get(index + 1)
+          .is_some_and(|&(next, _, pending)| !pending && next == end(span));
+        match (left, right) {
+          (true, false) => counters.coalesced_left += 1,
+          (false, true) => counters.coalesced_right += 1,
+          (true, true) => counters.coalesced_both += 1,
+          (false, false) => {},
+        }
+      }
+
+      match regions.last_mut() {
+        Some(last) if last.location + last.size.get() == location => {
+          // note: this unwrap is ok because the sum of all free-regions
+          // cannot be larger than the total size of the allocator; which we
+          // know is some `Size`.
+          last.size = last.size.checked_add(size.get()).unwrap();
+        },
+        last => {
+          assert!(
+            last.is_none_or(|last| last.location + last.size.get() <= location),
+            "Double free. Tried to free {alloc:?}, which overlaps a free-region \
+              or another pending free",
+            alloc = Allocation { offset: location, size },
+          );
+          regions.push(FreeRegion { location, size });
+        },
+      }
+    }
+
+    self.available += freed;
+    self.counters = counters;
+    self.replace_free_regions(regions);
+
+    pending.clear();
+    self.deferred = Some(pending);
+    self.notify_thresholds(usage);
+  }
+
+  /// Replace all of the free-regions in bulk
+  ///
+  /// `regions` must be sorted by location, & fully coalesced. `available` is
+  /// not updated.
+  fn replace_free_regions(&mut self, mut regions: Vec<FreeRegion>) {
+    // note: collecting from sorted input uses the B-trees' bulk-build path
+    self.location_map = regions
+      .iter()
+      .map(|region| (region.location, region.size))
+      .collect();
+    self.aligned_bins = Default::default();
+    for &region in &regions {
+      if let Some(bin) = aligned_bin(region.location) {
+        self.aligned_bins[bin].insert(region);
+      }
+    }
+    self.hints = Hints::default();
+    regions.sort_unstable();
+    self.free = regions.into_iter().collect();
+    self.counters.peak_free_regions =
+      self.counters.peak_free_regions.max(self.location_map.len());
+  }
+
+  /// Rebuild the internal free-lists compactly
+  ///
+  /// After a long period of churn the B-trees holding the free-regions can be
+  /// left with many sparsely-filled nodes. This rebuilds them from scratch in
+  /// bulk, packing the nodes densely; which improves cache behaviour for
+  /// subsequent operations. The allocator's state is not changed.
+  ///
+  /// This is linear in the number of free-regions.
+  pub fn rebuild(&mut self) {
+    // note: collecting from sorted input uses the B-trees' bulk-build path
+    self.free = ::core::mem::take(&mut self.free).into_iter().collect();
+    self.location_map = ::core::mem::take(&mut self.location_map)
+      .into_iter()
+      .collect();
+    for bin in &mut self.aligned_bins {
+      *bin = ::core::mem::take(bin).into_iter().collect();
+    }
+  }
+
+  /// Add new free space at the end of the allocator
+  ///
+  /// Returns `Err(Overflow)` if `self.capacity + additional` would overflow.
+  pub fn grow_capacity(&mut self, additional: Size) -> Result<(), Overflow> {
+    let Some(additional) = NonZero::new(additional) else {
+      return Ok(()); // `additional` is zero, so do nothing
+    };
+
+    let current_capacity = self.capacity;
+    let Some(new_capacity) = current_capacity.checked_add(additional.get())
+    else {
+      return Err(Overflow {
+        current_capacity,
+        additional,
+      });
+    };
+
+    let usage = self.usage();
+    self.capacity = new_capacity;
+    self.release(current_capacity.get(), additional);
+    #[cfg(feature = "wear-stats")]
+    self.resize_wear();
+    self.notify_thresholds(usage);
+    Ok(())
+  }
+
+  /// Try to re-size an existing allocation in-place
+  ///
+  /// Will not change the offset of the allocation and tries to expand the
+  /// allocation to the right if there is sufficient free space.
+  ///
+  /// Returns:
+  /// - `Ok(Allocation)` on success.
   /// - `Err(InsufficientSpace)` if there is not enough available space
   ///   to expand the allocation to `new_size`. In this case, the existing
   ///   allocation is left untouched.
@@ -326,8 +1590,21 @@ impl Allocator {
             new_alloc.offset + new_alloc.size(),
             new_free_region_size,
           );
+          self.counters.splits += 1;
         }
+        let usage = self.usage();
         self.available -= required_additional.get();
+        self.notify_thresholds(usage);
+        #[cfg(feature = "wear-stats")]
+        self.record_wear(Allocation {
+          offset: alloc.offset + alloc.size(),
+          size: required_additional,
+        });
+        #[cfg(feature = "std")]
+        self.emit(AllocatorEvent::Reallocate {
+          old: alloc,
+          new: new_alloc,
+        });
 
         Ok(new_alloc)
       },
@@ -335,15 +1612,24 @@ impl Allocator {
         // free the additional space
         let additional = NonZero::new(alloc.size() - new_size.get())
           .unwrap_or_else(|| unreachable!());
-        self.free(Allocation {
-          offset: alloc.offset + alloc.size() - additional.get(),
+        self.run_free_hooks(Allocation {
+          offset: alloc.offset + new_size.get(),
           size: additional,
         });
+        let usage = self.usage();
+        self.release(alloc.offset + new_size.get(), additional);
+        self.notify_thresholds(usage);
 
-        Ok(Allocation {
+        let new_alloc = Allocation {
           offset: alloc.offset,
           size: new_size,
-        })
+        };
+        #[cfg(feature = "std")]
+        self.emit(AllocatorEvent::Reallocate {
+          old: alloc,
+          new: new_alloc,
+        });
+        Ok(new_alloc)
       },
       Ordering::Equal => {
         // do nothing
@@ -352,6 +1638,188 @@ impl Allocator {
     }
   }
 
+  /// Change how free-regions are chosen to satisfy allocations
+  ///
+  /// This only affects future allocations; existing allocations are left
+  /// where they are. Setting [`Placement::Random`] (re-)seeds the generator,
+  /// so the same seed & sequence of operations always produces the same
+  /// layout.
+  pub fn set_placement(&mut self, placement: Placement) {
+    if let Placement::Random { seed } = placement {
+      self.rng = seed;
+    }
+    self.placement = placement;
+  }
+
+  /// Get how free-regions are chosen to satisfy allocations
+  ///
+  /// See [`set_placement`](Self::set_placement).
+  pub fn placement(&self) -> Placement {
+    self.placement
+  }
+
+  /// Make ordinary allocations fail once they would take the used space
+  /// beyond `percent` of the capacity
+  ///
+  /// This reserves the remaining space for critical allocations made with
+  /// [`alloc_privileged`](Self::alloc_privileged), so that e.g. background
+  /// streaming can't starve them. The limit applies to every method built on
+  /// [`alloc_with_align`](Self::alloc_with_align), but not to
+  /// [`try_reallocate`](Self::try_reallocate) or
+  /// [`take_largest_free_region`](Self::take_largest_free_region).
+  ///
+  /// The watermark is `100` by default; i.e. no limit.
+  ///
+  /// Panics:
+  /// - Panics if `percent > 100`
+  pub fn set_watermark(&mut self, percent: u8) {
+    assert!(percent <= 100, "`percent` must be at most 100");
+    self.watermark = percent;
+  }
+
+  /// Get the usage percentage beyond which ordinary allocations fail
+  ///
+  /// See [`set_watermark`](Self::set_watermark).
+  pub fn watermark(&self) -> u8 {
+    self.watermark
+  }
+
+  /// Choose whether aligned allocations first look for a free-region of
+  /// exactly the requested size, which is already aligned
+  ///
+  /// Taking an exact fit leaves no padding or leftover fragments behind at
+  /// all, whereas the padded search for `size + align - 1` usually splits a
+  /// larger region. Only a few exact-size regions are checked, so this costs
+  /// very little even when none of them are aligned.
+  ///
+  /// This is enabled by default, and only affects [`Placement::BestFit`].
+  pub fn set_exact_fit_first(&mut self, exact_fit_first: bool) {
+    self.exact_fit_first = exact_fit_first;
+  }
+
+  /// Round the sizes of future allocations up to one of `steps` size classes
+  /// per power of two, or stop rounding if `steps` is `None`
+  ///
+  /// e.g. with `Some(8)`, sizes from 1024 to 2048 are rounded up to a multiple
+  /// of 128. Freed regions are then more likely to exactly fit future
+  /// requests; this trades a bounded amount of internal waste (under
+  /// `1 / steps` of each allocation) for far fewer unusable slivers.
+  ///
+  /// The returned [`Allocation`]s report the rounded size. Sizes are not
+  /// rounded by default, or by [`try_reallocate`](Self::try_reallocate).
+  ///
+  /// Panics:
+  /// - Panics if `steps` is not a power of two
+  pub fn set_size_classes(&mut self, steps: Option<Size>) {
+    self.size_classes = steps.map(|steps| {
+      assert!(steps.is_power_of_two(), "`steps` must be a power of two");
+      NonZero::new(steps).unwrap_or_else(|| unreachable!())
+    });
+  }
+
+  /// Get the size an allocation of `size` would be rounded up to
+  ///
+  /// See [`set_size_classes`](Self::set_size_classes) &
+  /// [`set_granularity`](Self::set_granularity). Returns `size` unchanged if
+  /// sizes are not being rounded, or if rounding would overflow.
+  pub fn size_class(&self, size: Size) -> Size {
+    let class = match (self.size_classes, size.checked_ilog2()) {
+      (Some(steps), Some(magnitude)) => {
+        let step = 1 << magnitude.saturating_sub(steps.trailing_zeros());
+        size.checked_next_multiple_of(step).unwrap_or(size)
+      },
+      _ => size,
+    };
+    class
+      .checked_next_multiple_of(self.granularity.get())
+      .unwrap_or(class)
+  }
+
+  /// Get the largest size which [`size_class`](Self::size_class) leaves
+  /// unchanged, which is no larger than `size`
+  fn size_class_at_most(&self, size: Size) -> Size {
+    let class = match (self.size_classes, size.checked_ilog2()) {
+      (Some(steps), Some(magnitude)) => {
+        let step = 1 << magnitude.saturating_sub(steps.trailing_zeros());
+        size - size % step
+      },
+      _ => size,
+    };
+    class - class % self.granularity.get()
+  }
+
+  /// Round the sizes of future allocations up to a multiple of `granularity`
+  ///
+  /// Combined with a `min_align` of the same value (see
+  /// [`with_min_align`](Self::with_min_align)), this keeps every allocation
+  /// & free-region a whole number of `granularity`-sized pages. This is
+  /// applied after any [size classes](Self::set_size_classes). The default
+  /// is `1`.
+  ///
+  /// Panics:
+  /// - Panics if `granularity == 0`
+  pub fn set_granularity(&mut self, granularity: Size) {
+    self.granularity =
+      NonZero::new(granularity).expect("`granularity` must be non-zero");
+  }
+
+  /// Offset successive allocations of the same size from one another by
+  /// different multiples of a stride, or stop if `coloring` is `None`
+  ///
+  /// Buffers of the same size which all start at the same offset within a
+  /// power-of-two block tend to alias the same cache sets or memory banks.
+  /// With coloring, the `n`th of a run of same-size allocations is placed
+  /// `(n % colors) * stride` bytes past a location aligned to
+  /// `stride * colors`. The bytes skipped over are returned to the
+  /// free-lists, but this does cost more padding fragments than plain
+  /// allocations. It only applies to allocations whose alignment (including
+  /// the [`min_align`](Self::min_align)) divides the stride.
+  ///
+  /// Coloring is off by default.
+  ///
+  /// Panics:
+  /// - Panics if `stride` is not a power of two
+  /// - Panics if `colors == 0`
+  /// - Panics if `stride * colors` overflows
+  pub fn set_coloring(&mut self, coloring: Option<Coloring>) {
+    if let Some(Coloring { stride, colors }) = coloring {
+      assert!(stride.is_power_of_two(), "`stride` must be a power of two");
+      assert!(colors != 0, "`colors` must be non-zero");
+      assert!(
+        stride.checked_mul(colors).is_some(),
+        "`stride * colors` overflows"
+      );
+    }
+    self.coloring = coloring;
+    self.color_cursor = (0, 0);
+  }
+
+  /// Get how successive allocations of the same size are offset, if they are
+  ///
+  /// See [`set_coloring`](Self::set_coloring).
+  pub fn coloring(&self) -> Option<Coloring> {
+    self.coloring
+  }
+
+  /// Choose what happens if the allocator is dropped while allocations are
+  /// still outstanding
+  ///
+  /// This catches lifecycle bugs early. Frees which are
+  /// [deferred](Self::set_deferred_coalescing) count as freed, but aren't
+  /// coalesced. The default is [`DropCheck::Panic`] when `debug_assertions`
+  /// are enabled, and [`DropCheck::Off`] otherwise; set it to `Off` to opt
+  /// out.
+  pub fn set_drop_check(&mut self, drop_check: DropCheck) {
+    self.drop_check = drop_check;
+  }
+
+  /// Get the minimum alignment applied to every allocation
+  ///
+  /// See [`with_min_align`](Self::with_min_align).
+  pub fn min_align(&self) -> Size {
+    self.min_align.get()
+  }
+
   /// Get the total capacity of the pool
   pub fn capacity(&self) -> Size {
     self.capacity.get()
@@ -370,59 +1838,1164 @@ impl Allocator {
     self.free.last().map_or(0, |region| region.size.get())
   }
 
+  /// Get the size of the smallest available memory region in this pool
+  pub fn smallest_available(&self) -> Size {
+    self.free.first().map_or(0, |region| region.size.get())
+  }
+
+  /// Get the size of the free-region at the given `percentile`, by size
+  ///
+  /// Uses the nearest-rank method, so `0` gives the
+  /// [smallest](Self::smallest_available), `50` the median, and `100` the
+  /// [largest](Self::largest_available) free-region. This is intended to
+  /// drive heuristics, e.g. compacting once the median free-region drops below
+  /// some size.
+  ///
+  /// This walks the free-regions from whichever end is nearer, so it is
+  /// linear in the distance to the smallest or largest free-region.
+  ///
+  /// Returns `0` if there are no free-regions.
+  ///
+  /// Panics:
+  /// - Panics if `percentile > 100`
+  pub fn free_size_percentile(&self, percentile: u8) -> Size {
+    assert!(percentile <= 100, "`percentile` must be at most 100");
+    let len = self.free.len();
+    if len == 0 {
+      return 0;
+    }
+    let rank = (percentile as usize * len).div_ceil(100).max(1);
+    let region = if rank <= len / 2 {
+      self.free.iter().nth(rank - 1)
+    } else {
+      self.free.iter().nth_back(len - rank)
+    };
+    region.map_or(0, |region| region.size.get())
+  }
+
+  /// Get the number of whole extents of `extent_size` bytes which could be
+  /// allocated with [`alloc_extent`](Self::alloc_extent), in total
+  ///
+  /// Only the parts of free-regions which are aligned to `extent_size` count,
+  /// so this can be less than `total_available() / extent_size`.
+  ///
+  /// Returns `0` if `extent_size == 0`.
+  pub fn available_extents(&self, extent_size: Size) -> Size {
+    let Some(align) =
+      NonZero::new(extent_size).and_then(|size| self.effective_align(size))
+    else {
+      return 0;
+    };
+    let extent_size = extent_size as u64;
+    self
+      .location_map
+      .iter()
+      .map(|(&location, &size)| {
+        let start = (location as u64).next_multiple_of(align.get() as u64);
+        let end = location as u64 + size.get() as u64;
+        (end.saturating_sub(start) / extent_size) as Size
+      })
+      .sum()
+  }
+
   /// Returns true if there are no allocations
   pub fn is_empty(&self) -> bool {
     self.capacity.get() == self.available
   }
 
-  /// Returns an iterator over the unallocated regions
+  /// Returns true if no allocation could possibly succeed
   ///
-  /// This should be used **only** for gathering metadata about the internal
-  /// state of the allocator for debugging purposes.
+  /// This is the case when there is no free space left, or when no
+  /// free-region can fit even a single byte once [`min_align`] &
+  /// the [watermark] are taken into account.
   ///
-  /// You must not use this instead of allocating; subsequent calls to `alloc`
-  /// will freely allocate from the reported regions.
-  pub fn report_free_regions(
-    &self,
-  ) -> impl Iterator<Item = Allocation> + use<'_> {
-    self.free.iter().map(|free_region| Allocation {
-      offset: free_region.location,
-      size: free_region.size,
-    })
+  /// [`min_align`]: Self::min_align
+  /// [watermark]: Self::set_watermark
+  pub fn is_full(&self) -> bool {
+    self.peek_best_fit(1, 1).is_none()
   }
 
-  /// Try to find a region with at least `size`
-  fn find_free_region(&mut self, size: NonZero<Size>) -> Option<FreeRegion> {
-    self
-      .free
-      .range(FreeRegion { size, location: 0 }..)
-      .copied()
-      .next()
+  /// Get the fraction of the capacity which is allocated, from `0.0` to `1.0`
+  pub fn usage_ratio(&self) -> f64 {
+    let (used, capacity) = self.usage();
+    used as f64 / capacity as f64
   }
 
-  /// Get the first free-region before `location`
-  fn previous_free_region(&self, location: Location) -> Option<FreeRegion> {
+  /// Report which free-region would be chosen to satisfy an allocation with
+  /// the given size & alignment, without allocating anything
+  ///
+  /// The whole free-region is returned, the allocation itself would be carved
+  /// out of it as described in [`alloc_with_align`](Self::alloc_with_align).
+  ///
+  /// This is intended for debugging placement decisions, and for external
+  /// planners.
+  ///
+  /// Returns `None` if the equivalent call to `alloc_with_align` would fail.
+  pub fn peek_best_fit(&self, size: Size, align: Size) -> Option<Allocation> {
+    let size = self.size_class(size);
+    if !self.within_watermark(size) {
+      return None;
+    }
+    let size = NonZero::new(size)?;
+    let align = self.effective_align(NonZero::new(align)?)?;
+
+    self.select_free_region(size, align, self.placement).map(
+      |FreeRegion { location, size }| Allocation {
+        offset: location,
+        size,
+      },
+    )
+  }
+
+  /// Query whether the location at `offset` is free or allocated
+  ///
+  /// Returns `None` if `offset` is out of bounds.
+  pub fn region_state(&self, offset: Location) -> Option<SpanState> {
+    if offset >= self.capacity.get() {
+      return None;
+    }
+
+    match self.containing_free_region(offset) {
+      Some(_) => Some(SpanState::Free),
+      None => Some(SpanState::Allocated),
+    }
+  }
+
+  /// Returns an iterator over the whole pool in address order, as alternating
+  /// runs of free & allocated space
+  ///
+  /// The ranges are contiguous, start at `0` and end at the capacity, and can
+  /// be used to directly index a buffer. This is the natural input for
+  /// visualizers & serializers.
+  ///
+  /// Note: The allocator doesn't track the boundaries between allocations, so
+  /// neighbouring allocations are reported as a single allocated run.
+  pub fn layout(
+    &self,
+  ) -> impl Iterator<Item = (Range<usize>, SpanState)> + use<'_> {
+    let end = self.capacity.get() as usize;
+    let mut cursor = 0;
+    let mut free_regions = self.location_map.iter().peekable();
+
+    ::core::iter::from_fn(move || {
+      if cursor >= end {
+        return None;
+      }
+      let (run_end, state) = match free_regions.peek() {
+        Some((&location, &size)) if location as usize == cursor => {
+          free_regions.next();
+          (cursor + size.get() as usize, SpanState::Free)
+        },
+        Some((&location, _)) => (location as usize, SpanState::Allocated),
+        None => (end, SpanState::Allocated),
+      };
+      let run = cursor..run_end;
+      cursor = run_end;
+      Some((run, state))
+    })
+  }
+
+  /// Get the first free-region which starts at or after `offset`
+  ///
+  /// If `offset` lies part-way through a free-region, that free-region is
+  /// skipped, & the next one is returned; see
+  /// [`region_state`](Self::region_state) to check `offset` itself. This is
+  /// intended for external defragmenters & placement heuristics.
+  ///
+  /// Returns `None` if there are no free-regions at or after `offset`.
+  pub fn next_free_region_after(
+    &self,
+    offset: Location,
+  ) -> Option<Allocation> {
+    self
+      .location_map
+      .range(offset..)
+      .next()
+      .map(|(&offset, &size)| Allocation { offset, size })
+  }
+
+  /// Returns true if the entire range from `offset` to `offset + size` is free
+  ///
+  /// Returns false if `size == 0`, or if the range extends past the end of the
+  /// pool.
+  pub fn is_range_free(&self, offset: Location, size: Size) -> bool {
+    let Some(end) = offset.checked_add(size) else {
+      return false;
+    };
+    if size == 0 {
+      return false;
+    }
+
+    // free-regions are always fully coalesced, so a free range must lie
+    // entirely within a single free-region
+    self
+      .containing_free_region(offset)
+      .is_some_and(|region| end <= region.location + region.size.get())
+  }
+
+  /// Check that `alloc` is plausibly a live allocation of this allocator
+  ///
+  /// This is cheap; two lookups of the free-regions by location. It is
+  /// intended for defensive callers to verify allocations received across an
+  /// API boundary before using them. Like
+  /// [`assert_disjoint_from`](Self::assert_disjoint_from), it cannot tell
+  /// whether the allocation was actually returned by this allocator, only
+  /// that it is consistent with its state.
+  ///
+  /// Note: Frees deferred by
+  /// [`set_deferred_coalescing`](Self::set_deferred_coalescing) are not
+  /// free-regions yet, so are not detected.
+  ///
+  /// Returns:
+  /// - `Err(InvalidAllocation::OutOfBounds)` if the allocation extends past
+  ///   the end of the pool.
+  /// - `Err(InvalidAllocation::OverlapsFree)` if the allocation overlaps a
+  ///   free-region; e.g. it has already been freed.
+  pub fn validate_allocation(
+    &self,
+    alloc: &Allocation,
+  ) -> Result<(), InvalidAllocation> {
+    let end = alloc
+      .offset
+      .checked_add(alloc.size.get())
+      .filter(|&end| end <= self.capacity.get())
+      .ok_or(InvalidAllocation::OutOfBounds)?;
+    let overlapping = self
+      .containing_free_region(alloc.offset)
+      .or_else(|| self.following_free_region(alloc.offset))
+      .filter(|region| region.location < end);
+    match overlapping {
+      Some(FreeRegion { location, size }) => {
+        Err(InvalidAllocation::OverlapsFree(Allocation {
+          offset: location,
+          size,
+        }))
+      },
+      None => Ok(()),
+    }
+  }
+
+  /// Check that the given allocations are plausibly live allocations of this
+  /// allocator
+  ///
+  /// This is a sanity check for tests of systems that cache [`Allocation`]s;
+  /// it cannot tell whether the allocations were actually returned by this
+  /// allocator, only that they are consistent with its state.
+  ///
+  /// # Panics
+  ///
+  /// - Panics if any of the allocations extends past the end of the pool.
+  /// - Panics if any two of the allocations overlap.
+  /// - Panics if any of the allocations overlaps a free-region.
+  pub fn assert_disjoint_from(&self, allocations: &[Allocation]) {
+    let mut allocations = allocations.to_vec();
+    allocations.sort_unstable_by_key(Allocation::offset);
+
+    let capacity = self.capacity.get() as usize;
+    let mut previous: Option<Allocation> = None;
+    for allocation in allocations {
+      let Range { start, end } = allocation.range();
+      assert!(
+        end <= capacity,
+        "{allocation:?} extends past the end of the pool ({capacity})"
+      );
+      if let Some(previous) = previous {
+        assert!(
+          previous.range().end <= start,
+          "{previous:?} overlaps {allocation:?}"
+        );
+      }
+      let overlapping = self
+        .containing_free_region(allocation.offset)
+        .or_else(|| self.following_free_region(allocation.offset))
+        .filter(|region| (region.location as usize) < end);
+      if let Some(region) = overlapping {
+        panic!("{allocation:?} overlaps free-region {region:?}");
+      }
+      previous = Some(allocation);
+    }
+  }
+
+  /// Count the free-regions falling into each of the given size buckets
+  ///
+  /// `bucket_edges` must be sorted in ascending order. Returns
+  /// `bucket_edges.len() + 1` buckets, where:
+  /// - the first bucket counts the free-regions smaller than
+  ///   `bucket_edges[0]`,
+  /// - bucket `i` counts the free-regions with
+  ///   `bucket_edges[i - 1] <= size < bucket_edges[i]`, and
+  /// - the last bucket counts the free-regions at least as large as the last
+  ///   edge.
+  ///
+  /// For example:
+  /// ```
+  /// # use ::orderly_allocator::Allocator;
+  /// let allocator = Allocator::new(10_000);
+  /// let histogram = allocator.free_size_histogram(&[64, 4096]);
+  /// assert_eq!(histogram.len(), 3);
+  /// assert_eq!(histogram[2].count, 1);
+  /// assert_eq!(histogram[2].total_size, 10_000);
+  /// ```
+  ///
+  /// # Panics
+  ///
+  /// - Panics if `bucket_edges` is not sorted.
+  pub fn free_size_histogram(
+    &self,
+    bucket_edges: &[Size],
+  ) -> Vec<HistogramBucket> {
+    assert!(
+      bucket_edges.is_sorted(),
+      "`bucket_edges` must be sorted in ascending order"
+    );
+
+    let mut buckets =
+      ::alloc::vec![HistogramBucket::default(); bucket_edges.len() + 1];
+
+    // `free` is sorted by size, so we only ever need to move forwards through
+    // the buckets
+    let mut bucket = 0;
+    for region in &self.free {
+      while bucket_edges
+        .get(bucket)
+        .is_some_and(|&edge| region.size.get() >= edge)
+      {
+        bucket += 1;
+      }
+      buckets[bucket].count += 1;
+      buckets[bucket].total_size += region.size.get();
+    }
+
+    buckets
+  }
+
+  /// Compute a hash of the allocator's logical state; its capacity & the
+  /// locations and sizes of its free-regions
+  ///
+  /// Two allocators with the same logical state always produce the same hash,
+  /// regardless of the sequence of operations that led to that state. The
+  /// hash is also stable across platforms & runs, so it can be compared
+  /// between processes, e.g. after replaying an operation log.
+  ///
+  /// note: This is a 64-bit FNV-1a hash; it is cheap, but is not resistant to
+  /// deliberate collisions.
+  pub fn state_hash(&self) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let words = ::core::iter::once(self.capacity.get()).chain(
+      self
+        .location_map
+        .iter()
+        .flat_map(|(&location, &size)| [location, size.get()]),
+    );
+
+    words
+      .flat_map(Size::to_le_bytes)
+      .fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+      })
+  }
+
+  /// Get a snapshot of the allocator's statistics
+  pub fn stats(&self) -> Stats {
+    Stats {
+      capacity: self.capacity.get(),
+      total_available: self.available,
+      largest_available: self.largest_available(),
+      free_regions: self.location_map.len(),
+      alignment_padding: self.counters.alignment_padding,
+      peak_free_regions: self.counters.peak_free_regions,
+      peak_btree_nodes: 2 * max_btree_nodes(self.counters.peak_free_regions),
+      splits: self.counters.splits,
+      alignment_fragments: self.counters.alignment_fragments,
+      coalesced_left: self.counters.coalesced_left,
+      coalesced_right: self.counters.coalesced_right,
+      coalesced_both: self.counters.coalesced_both,
+      failed_allocs: self.counters.failed_allocs,
+    }
+  }
+
+  /// Get a coarse measure of the memory pressure on the pool, with the
+  /// action advised to relieve it
+  ///
+  /// This combines the occupancy of the pool, the fragmentation of its free
+  /// space, & how many of the last 64 allocation requests failed, so that
+  /// memory policy can be decided in one place:
+  /// - [`High`](PressureLevel::High) if at least 90% of the pool is used, or
+  ///   at least 4 recent requests failed
+  /// - [`Medium`](PressureLevel::Medium) if at least 75% of the pool is used,
+  ///   any recent request failed, or the pool is at least half used & at
+  ///   least half of its free space is outside the largest free-region
+  /// - [`Low`](PressureLevel::Low) otherwise
+  ///
+  /// Fragmented pools are advised to [compact](PressureAction::Compact),
+  /// otherwise medium pressure is advised to
+  /// [evict](PressureAction::Evict), & high pressure to
+  /// [grow](PressureAction::Grow).
+  ///
+  /// For example:
+  /// ```
+  /// # use ::orderly_allocator::{Allocator, PressureAction, PressureLevel};
+  /// let mut allocator = Allocator::new(1_000);
+  /// assert_eq!(allocator.pressure().level, PressureLevel::Low);
+  /// let a = allocator.alloc(950).unwrap();
+  /// assert_eq!(allocator.alloc(100), None);
+  /// let pressure = allocator.pressure();
+  /// assert_eq!(pressure.level, PressureLevel::High);
+  /// assert_eq!(pressure.action, PressureAction::Grow);
+  /// # allocator.free(a);
+  /// ```
+  pub fn pressure(&self) -> Pressure {
+    let occupancy = self.usage_ratio();
+    let fragmentation = match self.available {
+      0 => 0.,
+      available => 1. - self.largest_available() as f64 / available as f64,
+    };
+    let recent_failures = self.counters.recent_allocs.count_ones();
+    let fragmented = occupancy >= 0.5 && fragmentation >= 0.5;
+
+    let level = if occupancy >= 0.9 || recent_failures >= 4 {
+      PressureLevel::High
+    } else if occupancy >= 0.75 || recent_failures > 0 || fragmented {
+      PressureLevel::Medium
+    } else {
+      PressureLevel::Low
+    };
+    let action = match level {
+      PressureLevel::Low => PressureAction::None,
+      _ if fragmented => PressureAction::Compact,
+      PressureLevel::Medium => PressureAction::Evict,
+      PressureLevel::High => PressureAction::Grow,
+    };
+
+    Pressure {
+      level,
+      action,
+      occupancy,
+      fragmentation,
+      recent_failures,
+    }
+  }
+
+  /// Summarise the external fragmentation of the pool
+  ///
+  /// Free-regions smaller than `unusable_below` are counted as unusable; pick
+  /// the smallest size the application would normally allocate. The report is
+  /// computed in a single pass over the free-regions, and implements
+  /// [`Display`](fmt::Display), so it is suitable for periodic logging.
+  ///
+  /// For example:
+  /// ```
+  /// # use ::orderly_allocator::Allocator;
+  /// let mut allocator = Allocator::new(1_000);
+  /// let a = allocator.alloc(10).unwrap();
+  /// let b = allocator.alloc(100).unwrap();
+  /// allocator.free(a);
+  /// let report = allocator.fragmentation_report(64);
+  /// assert_eq!(report.free_regions, 2);
+  /// assert_eq!(report.largest, 890);
+  /// assert_eq!(report.unusable, 10);
+  /// # allocator.free(b);
+  /// ```
+  pub fn fragmentation_report(
+    &self,
+    unusable_below: Size,
+  ) -> FragmentationReport {
+    let count = self.free.len();
+    let mut report = FragmentationReport {
+      free_regions: count,
+      ..FragmentationReport::default()
+    };
+    if count == 0 {
+      return report;
+    }
+
+    // `free` is sorted by size, so the percentiles are found by rank
+    let median_rank = (count - 1) / 2;
+    let p95_rank = (count * 95).div_ceil(100) - 1;
+    for (rank, region) in self.free.iter().enumerate() {
+      let size = region.size.get();
+      report.total += size;
+      if size < unusable_below {
+        report.unusable += size;
+      }
+      if rank == median_rank {
+        report.median = size;
+      }
+      if rank == p95_rank {
+        report.p95 = size;
+      }
+      report.largest = size;
+    }
+    report.mean = report.total as f64 / count as f64;
+
+    report
+  }
+
+  /// Report the differences between the free-regions of this allocator &
+  /// `other`
+  ///
+  /// Free-regions are compared exactly; a region which differs only in its
+  /// size is reported on both sides. Differences in capacity are not reported,
+  /// except as far as they affect the free-regions.
+  ///
+  /// This is intended for debugging divergence, e.g. between a live allocator
+  /// and one restored from a log.
+  pub fn diff(&self, other: &Allocator) -> FreeRegionDiff {
+    let mut diff = FreeRegionDiff::default();
+    let mut ours = self.location_map.iter().peekable();
+    let mut theirs = other.location_map.iter().peekable();
+
+    // both maps are sorted by location, so walk them in lockstep
+    loop {
+      let side = match (ours.peek(), theirs.peek()) {
+        (None, None) => break,
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(a), Some(b)) if a == b => {
+          ours.next();
+          theirs.next();
+          continue;
+        },
+        (Some(a), Some(b)) => a.cmp(b),
+      };
+      let (list, (&offset, &size)) = match side {
+        Ordering::Greater => (&mut diff.only_in_other, theirs.next().unwrap()),
+        _ => (&mut diff.only_in_self, ours.next().unwrap()),
+      };
+      list.push(Allocation { offset, size });
+    }
+
+    diff
+  }
+
+  /// Make this allocator an exact copy of `source`, reusing the memory of its
+  /// internal free-lists
+  ///
+  /// Only the free-regions which differ between the two allocators are
+  /// removed or inserted, so when `source` has changed little since the last
+  /// copy this is much cheaper than [`clone`](Clone::clone). This makes e.g.
+  /// snapshotting the allocator every frame for rollback affordable.
+  /// [`Clone::clone_from`] uses this.
+  pub fn copy_state_from(&mut self, source: &Allocator) {
+    let FreeRegionDiff {
+      only_in_self,
+      only_in_other,
+    } = self.diff(source);
+    for region in only_in_self {
+      self.remove_free_region(region.offset, region.size);
+    }
+    for region in only_in_other {
+      self.insert_free_region(region.offset, region.size);
+    }
+
+    self.capacity = source.capacity;
+    self.available = source.available;
+    self.min_align = source.min_align;
+    self.counters = source.counters;
+    self.placement = source.placement;
+    self.rng = source.rng;
+    #[cfg(feature = "wear-stats")]
+    self.wear.clone_from(&source.wear);
+    self.watermark = source.watermark;
+    #[cfg(target_has_atomic = "ptr")]
+    self.thresholds.clone_from(&source.thresholds);
+    #[cfg(target_has_atomic = "ptr")]
+    self.hooks.clone_from(&source.hooks);
+    #[cfg(feature = "std")]
+    self.events.clone_from(&source.events);
+    self.drop_check = source.drop_check;
+    self.size_classes = source.size_classes;
+    self.granularity = source.granularity;
+    self.exact_fit_first = source.exact_fit_first;
+    self.coloring = source.coloring;
+    self.color_cursor = source.color_cursor;
+    self.deferred.clone_from(&source.deferred);
+    #[cfg(feature = "op-costs")]
+    self.costs.clone_from(&source.costs);
+    self.hints = source.hints;
+  }
+
+  /// Returns an iterator over the unallocated regions
+  ///
+  /// The regions are ordered by size, then by location; so the largest
+  /// regions can be taken from the back. The exact number of regions is known
+  /// up-front.
+  ///
+  /// This should be used **only** for gathering metadata about the internal
+  /// state of the allocator for debugging purposes.
+  ///
+  /// You must not use this instead of allocating; subsequent calls to `alloc`
+  /// will freely allocate from the reported regions.
+  pub fn report_free_regions(
+    &self,
+  ) -> impl DoubleEndedIterator<Item = Allocation> + ExactSizeIterator + use<'_>
+  {
+    self.free.iter().map(|free_region| Allocation {
+      offset: free_region.location,
+      size: free_region.size,
+    })
+  }
+
+  /// Returns an iterator over the unallocated regions with at least `size`
+  /// space
+  ///
+  /// This only visits candidate regions, so it's cheap even when the pool is
+  /// fragmented into many small regions. The regions are ordered as in
+  /// [`report_free_regions`](Self::report_free_regions); smallest first.
+  ///
+  /// This is intended for planners, e.g. deciding where several large
+  /// resources could go. As with `report_free_regions`, you must not use this
+  /// instead of allocating.
+  pub fn free_regions_at_least(
+    &self,
+    size: Size,
+  ) -> impl DoubleEndedIterator<Item = Allocation> + use<'_> {
+    let size = NonZero::new(size).unwrap_or(NonZero::<Size>::MIN);
+    self
+      .free
+      .range(FreeRegion { size, location: 0 }..)
+      .map(|free_region| Allocation {
+        offset: free_region.location,
+        size: free_region.size,
+      })
+  }
+
+  /// Get the used space & the capacity
+  fn usage(&self) -> (u64, u64) {
+    let capacity = self.capacity.get() as u64;
+    (capacity - self.available as u64, capacity)
+  }
+
+  /// Returns true if allocating `size` more bytes keeps the used space within
+  /// the watermark
+  fn within_watermark(&self, size: Size) -> bool {
+    let (used, capacity) = self.usage();
+    (used + size as u64) * 100 <= capacity * self.watermark as u64
+  }
+
+  /// Record the outcome of an allocation request, for
+  /// [`pressure`](Self::pressure)
+  fn record_attempt(&mut self, succeeded: bool) {
+    self.counters.recent_allocs =
+      self.counters.recent_allocs << 1 | u64::from(!succeeded);
+    if !succeeded {
+      self.counters.failed_allocs += 1;
+    }
+  }
+
+  /// Emit the event for an attempted allocation
+  #[cfg(feature = "std")]
+  fn emit_alloc(&self, size: Size, align: Size, result: Option<Allocation>) {
+    self.emit(match result {
+      Some(allocation) => AllocatorEvent::Alloc(allocation),
+      None => AllocatorEvent::Failed { size, align },
+    });
+  }
+
+  /// Thresholds require `Arc`, so there is nothing to notify on targets
+  /// without atomics
+  #[cfg(not(target_has_atomic = "ptr"))]
+  fn notify_thresholds(&self, _usage: (u64, u64)) {}
+
+  /// Hooks require `Arc`, so there are none to invoke on targets without
+  /// atomics
+  #[cfg(not(target_has_atomic = "ptr"))]
+  fn run_free_hooks(&self, _alloc: Allocation) {}
+
+  /// Costs are only measured with the `op-costs` feature
+  #[cfg(not(feature = "op-costs"))]
+  fn count_lookup(&self, _visited: usize) {}
+
+  /// Costs are only measured with the `op-costs` feature
+  #[cfg(not(feature = "op-costs"))]
+  fn count_visited(&self, _visited: usize) {}
+
+  /// Costs are only measured with the `op-costs` feature
+  #[cfg(not(feature = "op-costs"))]
+  fn count_tree_update(&self) {}
+
+  /// Combine the requested alignment with the minimum alignment
+  ///
+  /// Returns `None` if the combined alignment overflows.
+  fn effective_align(&self, align: NonZero<Size>) -> Option<NonZero<Size>> {
+    let min_align = self.min_align.get();
+    if align.get().is_multiple_of(min_align) {
+      return Some(align);
+    }
+    // `min_align` is a power of two, so the least common multiple is `align`
+    // with its trailing zeros topped up to those of `min_align`
+    let shift = min_align.trailing_zeros() - align.trailing_zeros();
+    align.checked_mul(NonZero::new(1 << shift)?)
+  }
+
+  /// Choose the free-region that an allocation with the given size & alignment
+  /// would be carved from
+  fn select_free_region(
+    &self,
+    size: NonZero<Size>,
+    align: NonZero<Size>,
+    placement: Placement,
+  ) -> Option<FreeRegion> {
+    match placement {
+      Placement::Hot => return self.find_lowest_fit(size, align),
+      Placement::Cold => return self.find_highest_fit(size, align),
+      _ => {},
+    }
+
+    let padded_size = size.checked_add(align.get() - 1);
+    let largest = self.free.last().copied();
+    match (padded_size, placement) {
+      (Some(padded_size), Placement::BestFit) => {
+        // note: without padding, an exact fit is as good as any region
+        // best-fit could find
+        let hint = (padded_size == size)
+          .then(|| self.hints.exact_fit(size))
+          .flatten();
+        if hint.is_some() {
+          return hint;
+        }
+        if self.exact_fit_first && padded_size != size {
+          let exact_fit = self.find_aligned_exact_fit(size, align);
+          if exact_fit.is_some() {
+            return exact_fit;
+          }
+        }
+        let region = self.find_free_region(padded_size);
+        if padded_size == size {
+          return region;
+        }
+        // note: a pre-aligned region which is no larger than the best-fit
+        // doesn't leave behind a padding fragment
+        return match self.find_pre_aligned(size, align) {
+          Some(pre_aligned)
+            if region.is_none_or(|region| pre_aligned.size <= region.size) =>
+          {
+            Some(pre_aligned)
+          },
+          _ => region,
+        };
+      },
+      (Some(padded_size), Placement::PreserveLargest) => {
+        let region = self.find_free_region(padded_size)?;
+        if Some(region) != largest {
+          return Some(region);
+        }
+      },
+      _ => {},
+    }
+
+    // note: If no free-region can fit the worst-case padding, check how much
+    // padding each candidate actually needs. This is linear in the number of
+    // candidates, but regions this large are necessarily few.
+    let min_size = match placement {
+      Placement::PreserveLargest => size,
+      _ => padded_size.unwrap_or(size),
+    };
+    self.count_lookup(0);
+    let mut candidates = self
+      .free
+      .range(
+        FreeRegion {
+          size: min_size,
+          location: 0,
+        }..,
+      )
+      .copied()
+      .inspect(|_| self.count_visited(1))
+      .filter(|region| {
+        let misalignment = (align.get() - (region.location % align)) % align;
+        misalignment as u64 + size.get() as u64 <= region.size.get() as u64
+      });
+
+    match placement {
+      Placement::BestFit => candidates.next(),
+      Placement::PreserveLargest => {
+        // note: best-fit would split the largest free-region, so look for any
+        // other region which can fit the allocation once aligned. This is
+        // linear in the number of candidates.
+        let mut candidates = candidates.peekable();
+        let first = candidates.peek().copied();
+        candidates.find(|&region| Some(region) != largest).or(first)
+      },
+      Placement::Random { .. } => {
+        // note: counting the candidates is linear in the number of
+        // free-regions, which is the price of a uniform choice
+        let count = candidates.clone().count() as u64;
+        if count == 0 {
+          return None;
+        }
+        let index = splitmix64(self.rng.wrapping_add(SPLITMIX_GAMMA)) % count;
+        candidates.nth(index as usize)
+      },
+      Placement::Hot | Placement::Cold => unreachable!(),
+    }
+  }
+
+  /// Find the free-region nearest the start of the pool which can fit the
+  /// aligned allocation
+  ///
+  /// This is linear in the number of free-regions before it.
+  fn find_lowest_fit(
+    &self,
+    size: NonZero<Size>,
+    align: NonZero<Size>,
+  ) -> Option<FreeRegion> {
+    self.count_lookup(0);
     self
+      .location_map
+      .iter()
+      .map(|(&location, &size)| FreeRegion { location, size })
+      .inspect(|_| self.count_visited(1))
+      .find(|region| {
+        let misalignment = (align.get() - (region.location % align)) % align;
+        misalignment as u64 + size.get() as u64 <= region.size.get() as u64
+      })
+  }
+
+  /// Find the free-region nearest the end of the pool which can fit the
+  /// aligned allocation
+  ///
+  /// This is linear in the number of free-regions after it.
+  fn find_highest_fit(
+    &self,
+    size: NonZero<Size>,
+    align: NonZero<Size>,
+  ) -> Option<FreeRegion> {
+    self.count_lookup(0);
+    self
+      .location_map
+      .iter()
+      .rev()
+      .map(|(&location, &size)| FreeRegion { location, size })
+      .inspect(|_| self.count_visited(1))
+      .find(|region| {
+        let end = region.location + region.size.get();
+        end
+          .checked_sub(size.get())
+          .is_some_and(|last| last - last % align >= region.location)
+      })
+  }
+
+  /// Find the free-region nearest to `target` which can fit `size`, along with
+  /// the location within it nearest to `target`
+  fn find_near(
+    &self,
+    target: Location,
+    size: NonZero<Size>,
+  ) -> Option<(FreeRegion, Location)> {
+    use ::core::ops::Bound as B;
+    let target = target as u64;
+    let align = self.min_align.get() as u64;
+    let to_region = |(&location, &size): (&Location, &NonZero<Size>)| {
+      FreeRegion { location, size }
+    };
+    // the distance from `target` to the nearest location in the region
+    let lower_bound = |region: &FreeRegion| {
+      let start = region.location as u64;
+      let end = start + region.size.get() as u64;
+      start.saturating_sub(target) + target.saturating_sub(end - 1)
+    };
+    // the aligned location within the region nearest to `target`
+    let place = |region: &FreeRegion| {
+      let start = (region.location as u64).next_multiple_of(align);
+      let end = region.location as u64 + region.size.get() as u64;
+      let last = end.checked_sub(size.get() as u64)? / align * align;
+      (start <= last).then(|| (target / align * align).clamp(start, last))
+    };
+
+    self.count_lookup(0);
+    let mut before = self
+      .location_map
+      .range(..=target as Location)
+      .rev()
+      .map(to_region)
+      .inspect(|_| self.count_visited(1))
+      .peekable();
+    self.count_lookup(0);
+    let mut after = self
+      .location_map
+      .range((B::Excluded(target as Location), B::Unbounded))
+      .map(to_region)
+      .inspect(|_| self.count_visited(1))
+      .peekable();
+
+    let mut best: Option<(u64, FreeRegion, u64)> = None;
+    loop {
+      // visit whichever side has the nearer next region
+      let region = match (before.peek(), after.peek()) {
+        (Some(b), Some(a)) if lower_bound(a) < lower_bound(b) => after.next(),
+        (Some(_), _) => before.next(),
+        (None, Some(_)) => after.next(),
+        (None, None) => break,
+      };
+      let region = region.unwrap_or_else(|| unreachable!());
+      if best.is_some_and(|(distance, ..)| distance <= lower_bound(&region)) {
+        break;
+      }
+      if let Some(location) = place(&region) {
+        let distance = location.abs_diff(target);
+        if best.is_none_or(|(best_distance, ..)| distance < best_distance) {
+          best = Some((distance, region, location));
+        }
+      }
+    }
+
+    best.map(|(_, region, location)| (region, location as Location))
+  }
+
+  /// Try to find the smallest region with at least `size`, which already
+  /// starts at a location aligned to `align`
+  ///
+  /// Only regions in the aligned bins are considered, so this returns `None`
+  /// for alignments smaller than the smallest bin.
+  fn find_pre_aligned(
+    &self,
+    size: NonZero<Size>,
+    align: NonZero<Size>,
+  ) -> Option<FreeRegion> {
+    if !align.is_power_of_two() {
+      return None;
+    }
+    let alignment = align.trailing_zeros();
+    ALIGNED_BINS
+      .iter()
+      .zip(&self.aligned_bins)
+      .filter(|&(&bin, _)| bin >= alignment)
+      .filter_map(|(_, regions)| {
+        let region = regions.range(FreeRegion { size, location: 0 }..).next();
+        self.count_lookup(region.is_some() as usize);
+        region
+      })
+      .min()
+      .copied()
+  }
+
+  /// Try to find a region of exactly `size`, which already starts at a
+  /// location aligned to `align`
+  ///
+  /// Only the first few such regions are checked, see [`EXACT_FIT_PROBES`].
+  fn find_aligned_exact_fit(
+    &self,
+    size: NonZero<Size>,
+    align: NonZero<Size>,
+  ) -> Option<FreeRegion> {
+    self.count_lookup(0);
+    self
+      .free
+      .range(FreeRegion { size, location: 0 }..)
+      .take_while(|region| region.size == size)
+      .take(EXACT_FIT_PROBES)
+      .inspect(|_| self.count_visited(1))
+      .find(|region| region.location % align == 0)
+      .copied()
+  }
+
+  /// Try to find a region with at least `size`
+  fn find_free_region(&self, size: NonZero<Size>) -> Option<FreeRegion> {
+    let region = self
+      .free
+      .range(FreeRegion { size, location: 0 }..)
+      .copied()
+      .next();
+    self.count_lookup(region.is_some() as usize);
+    region
+  }
+
+  /// Get the first free-region before `location`
+  fn previous_free_region(&self, location: Location) -> Option<FreeRegion> {
+    let region = self
       .location_map
       .range(..location)
       .next_back()
-      .map(|(&location, &size)| FreeRegion { location, size })
+      .map(|(&location, &size)| FreeRegion { location, size });
+    self.count_lookup(region.is_some() as usize);
+    region
   }
 
   /// Get the first free-region after `location`
   fn following_free_region(&self, location: Location) -> Option<FreeRegion> {
     use ::core::ops::Bound as B;
-    self
+    let region = self
       .location_map
       .range((B::Excluded(location), B::Unbounded))
       .next()
-      .map(|(&location, &size)| FreeRegion { location, size })
+      .map(|(&location, &size)| FreeRegion { location, size });
+    self.count_lookup(region.is_some() as usize);
+    region
+  }
+
+  /// Get the free-region containing `location`, if any
+  fn containing_free_region(&self, location: Location) -> Option<FreeRegion> {
+    let region = self
+      .location_map
+      .range(..=location)
+      .next_back()
+      .map(|(&location, &size)| FreeRegion { location, size });
+    self.count_lookup(region.is_some() as usize);
+    region.filter(|region| location < region.location + region.size.get())
+  }
+
+  /// Return a region to the internal free lists, coalescing it with any
+  /// neighbouring free-regions
+  ///
+  /// Returns the resulting free-region.
+  fn release(&mut self, offset: Location, size: NonZero<Size>) -> FreeRegion {
+    let mut free_region = FreeRegion {
+      location: offset,
+      size,
+    };
+
+    // coalesce
+    let mut coalesced = (false, false);
+    {
+      if let Some(FreeRegion { location, size }) =
+        self.previous_free_region(offset)
+      {
+        if location + size.get() == free_region.location {
+          coalesced.0 = true;
+          self.remove_free_region(location, size);
+          free_region.location = location;
+          // note: this unwrap is ok because the sum of all free-regions cannot
+          // be larger than the total size of the allocator; which we know is
+          // some `Size`.
+          free_region.size = free_region.size.checked_add(size.get()).unwrap();
+        }
+      };
+
+      if let Some(FreeRegion { location, size }) =
+        self.following_free_region(offset)
+      {
+        if free_region.location + free_region.size.get() == location {
+          coalesced.1 = true;
+          self.remove_free_region(location, size);
+          // note: this unwrap is ok because the sum of all free-regions cannot
+          // be larger than the total size of the allocator; which we know is
+          // some `Size`.
+          free_region.size = free_region.size.checked_add(size.get()).unwrap();
+        }
+      }
+    }
+    match coalesced {
+      (true, false) => self.counters.coalesced_left += 1,
+      (false, true) => self.counters.coalesced_right += 1,
+      (true, true) => self.counters.coalesced_both += 1,
+      (false, false) => {},
+    }
+
+    self.insert_free_region(free_region.location, free_region.size);
+    self.available += size.get();
+
+    free_region
+  }
+
+  /// Move a live allocation to `to`, which must be free apart from any
+  /// overlap with the allocation itself
+  ///
+  /// This only moves the bookkeeping, for compaction; the usage doesn't
+  /// change, so thresholds are not notified.
+  pub(crate) fn relocate(
+    &mut self,
+    from: Allocation,
+    to: Location,
+  ) -> Allocation {
+    let region = self.release(from.offset, from.size);
+    assert!(
+      region.location <= to
+        && to + from.size.get() <= region.location + region.size.get(),
+      "tried to relocate {from:?} to {to}, which is not free"
+    );
+    self.claim(region, to, from.size);
+
+    let allocation = Allocation {
+      offset: to,
+      size: from.size,
+    };
+    #[cfg(feature = "wear-stats")]
+    self.record_wear(allocation);
+    #[cfg(feature = "std")]
+    {
+      self.emit(AllocatorEvent::Free(from));
+      self.emit(AllocatorEvent::Alloc(allocation));
+    }
+    allocation
+  }
+
+  /// Find the free-region with the most room once its start is aligned to
+  /// the `min_align`, returning it with the aligned start & the room
+  fn roomiest_free_region(&self) -> Option<(FreeRegion, Location, Size)> {
+    let largest = self.free.last()?.size;
+    let min_align = self.min_align.get();
+    // note: the padding is less than `min_align`, so only free-regions
+    // almost as large as the largest can have the most room
+    let smallest = NonZero::new(largest.get().saturating_sub(min_align - 1))
+      .unwrap_or(NonZero::<Size>::MIN);
+    self
+      .free
+      .range(
+        FreeRegion {
+          size: smallest,
+          location: 0,
+        }..,
+      )
+      .map(|&region| {
+        let end = region.location + region.size.get();
+        let start = region.location.checked_next_multiple_of(min_align);
+        let room = start.map_or(0, |start| end.saturating_sub(start));
+        (region, start.unwrap_or(region.location), room)
+      })
+      .max_by_key(|&(.., room)| room)
+  }
+
+  /// Allocate `[offset, offset + size)` from within the free-region
+  /// `region`, which must contain it
+  fn alloc_at(
+    &mut self,
+    region: FreeRegion,
+    offset: Location,
+    size: NonZero<Size>,
+  ) -> Allocation {
+    let usage = self.usage();
+    self.claim(region, offset, size);
+    self.notify_thresholds(usage);
+
+    let allocation = Allocation { offset, size };
+    #[cfg(feature = "wear-stats")]
+    self.record_wear(allocation);
+    allocation
+  }
+
+  /// Take `[start, start + size)` out of the free-region `region`, which must
+  /// contain it, returning the parts either side to the free-lists
+  fn claim(
+    &mut self,
+    region: FreeRegion,
+    start: Location,
+    size: NonZero<Size>,
+  ) {
+    let end = start + size.get();
+    self.remove_free_region(region.location, region.size);
+    if let Some(leading) = NonZero::new(start - region.location) {
+      self.insert_free_region(region.location, leading);
+    }
+    if let Some(trailing) =
+      NonZero::new(region.location + region.size.get() - end)
+    {
+      self.insert_free_region(end, trailing);
+    }
+    self.available -= size.get();
   }
 
   /// remove a region from the internal free lists
   fn remove_free_region(&mut self, location: Location, size: NonZero<Size>) {
+    self.count_tree_update();
     self.location_map.remove(&location);
     let region_existed = self.free.remove(&FreeRegion { location, size });
+    self.hints.remove(FreeRegion { location, size });
+    if let Some(bin) = aligned_bin(location) {
+      self.aligned_bins[bin].remove(&FreeRegion { location, size });
+    }
 
     assert!(
       region_existed,
@@ -433,8 +3006,15 @@ impl Allocator {
 
   /// add a region to the internal free lists
   fn insert_free_region(&mut self, location: Location, size: NonZero<Size>) {
+    self.count_tree_update();
     self.free.insert(FreeRegion { location, size });
+    self.hints.insert(FreeRegion { location, size });
+    if let Some(bin) = aligned_bin(location) {
+      self.aligned_bins[bin].insert(FreeRegion { location, size });
+    }
     let existing_size = self.location_map.insert(location, size);
+    self.counters.peak_free_regions =
+      self.counters.peak_free_regions.max(self.location_map.len());
 
     assert!(
       existing_size.is_none(),
@@ -458,6 +3038,439 @@ impl fmt::Debug for Allocator {
   }
 }
 
+impl Drop for Allocator {
+  fn drop(&mut self) {
+    if self.drop_check == DropCheck::Off {
+      return;
+    }
+    // note: pending frees are counted without coalescing them, so dropping
+    // never runs threshold callbacks or double-free checks
+    let pending: u64 = self
+      .deferred
+      .iter()
+      .flatten()
+      .map(|alloc| alloc.size.get() as u64)
+      .sum();
+    let outstanding =
+      ((self.capacity.get() - self.available) as u64).saturating_sub(pending);
+    if outstanding == 0 {
+      return;
+    }
+
+    match self.drop_check {
+      DropCheck::Off => {},
+      #[cfg(feature = "std")]
+      DropCheck::Warn => ::std::eprintln!(
+        "warning: orderly_allocator::Allocator dropped with {outstanding} \
+          bytes still allocated"
+      ),
+      #[cfg(not(feature = "std"))]
+      DropCheck::Warn => {},
+      DropCheck::Panic => {
+        // note: panicking while already unwinding would abort
+        #[cfg(feature = "std")]
+        if ::std::thread::panicking() {
+          return;
+        }
+        panic!("Allocator dropped with {outstanding} bytes still allocated")
+      },
+    }
+  }
+}
+
+impl Clone for Allocator {
+  fn clone(&self) -> Self {
+    Allocator {
+      free: self.free.clone(),
+      location_map: self.location_map.clone(),
+      capacity: self.capacity,
+      available: self.available,
+      min_align: self.min_align,
+      counters: self.counters,
+      placement: self.placement,
+      rng: self.rng,
+      #[cfg(feature = "wear-stats")]
+      wear: self.wear.clone(),
+      watermark: self.watermark,
+      #[cfg(target_has_atomic = "ptr")]
+      thresholds: self.thresholds.clone(),
+      #[cfg(target_has_atomic = "ptr")]
+      hooks: self.hooks.clone(),
+      #[cfg(feature = "std")]
+      events: self.events.clone(),
+      drop_check: self.drop_check,
+      size_classes: self.size_classes,
+      granularity: self.granularity,
+      exact_fit_first: self.exact_fit_first,
+      coloring: self.coloring,
+      color_cursor: self.color_cursor,
+      deferred: self.deferred.clone(),
+      #[cfg(feature = "op-costs")]
+      costs: self.costs.clone(),
+      hints: self.hints,
+      aligned_bins: self.aligned_bins.clone(),
+    }
+  }
+
+  /// Reuses the memory of the internal free-lists, see
+  /// [`copy_state_from`](Allocator::copy_state_from)
+  fn clone_from(&mut self, source: &Self) {
+    self.copy_state_from(source);
+  }
+}
+
+/// Allocators are equal if they have the same capacity, and the same set of
+/// free-regions
+///
+/// This compares the logical state only, so allocators which reached the same
+/// state through different sequences of operations are equal.
+impl PartialEq for Allocator {
+  fn eq(&self, other: &Self) -> bool {
+    // note: `free` holds the same regions as `location_map`, and `available`
+    // is their total size, so neither needs comparing
+    self.capacity == other.capacity && self.location_map == other.location_map
+  }
+}
+
+impl Eq for Allocator {}
+
+/// A bucket of a histogram over the sizes of free-regions
+///
+/// See [`Allocator::free_size_histogram`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct HistogramBucket {
+  /// The number of free-regions in this bucket
+  pub count: usize,
+  /// The combined size of the free-regions in this bucket
+  pub total_size: Size,
+}
+
+/// The most nodes an `alloc` B-tree can occupy holding `len` entries
+///
+/// Every node but the root holds at least `B - 1 = 5` entries.
+fn max_btree_nodes(len: usize) -> usize {
+  match len {
+    0 => 0,
+    len => 1 + (len - 1) / 5,
+  }
+}
+
+/// A snapshot of an allocator's statistics
+///
+/// See [`Allocator::stats`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub struct Stats {
+  /// The total capacity of the pool
+  pub capacity: Size,
+  /// The total free space in the pool
+  pub total_available: Size,
+  /// The size of the largest free-region
+  pub largest_available: Size,
+  /// The number of separate free-regions
+  pub free_regions: usize,
+  /// The cumulative number of bytes split off free-regions to satisfy
+  /// alignment requests
+  ///
+  /// This is the main hidden cost of
+  /// [`alloc_with_align`](Allocator::alloc_with_align). The padding is
+  /// returned to the free-lists, but tends to leave small fragments behind.
+  ///
+  /// This is never reset, not even by [`reset`](Allocator::reset).
+  pub alignment_padding: u64,
+  /// The largest number of separate free-regions there have been at once
+  ///
+  /// Each free-region occupies one entry in each of the allocator's two
+  /// internal B-trees, so this bounds the worst-case size of its metadata.
+  /// Soft-realtime users can use this to check that their workload stays
+  /// within the metadata they planned for.
+  ///
+  /// This is never reset, not even by [`reset`](Allocator::reset).
+  pub peak_free_regions: usize,
+  /// An upper bound on the number of B-tree nodes the allocator's metadata
+  /// occupied at [`peak_free_regions`](Self::peak_free_regions), across
+  /// both of its internal B-trees
+  ///
+  /// The `alloc` B-trees don't expose their nodes, so this is derived from
+  /// their node capacity instead: every node holds up to 11 entries, & every
+  /// node but the root holds at least 5. Soft-realtime users can multiply
+  /// this by the size of a node to budget the metadata's worst case.
+  ///
+  /// This is never reset, not even by [`reset`](Allocator::reset).
+  pub peak_btree_nodes: usize,
+  /// The cumulative number of times an allocation was carved out of a larger
+  /// free-region, leaving a smaller free-region behind
+  ///
+  /// Padding split off to reach alignment is counted separately, as
+  /// [`alignment_fragments`](Self::alignment_fragments).
+  pub splits: u64,
+  /// The cumulative number of free-regions created by padding allocations
+  /// to reach alignment
+  pub alignment_fragments: u64,
+  /// The cumulative number of freed regions which were merged with only the
+  /// free-region before them
+  pub coalesced_left: u64,
+  /// The cumulative number of freed regions which were merged with only the
+  /// free-region after them
+  pub coalesced_right: u64,
+  /// The cumulative number of freed regions which were merged with the
+  /// free-regions on both sides, reducing the number of free-regions
+  ///
+  /// Comparing these counters before & after a workload can attribute growth
+  /// in fragmentation to specific behaviours, e.g. to validate a change of
+  /// [`Placement`].
+  pub coalesced_both: u64,
+  /// The cumulative number of allocation requests which could not be
+  /// satisfied, including those refused by the
+  /// [watermark](Allocator::set_watermark)
+  pub failed_allocs: u64,
+}
+
+/// A summary of the sizes of an allocator's free-regions
+///
+/// See [`Allocator::fragmentation_report`].
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct FragmentationReport {
+  /// The number of separate free-regions
+  pub free_regions: usize,
+  /// The mean size of the free-regions
+  pub mean: f64,
+  /// The median size of the free-regions
+  ///
+  /// If there are an even number of free-regions, this is the lower of the
+  /// two middle sizes.
+  pub median: Size,
+  /// The 95th percentile size of the free-regions, by nearest rank
+  pub p95: Size,
+  /// The size of the largest free-region
+  pub largest: Size,
+  /// The total free space in the pool
+  pub total: Size,
+  /// The total size of the free-regions too small to be useful
+  pub unusable: Size,
+}
+
+impl FragmentationReport {
+  /// Get the fraction of the free space which is outside the largest
+  /// free-region, in the range `0.0..=1.0`
+  ///
+  /// This is `0.0` when the free space is contiguous (or there is none).
+  pub fn fragmentation(&self) -> f64 {
+    if self.total == 0 {
+      return 0.;
+    }
+    1. - self.largest as f64 / self.total as f64
+  }
+}
+
+impl fmt::Display for FragmentationReport {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_fmt(format_args!(
+      "{} free-regions totalling {} (mean {:.1}, median {}, p95 {}, largest \
+       {}), {} unusable",
+      self.free_regions,
+      self.total,
+      self.mean,
+      self.median,
+      self.p95,
+      self.largest,
+      self.unusable,
+    ))
+  }
+}
+
+/// A coarse level of memory pressure
+///
+/// See [`Allocator::pressure`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum PressureLevel {
+  /// There is plenty of contiguous free space
+  Low,
+  /// Allocations are starting to fail, or soon will
+  Medium,
+  /// The pool is nearly full, or allocations are failing regularly
+  High,
+}
+
+/// The action advised to relieve memory pressure
+///
+/// See [`Allocator::pressure`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PressureAction {
+  /// Nothing needs doing
+  None,
+  /// Free allocations which can be recreated later, e.g. cached resources
+  Evict,
+  /// Move allocations together to reunite the fragmented free space
+  Compact,
+  /// Add capacity, e.g. with [`grow_capacity`](Allocator::grow_capacity)
+  Grow,
+}
+
+/// The memory pressure on a pool, & the action advised to relieve it
+///
+/// See [`Allocator::pressure`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Pressure {
+  /// How much pressure the pool is under
+  pub level: PressureLevel,
+  /// The action advised to relieve it
+  pub action: PressureAction,
+  /// The fraction of the capacity which is allocated
+  pub occupancy: f64,
+  /// The fraction of the free space which is outside the largest
+  /// free-region
+  pub fragmentation: f64,
+  /// How many of the last 64 allocation requests failed
+  pub recent_failures: u32,
+}
+
+/// How a free-region was split to satisfy an aligned allocation
+///
+/// See [`Allocator::alloc_with_align_report`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct AlignReport {
+  /// The number of bytes split off the start of the free-region to reach the
+  /// requested alignment
+  ///
+  /// These are returned to the free-lists as a separate free-region.
+  pub padding: Size,
+  /// The number of bytes left over after the end of the allocation
+  pub leftover: Size,
+}
+
+/// How an [`Allocator`] chooses among the free-regions which can fit an
+/// allocation
+///
+/// See [`Allocator::set_placement`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Placement {
+  /// Choose the smallest suitable free-region, preferring lower locations
+  ///
+  /// This is the default, and keeps fragmentation low.
+  #[default]
+  BestFit,
+  /// Like [`BestFit`](Self::BestFit), but avoid splitting the largest
+  /// free-region whenever any other free-region can fit the allocation
+  ///
+  /// This preserves the pool's ability to serve a future large allocation.
+  /// It mostly makes a difference for aligned allocations, where best-fit
+  /// conservatively skips regions which are too small to fit the worst-case
+  /// padding; in that case choosing a region is linear in the number of
+  /// free-regions.
+  PreserveLargest,
+  /// Choose uniformly among all suitable free-regions, using a pseudo-random
+  /// generator seeded with `seed`
+  ///
+  /// This spreads allocations across the pool, e.g. for wear-leveling a
+  /// flash-backed pool, or to make the layout harder to predict. It tends to
+  /// fragment the pool much more than [`BestFit`](Self::BestFit), and each
+  /// allocation is linear in the number of free-regions.
+  ///
+  /// Note: The generator is not cryptographically secure.
+  Random { seed: u64 },
+  /// Choose the free-region nearest the start of the pool, for frequently
+  /// rewritten data
+  ///
+  /// Together with [`Cold`](Self::Cold), this keeps short-lived allocations
+  /// clustered away from long-lived static data, so the two don't interleave
+  /// & fragment each other's space. Typically used per-request, see
+  /// [`Allocator::alloc_with_placement`]. Choosing a region is linear in the
+  /// number of free-regions before it.
+  Hot,
+  /// Choose the free-region nearest the end of the pool, carving the
+  /// allocation from the end of it, for long-lived data
+  ///
+  /// See [`Hot`](Self::Hot). The padding reported by
+  /// [`Allocator::alloc_with_align_report`] is after the allocation, & the
+  /// leftover is before it. Choosing a region is linear in the number of
+  /// free-regions after it.
+  Cold,
+}
+
+/// How an [`Allocator`] offsets successive allocations of the same size
+///
+/// See [`Allocator::set_coloring`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Coloring {
+  /// The distance between neighbouring colors, e.g. a cache line or the
+  /// width of a memory bank
+  pub stride: Size,
+  /// The number of distinct offsets to cycle through
+  pub colors: Size,
+}
+
+/// The increment of the splitmix64 generator
+const SPLITMIX_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// The output function of the splitmix64 generator, for the given state
+fn splitmix64(state: u64) -> u64 {
+  let mut z = state;
+  z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+  z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+  z ^ (z >> 31)
+}
+
+/// What an [`Allocator`] does if it is dropped while allocations are still
+/// outstanding
+///
+/// See [`Allocator::set_drop_check`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum DropCheck {
+  /// Do nothing
+  Off,
+  /// Print a warning to stderr
+  ///
+  /// This requires the `std` feature; otherwise it does nothing.
+  Warn,
+  /// Panic
+  ///
+  /// Without the `std` feature, this can't tell whether the allocator is
+  /// being dropped during unwinding, in which case the panic aborts.
+  Panic,
+}
+
+impl Default for DropCheck {
+  /// [`Panic`](Self::Panic) when `debug_assertions` are enabled, otherwise
+  /// [`Off`](Self::Off)
+  fn default() -> Self {
+    if cfg!(debug_assertions) {
+      DropCheck::Panic
+    } else {
+      DropCheck::Off
+    }
+  }
+}
+
+/// The state of a location within the pool
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum SpanState {
+  /// The location is not part of any allocation
+  Free,
+  /// The location belongs to some allocation
+  Allocated,
+}
+
+/// The differences between the free-regions of two allocators
+///
+/// See [`Allocator::diff`].
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct FreeRegionDiff {
+  /// The free-regions which exist only in `self`, sorted by location
+  pub only_in_self: Vec<Allocation>,
+  /// The free-regions which exist only in `other`, sorted by location
+  pub only_in_other: Vec<Allocation>,
+}
+
+impl FreeRegionDiff {
+  /// Returns true if there are no differences
+  pub fn is_empty(&self) -> bool {
+    self.only_in_self.is_empty() && self.only_in_other.is_empty()
+  }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct Overflow {
   pub current_capacity: NonZero<Size>,
@@ -473,6 +3486,31 @@ impl fmt::Display for Overflow {
   }
 }
 
+/// Why an [`Allocation`] is not a live allocation of an [`Allocator`]
+///
+/// See [`Allocator::validate_allocation`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum InvalidAllocation {
+  /// The allocation extends past the end of the pool
+  OutOfBounds,
+  /// The allocation overlaps this free-region
+  OverlapsFree(Allocation),
+}
+
+impl Error for InvalidAllocation {}
+impl fmt::Display for InvalidAllocation {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      InvalidAllocation::OutOfBounds => f.write_str(
+        "OutOfBounds Error: The allocation extends past the end of the pool",
+      ),
+      InvalidAllocation::OverlapsFree(region) => f.write_fmt(format_args!(
+        "OverlapsFree Error: The allocation overlaps the free-region {region:?}"
+      )),
+    }
+  }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub enum ReallocateError {
   InsufficientSpace {
@@ -0,0 +1,80 @@
+use {
+  crate::{Allocation, AllocationId, Size},
+  ::alloc::vec::Vec,
+  ::core::{error::Error, fmt},
+};
+
+/// A planned move of allocations from one [`TrackedAllocator`] to another
+///
+/// See [`TrackedAllocator::plan_migration`].
+///
+/// [`TrackedAllocator`]: crate::TrackedAllocator
+/// [`TrackedAllocator::plan_migration`]: crate::TrackedAllocator::plan_migration
+#[derive(Clone, Debug)]
+pub struct MigrationPlan {
+  pub(crate) copies: Vec<MigrationCopy>,
+  /// The alignment each move needs in the destination
+  pub(crate) aligns: Vec<Size>,
+  /// The [`state_hash`](crate::Allocator::state_hash) of each allocator
+  /// when the plan was made
+  pub(crate) source_hash: u64,
+  pub(crate) destination_hash: u64,
+}
+
+impl MigrationPlan {
+  /// Get the copies needed to move the data, in the order the moves were
+  /// requested
+  pub fn copies(&self) -> &[MigrationCopy] {
+    &self.copies
+  }
+
+  /// Get the total number of bytes to copy
+  pub fn bytes(&self) -> u64 {
+    self.copies.iter().map(|copy| copy.from.size() as u64).sum()
+  }
+}
+
+/// A copy needed to carry out a [`MigrationPlan`], or a
+/// [compaction](crate::TrackedAllocator::compact)
+///
+/// `from.size()` bytes should be copied from `from.offset()` in the source
+/// pool, to `to.offset()` in the destination pool. `to` may be larger than
+/// `from` if the destination rounds sizes up, e.g. with
+/// [size classes](crate::Allocator::set_size_classes). When compacting, both
+/// are in the same pool.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct MigrationCopy {
+  /// The id of the allocation in the source pool
+  pub id: AllocationId,
+  /// The allocation in the source pool
+  pub from: Allocation,
+  /// The allocation it will become in the destination pool
+  pub to: Allocation,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MigrationError {
+  /// The id does not belong to a live allocation, or was given twice
+  InvalidId(AllocationId),
+  /// The destination can't fit every allocation
+  InsufficientSpace,
+  /// The source or destination has changed since the plan was made
+  Stale,
+}
+
+impl Error for MigrationError {}
+impl fmt::Display for MigrationError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      MigrationError::InvalidId(id) => f.write_fmt(format_args!(
+        "Invalid id: {id:?} is not a live allocation, or was given twice"
+      )),
+      MigrationError::InsufficientSpace => f.write_str(
+        "InsufficientSpace Error: The destination can't fit every allocation",
+      ),
+      MigrationError::Stale => {
+        f.write_str("Stale plan: An allocator has changed since planning")
+      },
+    }
+  }
+}
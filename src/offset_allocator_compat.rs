@@ -0,0 +1,75 @@
+use {
+  crate::{Allocation, Allocator, Size},
+  ::core::num::NonZero,
+  ::offset_allocator::{NodeIndex, StorageReport},
+};
+
+impl Allocation {
+  /// Convert an allocation made by an [`offset_allocator::Allocator`] into an
+  /// [`Allocation`]
+  ///
+  /// The size is looked up in `allocator`, which must be the allocator that
+  /// made `allocation`.
+  ///
+  /// Returns `None` if:
+  /// - `allocation` is not live in `allocator`, or
+  /// - the offset does not fit in a `u32`.
+  ///
+  /// [`offset_allocator::Allocator`]: ::offset_allocator::Allocator
+  pub fn from_offset_allocation<NI: NodeIndex>(
+    allocation: ::offset_allocator::Allocation<NI>,
+    allocator: &::offset_allocator::Allocator<NI>,
+  ) -> Option<Self> {
+    let size = NonZero::new(allocator.allocation_size(allocation))?;
+    let offset = Size::try_from(allocation.offset.to_usize()).ok()?;
+
+    Some(Allocation { offset, size })
+  }
+
+  /// Split the allocation into an `(offset, size)` pair
+  ///
+  /// This mirrors the `(offset, metadata)` pair carried by
+  /// [`offset_allocator::Allocation`], for code which stores either kind of
+  /// handle in the same place.
+  ///
+  /// [`offset_allocator::Allocation`]: ::offset_allocator::Allocation
+  pub fn into_parts(self) -> (Size, NonZero<Size>) {
+    (self.offset, self.size)
+  }
+
+  /// Reassemble an allocation from an `(offset, size)` pair
+  ///
+  /// See [`into_parts`](Self::into_parts).
+  pub fn from_parts((offset, size): (Size, NonZero<Size>)) -> Self {
+    Allocation { offset, size }
+  }
+}
+
+impl Allocator {
+  /// Create a new allocator with the same signature as
+  /// [`offset_allocator::Allocator::with_max_allocs`]
+  ///
+  /// This allocator has no fixed limit on the number of allocations, so
+  /// `max_allocs` is ignored. This only exists so that code supporting either
+  /// backend can construct both the same way.
+  ///
+  /// Panics:
+  /// - Panics if `size == 0`
+  ///
+  /// [`offset_allocator::Allocator::with_max_allocs`]: ::offset_allocator::Allocator::with_max_allocs
+  pub fn with_max_allocs(size: Size, max_allocs: u32) -> Self {
+    let _ = max_allocs;
+    Allocator::new(size)
+  }
+
+  /// Get a summary of the free space in the same form as
+  /// [`offset_allocator::Allocator::storage_report`]
+  ///
+  /// [`offset_allocator::Allocator::storage_report`]: ::offset_allocator::Allocator::storage_report
+  pub fn storage_report(&self) -> StorageReport {
+    StorageReport {
+      total_free_space: self.total_available(),
+      largest_free_region: self.largest_available(),
+    }
+  }
+}
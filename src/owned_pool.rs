@@ -0,0 +1,315 @@
+use {
+  crate::{Allocation, Allocator, Size},
+  ::alloc::alloc::{alloc, dealloc, handle_alloc_error},
+  ::core::{
+    alloc::Layout,
+    cell::{Ref, RefCell},
+    fmt,
+    num::NonZero,
+    ptr::{self, NonNull},
+  },
+};
+
+/// An [`Allocator`] which owns the pool of memory it manages
+///
+/// Rather than [`Allocation`]s, this hands out pointers into its buffer, so it
+/// can be used to back standard collections. With the `allocator-api2` feature
+/// enabled this implements [`allocator_api2::alloc::Allocator`], which allows
+/// e.g. `allocator_api2::vec::Vec` to live inside the pool on stable Rust. With
+/// the `unstable` feature enabled this implements [`core::alloc::Allocator`]
+/// too, on nightly Rust.
+///
+/// Blocks are aligned relative to the address of the buffer, so requests with
+/// a greater alignment than the buffer itself will fail.
+///
+/// [`allocator_api2::alloc::Allocator`]: https://docs.rs/allocator-api2
+pub struct OwnedPool {
+  /// The start of the buffer
+  base: NonNull<u8>,
+  /// The alignment of `base`
+  align: usize,
+  /// The layout the buffer was allocated with, or `None` if the buffer belongs
+  /// to the user
+  layout: Option<Layout>,
+  allocator: RefCell<Allocator>,
+}
+
+// SAFETY: The pool has exclusive access to its buffer, and any outstanding
+// blocks either borrow the pool or own it.
+unsafe impl Send for OwnedPool {}
+
+impl OwnedPool {
+  /// Allocate a new buffer of `capacity` bytes, aligned to `align`, and create
+  /// a pool to manage it
+  ///
+  /// Panics:
+  /// - Panics if `capacity == 0`, or
+  /// - if `align` is not a power of two.
+  pub fn new(capacity: Size, align: usize) -> Self {
+    let allocator = Allocator::new(capacity);
+    let layout = Layout::from_size_align(capacity as usize, align)
+      .expect("`align` is not a power of two");
+
+    // SAFETY: `layout` has a non-zero size, since `capacity != 0`
+    let Some(base) = NonNull::new(unsafe { alloc(layout) }) else {
+      handle_alloc_error(layout);
+    };
+
+    OwnedPool {
+      base,
+      align,
+      layout: Some(layout),
+      allocator: RefCell::new(allocator),
+    }
+  }
+
+  /// Create a pool to manage a user-provided buffer of `capacity` bytes
+  /// starting at `base`
+  ///
+  /// The buffer is not freed when the pool is dropped.
+  ///
+  /// Panics:
+  /// - Panics if `capacity == 0`
+  ///
+  /// # Safety
+  ///
+  /// - `base` must be valid for reads and writes of `capacity` bytes for the
+  ///   entire lifetime of the pool, and
+  /// - the buffer must not be accessed other than through pointers handed out
+  ///   by the pool.
+  pub unsafe fn from_raw_parts(base: NonNull<u8>, capacity: Size) -> Self {
+    let allocator = Allocator::new(capacity);
+    let align = 1 << base.addr().trailing_zeros();
+
+    OwnedPool {
+      base,
+      align,
+      layout: None,
+      allocator: RefCell::new(allocator),
+    }
+  }
+
+  /// Get the address of the start of the buffer
+  pub fn base(&self) -> NonNull<u8> {
+    self.base
+  }
+
+  /// Get read-only access to the underlying [`Allocator`]
+  ///
+  /// This can be used to query e.g. the available space in the pool.
+  ///
+  /// Panics:
+  /// - Panics if called while the pool is allocating or deallocating
+  pub fn allocator(&self) -> Ref<'_, Allocator> {
+    self.allocator.borrow()
+  }
+
+  /// Try to allocate a block of memory fitting `layout`
+  ///
+  /// Zero-sized requests always succeed, without consuming any space from the
+  /// pool.
+  ///
+  /// Returns `None` if:
+  /// - there is no free-region which can fit the block, or
+  /// - `layout.align()` is greater than the alignment of the buffer.
+  pub fn allocate(&self, layout: Layout) -> Option<NonNull<[u8]>> {
+    if layout.align() > self.align {
+      return None;
+    }
+    if layout.size() == 0 {
+      return Some(dangling(layout));
+    }
+
+    let size = Size::try_from(layout.size()).ok()?;
+    let align = Size::try_from(layout.align()).ok()?;
+    let allocation =
+      self.allocator.borrow_mut().alloc_with_align(size, align)?;
+
+    Some(self.block(allocation))
+  }
+
+  /// Deallocate the block of memory at `ptr`
+  ///
+  /// # Safety
+  ///
+  /// - `ptr` must have been returned by this pool, and not yet deallocated,
+  ///   and
+  /// - `layout` must be the layout the block was allocated with.
+  pub unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+    let Some(size) = NonZero::new(layout.size() as Size) else {
+      return; // zero-sized blocks don't consume any space
+    };
+
+    self.allocator.borrow_mut().free(Allocation {
+      offset: self.offset_of(ptr),
+      size,
+    });
+  }
+
+  /// Re-size the block of memory at `ptr` to fit `new_layout`
+  ///
+  /// The block is resized in-place if possible, otherwise the contents are
+  /// moved to a new block.
+  ///
+  /// Returns `None` if a new block was required but could not be allocated,
+  /// in which case the existing block is left untouched.
+  ///
+  /// # Safety
+  ///
+  /// - `ptr` must have been returned by this pool, and not yet deallocated,
+  ///   and
+  /// - `old_layout` must be the layout the block was allocated with.
+  pub unsafe fn reallocate(
+    &self,
+    ptr: NonNull<u8>,
+    old_layout: Layout,
+    new_layout: Layout,
+  ) -> Option<NonNull<[u8]>> {
+    let in_place = ptr.addr().get().is_multiple_of(new_layout.align());
+    if let (true, Some(old_size), Ok(new_size)) = (
+      in_place,
+      NonZero::new(old_layout.size() as Size),
+      Size::try_from(new_layout.size()),
+    ) {
+      let allocation = Allocation {
+        offset: self.offset_of(ptr),
+        size: old_size,
+      };
+      let reallocated = self
+        .allocator
+        .borrow_mut()
+        .try_reallocate(allocation, new_size);
+      if let Ok(allocation) = reallocated {
+        return Some(self.block(allocation));
+      }
+    }
+
+    let new_block = self.allocate(new_layout)?;
+    let count = old_layout.size().min(new_layout.size());
+    // SAFETY: Both blocks are valid for at least `count` bytes, and they are
+    // distinct live blocks so they cannot overlap.
+    unsafe {
+      ptr::copy_nonoverlapping(ptr.as_ptr(), new_block.cast().as_ptr(), count);
+      self.deallocate(ptr, old_layout);
+    }
+
+    Some(new_block)
+  }
+
+  /// Get the block of memory corresponding to `allocation`
+  fn block(&self, allocation: Allocation) -> NonNull<[u8]> {
+    // SAFETY: The allocation lies within the buffer
+    let start = unsafe { self.base.add(allocation.offset as usize) };
+    NonNull::slice_from_raw_parts(start, allocation.size() as usize)
+  }
+
+  /// Get the offset of `ptr` relative to the start of the buffer
+  fn offset_of(&self, ptr: NonNull<u8>) -> Size {
+    (ptr.addr().get() - self.base.addr().get()) as Size
+  }
+}
+
+/// A well-aligned pointer to a zero-sized block
+fn dangling(layout: Layout) -> NonNull<[u8]> {
+  let ptr = NonNull::new(ptr::without_provenance_mut(layout.align()))
+    .unwrap_or_else(|| unreachable!());
+  NonNull::slice_from_raw_parts(ptr, 0)
+}
+
+impl Drop for OwnedPool {
+  fn drop(&mut self) {
+    if let Some(layout) = self.layout {
+      // SAFETY: The buffer was allocated by us with this layout
+      unsafe { dealloc(self.base.as_ptr(), layout) }
+    }
+  }
+}
+
+impl fmt::Debug for OwnedPool {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("OwnedPool")
+      .field("base", &self.base)
+      .field("align", &self.align)
+      .field("allocator", &self.allocator)
+      .finish()
+  }
+}
+
+#[cfg(feature = "allocator-api2")]
+// SAFETY: Blocks are handed out from disjoint allocations of the buffer, which
+// stays put for the lifetime of the pool.
+unsafe impl ::allocator_api2::alloc::Allocator for OwnedPool {
+  fn allocate(
+    &self,
+    layout: Layout,
+  ) -> Result<NonNull<[u8]>, ::allocator_api2::alloc::AllocError> {
+    OwnedPool::allocate(self, layout)
+      .ok_or(::allocator_api2::alloc::AllocError)
+  }
+
+  unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+    // SAFETY: Upheld by the caller
+    unsafe { OwnedPool::deallocate(self, ptr, layout) }
+  }
+
+  unsafe fn grow(
+    &self,
+    ptr: NonNull<u8>,
+    old_layout: Layout,
+    new_layout: Layout,
+  ) -> Result<NonNull<[u8]>, ::allocator_api2::alloc::AllocError> {
+    // SAFETY: Upheld by the caller
+    unsafe { self.reallocate(ptr, old_layout, new_layout) }
+      .ok_or(::allocator_api2::alloc::AllocError)
+  }
+
+  unsafe fn shrink(
+    &self,
+    ptr: NonNull<u8>,
+    old_layout: Layout,
+    new_layout: Layout,
+  ) -> Result<NonNull<[u8]>, ::allocator_api2::alloc::AllocError> {
+    // SAFETY: Upheld by the caller
+    unsafe { self.reallocate(ptr, old_layout, new_layout) }
+      .ok_or(::allocator_api2::alloc::AllocError)
+  }
+}
+
+#[cfg(feature = "unstable")]
+// SAFETY: Blocks are handed out from disjoint allocations of the buffer, which
+// stays put for the lifetime of the pool.
+unsafe impl ::core::alloc::Allocator for OwnedPool {
+  fn allocate(
+    &self,
+    layout: Layout,
+  ) -> Result<NonNull<[u8]>, ::core::alloc::AllocError> {
+    OwnedPool::allocate(self, layout).ok_or(::core::alloc::AllocError)
+  }
+
+  unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+    // SAFETY: Upheld by the caller
+    unsafe { OwnedPool::deallocate(self, ptr, layout) }
+  }
+
+  unsafe fn grow(
+    &self,
+    ptr: NonNull<u8>,
+    old_layout: Layout,
+    new_layout: Layout,
+  ) -> Result<NonNull<[u8]>, ::core::alloc::AllocError> {
+    // SAFETY: Upheld by the caller
+    unsafe { self.reallocate(ptr, old_layout, new_layout) }
+      .ok_or(::core::alloc::AllocError)
+  }
+
+  unsafe fn shrink(
+    &self,
+    ptr: NonNull<u8>,
+    old_layout: Layout,
+    new_layout: Layout,
+  ) -> Result<NonNull<[u8]>, ::core::alloc::AllocError> {
+    // SAFETY: Upheld by the caller
+    unsafe { self.reallocate(ptr, old_layout, new_layout) }
+      .ok_or(::core::alloc::AllocError)
+  }
+}
@@ -0,0 +1,285 @@
+use {
+  crate::{Allocation, Allocator, Plain, ReallocateError, Size},
+  ::core::{fmt, marker::PhantomData, mem, num::NonZero, ptr, slice},
+};
+
+/// A growable array of `T`s living inside a pool
+///
+/// Like [`PoolBox`](crate::PoolBox), this only records where the elements
+/// live; the elements themselves live in a buffer managed by the user, which
+/// must be passed to any method that reads or writes them.
+///
+/// When the array needs to grow it first tries to expand its allocation
+/// in-place with [`try_reallocate`](Allocator::try_reallocate), and only moves
+/// the elements to a new allocation if that fails.
+///
+/// Zero-sized types are not supported; attempting to reserve space for them
+/// fails.
+pub struct PoolVec<T> {
+  allocation: Option<Allocation>,
+  len: Size,
+  marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Plain> PoolVec<T> {
+  /// Create a new, empty array
+  ///
+  /// This does not allocate until elements are pushed.
+  pub const fn new() -> Self {
+    PoolVec {
+      allocation: None,
+      len: 0,
+      marker: PhantomData,
+    }
+  }
+
+  /// Get the number of elements in the array
+  pub fn len(&self) -> Size {
+    self.len
+  }
+
+  /// Returns true if the array contains no elements
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  /// Get the number of elements the array can hold without growing
+  pub fn capacity(&self) -> Size {
+    self.allocation.map_or(0, |allocation| {
+      allocation.size() / mem::size_of::<T>() as Size
+    })
+  }
+
+  /// Get the underlying [`Allocation`], if anything has been allocated yet
+  pub fn allocation(&self) -> Option<Allocation> {
+    self.allocation
+  }
+
+  /// Reserve capacity for at least `additional` more elements
+  ///
+  /// If the elements need to be moved to a new allocation they are copied
+  /// within `buffer`.
+  ///
+  /// Returns:
+  /// - `Ok(())` on success.
+  /// - `Err(ReallocateError::Invalid)` if the new capacity would overflow, or
+  ///   if `T` is zero-sized.
+  /// - `Err(ReallocateError::InsufficientSpace)` if there is not enough space
+  ///   in the pool. In this case the array is left untouched.
+  ///
+  /// Panics:
+  /// - Panics if `buffer` is too short to contain the elements.
+  pub fn try_reserve(
+    &mut self,
+    allocator: &mut Allocator,
+    buffer: &mut [u8],
+    additional: Size,
+  ) -> Result<(), ReallocateError> {
+    let capacity = self.capacity();
+    let required = self
+      .len
+      .checked_add(additional)
+      .ok_or(ReallocateError::Invalid)?;
+    if required <= capacity {
+      return Ok(());
+    }
+
+    let element_size = Size::try_from(mem::size_of::<T>())
+      .ok()
+      .filter(|&size| size > 0)
+      .ok_or(ReallocateError::Invalid)?;
+    let align = Size::try_from(mem::align_of::<T>())
+      .map_err(|_| ReallocateError::Invalid)?;
+    let required_size = required
+      .checked_mul(element_size)
+      .ok_or(ReallocateError::Invalid)?;
+    // grow geometrically where possible, so pushing is amortised O(1)
+    let amortised_size = capacity
+      .saturating_mul(2)
+      .max(4)
+      .checked_mul(element_size)
+      .map_or(required_size, |size| size.max(required_size));
+    let candidate_sizes = [amortised_size, required_size];
+
+    let Some(old) = self.allocation else {
+      for size in candidate_sizes {
+        if let Some(allocation) = allocator.alloc_with_align(size, align) {
+          self.allocation = Some(allocation);
+          return Ok(());
+        }
+      }
+      return Err(ReallocateError::InsufficientSpace {
+        required_additional: NonZero::new(required_size)
+          .unwrap_or_else(|| unreachable!()),
+        available: allocator.largest_available(),
+      });
+    };
+
+    let mut error = ReallocateError::Invalid;
+    for size in candidate_sizes {
+      match allocator.try_reallocate(old, size) {
+        Ok(allocation) => {
+          self.allocation = Some(allocation);
+          return Ok(());
+        },
+        Err(err) => error = err,
+      }
+    }
+
+    for size in candidate_sizes {
+      if let Some(new) = allocator.alloc_with_align(size, align) {
+        let used = (self.len * element_size) as usize;
+        let start = old.offset as usize;
+        buffer.copy_within(start..start + used, new.offset as usize);
+        allocator.free(old);
+        self.allocation = Some(new);
+        return Ok(());
+      }
+    }
+
+    Err(error)
+  }
+
+  /// Append an element to the back of the array, growing it if necessary
+  ///
+  /// The element is written into `buffer`.
+  ///
+  /// Returns an error if the array needed to grow but could not; see
+  /// [`try_reserve`](Self::try_reserve).
+  ///
+  /// Panics:
+  /// - Panics if `buffer` is too short to contain the elements.
+  pub fn push(
+    &mut self,
+    allocator: &mut Allocator,
+    buffer: &mut [u8],
+    value: T,
+  ) -> Result<(), ReallocateError> {
+    self.try_reserve(allocator, buffer, 1)?;
+
+    let start = self.element_offset(self.len);
+    buffer[start..start + mem::size_of::<T>()]
+      .copy_from_slice(bytes_of(&value));
+    self.len += 1;
+
+    Ok(())
+  }
+
+  /// Remove the last element from the array and return it
+  ///
+  /// Returns `None` if the array is empty.
+  ///
+  /// Panics:
+  /// - Panics if `buffer` is too short to contain the elements.
+  pub fn pop(&mut self, buffer: &[u8]) -> Option<T> {
+    self.len = self.len.checked_sub(1)?;
+    Some(self.read(buffer, self.len))
+  }
+
+  /// Get a copy of the element at `index`
+  ///
+  /// Returns `None` if `index` is out of bounds.
+  ///
+  /// Panics:
+  /// - Panics if `buffer` is too short to contain the elements.
+  pub fn get(&self, buffer: &[u8], index: Size) -> Option<T> {
+    (index < self.len).then(|| self.read(buffer, index))
+  }
+
+  /// View the elements as a slice within `buffer`
+  ///
+  /// Returns `None` if the elements' address within `buffer` is not aligned
+  /// for `T`.
+  ///
+  /// Panics:
+  /// - Panics if `buffer` is too short to contain the elements.
+  pub fn as_slice<'b>(&self, buffer: &'b [u8]) -> Option<&'b [T]> {
+    if self.is_empty() {
+      return Some(&[]);
+    }
+    let bytes = &buffer[self.byte_range()];
+    let ptr = bytes.as_ptr().cast::<T>();
+    // SAFETY: The pointer is valid for `len` elements, and any bytes are a
+    // valid `T`
+    ptr
+      .is_aligned()
+      .then(|| unsafe { slice::from_raw_parts(ptr, self.len as usize) })
+  }
+
+  /// View the elements as a mutable slice within `buffer`
+  ///
+  /// Returns `None` if the elements' address within `buffer` is not aligned
+  /// for `T`.
+  ///
+  /// Panics:
+  /// - Panics if `buffer` is too short to contain the elements.
+  pub fn as_mut_slice<'b>(&self, buffer: &'b mut [u8]) -> Option<&'b mut [T]> {
+    if self.is_empty() {
+      return Some(&mut []);
+    }
+    let bytes = &mut buffer[self.byte_range()];
+    let ptr = bytes.as_mut_ptr().cast::<T>();
+    // SAFETY: The pointer is valid for `len` elements, and any bytes are a
+    // valid `T`. `T` contains no padding, so any value written through the
+    // slice leaves the bytes initialised.
+    ptr
+      .is_aligned()
+      .then(|| unsafe { slice::from_raw_parts_mut(ptr, self.len as usize) })
+  }
+
+  /// Remove all elements from the array, keeping its capacity
+  pub fn clear(&mut self) {
+    self.len = 0;
+  }
+
+  /// Free the array's allocation, consuming the array
+  pub fn free(self, allocator: &mut Allocator) {
+    if let Some(allocation) = self.allocation {
+      allocator.free(allocation);
+    }
+  }
+
+  /// Read the element at `index`, which must be in bounds
+  fn read(&self, buffer: &[u8], index: Size) -> T {
+    let start = self.element_offset(index);
+    let bytes = &buffer[start..start + mem::size_of::<T>()];
+    // SAFETY: `bytes` holds `size_of::<T>()` bytes, and any bytes are a valid
+    // `T`
+    unsafe { ptr::read_unaligned(bytes.as_ptr().cast::<T>()) }
+  }
+
+  /// Get the offset of the element at `index` within the buffer
+  fn element_offset(&self, index: Size) -> usize {
+    let base = self.allocation.map_or(0, |allocation| allocation.offset);
+    base as usize + index as usize * mem::size_of::<T>()
+  }
+
+  /// Get the range of the buffer occupied by the elements
+  fn byte_range(&self) -> ::core::ops::Range<usize> {
+    self.element_offset(0)..self.element_offset(self.len)
+  }
+}
+
+impl<T: Plain> Default for PoolVec<T> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<T> fmt::Debug for PoolVec<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("PoolVec")
+      .field("type", &::core::any::type_name::<T>())
+      .field("allocation", &self.allocation)
+      .field("len", &self.len)
+      .finish()
+  }
+}
+
+/// View a value as its raw bytes
+fn bytes_of<T: Plain>(value: &T) -> &[u8] {
+  // SAFETY: `T` contains no padding, so all of its bytes are initialised
+  unsafe {
+    slice::from_raw_parts(ptr::from_ref(value).cast(), mem::size_of::<T>())
+  }
+}
@@ -0,0 +1,145 @@
+use {
+  crate::{Allocation, Allocator, Location, Size},
+  ::core::{error::Error, fmt, num::NonZero, ops::Range},
+};
+
+/// A compatibility shim exposing the API of the `range-alloc` crate
+///
+/// This wraps an [`Allocator`], and hands out `Range`s rather than
+/// [`Allocation`]s, so that projects using `range_alloc::RangeAllocator<u32>`
+/// can switch over by changing a type alias.
+///
+/// Note: Unlike the allocator it mimics, this is not generic over the range's
+/// type; only `u32` ranges are supported.
+#[derive(Clone)]
+pub struct RangeAllocator {
+  allocator: Allocator,
+  initial_range: Range<Location>,
+}
+
+impl RangeAllocator {
+  /// Create a new allocator to manage `range`
+  ///
+  /// Panics:
+  /// - Panics if `range` is empty
+  pub fn new(range: Range<Location>) -> Self {
+    assert!(range.start < range.end, "range must not be empty");
+
+    RangeAllocator {
+      allocator: Allocator::new(range.end - range.start),
+      initial_range: range,
+    }
+  }
+
+  /// Get the range this allocator was created with
+  ///
+  /// This does not include any growth from [`grow_to`](Self::grow_to).
+  pub fn initial_range(&self) -> &Range<Location> {
+    &self.initial_range
+  }
+
+  /// Extend the managed range up to `new_end`
+  ///
+  /// Panics:
+  /// - Panics if `new_end` is less than the current end of the range
+  pub fn grow_to(&mut self, new_end: Location) {
+    let end = self.initial_range.start + self.allocator.capacity();
+    assert!(new_end >= end, "cannot shrink the range");
+
+    self
+      .allocator
+      .grow_capacity(new_end - end)
+      .unwrap_or_else(|_| unreachable!());
+  }
+
+  /// Try to allocate a range of the given length
+  ///
+  /// Returns `Err(RangeAllocationError)` if there is no free range large
+  /// enough.
+  ///
+  /// Panics:
+  /// - Panics if `length == 0`
+  pub fn allocate_range(
+    &mut self,
+    length: Size,
+  ) -> Result<Range<Location>, RangeAllocationError> {
+    assert_ne!(length, 0, "length must not be zero");
+
+    let allocation =
+      self
+        .allocator
+        .alloc(length)
+        .ok_or_else(|| RangeAllocationError {
+          fragmented_free_length: self.allocator.total_available(),
+        })?;
+    let start = self.initial_range.start + allocation.offset;
+
+    Ok(start..start + length)
+  }
+
+  /// Free a range previously returned by
+  /// [`allocate_range`](Self::allocate_range)
+  ///
+  /// Panics:
+  /// - Panics if `range` is empty, or starts before the managed range
+  /// - May panic if the range gets freed twice, see [`Allocator::free`]
+  pub fn free_range(&mut self, range: Range<Location>) {
+    assert!(range.start < range.end, "range must not be empty");
+    let offset = range
+      .start
+      .checked_sub(self.initial_range.start)
+      .expect("range is out of bounds");
+    let size =
+      NonZero::new(range.end - range.start).unwrap_or_else(|| unreachable!());
+
+    self.allocator.free(Allocation { offset, size });
+  }
+
+  /// Free ***all*** ranges
+  pub fn reset(&mut self) {
+    self.allocator.reset();
+  }
+
+  /// Returns true if there are no allocated ranges
+  pub fn is_empty(&self) -> bool {
+    self.allocator.is_empty()
+  }
+
+  /// Get the total length of the free ranges
+  pub fn total_available(&self) -> Size {
+    self.allocator.total_available()
+  }
+
+  /// Get the underlying [`Allocator`]
+  ///
+  /// Note: The offsets it reports are relative to the start of
+  /// [`initial_range`](Self::initial_range).
+  pub fn allocator(&self) -> &Allocator {
+    &self.allocator
+  }
+}
+
+impl fmt::Debug for RangeAllocator {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("RangeAllocator")
+      .field("initial_range", &self.initial_range)
+      .field("allocator", &self.allocator)
+      .finish()
+  }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RangeAllocationError {
+  /// The total length of the free ranges, none of which was large enough
+  pub fragmented_free_length: Size,
+}
+
+impl Error for RangeAllocationError {}
+impl fmt::Display for RangeAllocationError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_fmt(format_args!(
+      "RangeAllocation Error: No free range large enough; {} free in total",
+      self.fragmented_free_length
+    ))
+  }
+}
@@ -0,0 +1,319 @@
+use {
+  crate::{
+    fixed::best_fit, Allocation, Allocator, FreeRegion, Location, Size,
+  },
+  ::alloc::vec::Vec,
+  ::core::{error::Error, fmt, num::NonZero},
+};
+
+/// An [`Allocator`] locked into a mode which never touches the heap, for
+/// hard-realtime threads, e.g. audio or control loops
+///
+/// See [`Allocator::lock`]. The free-regions are moved out of the B-trees,
+/// whose nodes may need allocating or freeing on any insert or removal, into
+/// tables sorted by location which were preallocated when locking. Searches
+/// are linear in the number of free-regions, so each operation takes a
+/// bounded amount of time, & an operation which would need more room in the
+/// tables fails with [`RealtimeError::WouldAllocate`] instead.
+///
+/// The allocator's settings, e.g. its [`min_align`](Allocator::min_align),
+/// size classes & watermark, still apply. The strategy is always best-fit,
+/// & events, threshold callbacks & statistics are not updated while locked.
+pub struct LockedAllocator {
+  allocator: Allocator,
+  locations: Vec<Location>,
+  sizes: Vec<Size>,
+}
+
+impl Allocator {
+  /// Lock the allocator into a mode which never touches the heap, with room
+  /// for up to `max_free_regions` separate free-regions
+  ///
+  /// This should be done after preallocation, before entering the realtime
+  /// context; locking & [unlocking](LockedAllocator::unlock) do allocate.
+  /// Room is always made for at least the current free-regions. Frees
+  /// deferred by [`set_deferred_coalescing`](Self::set_deferred_coalescing)
+  /// are coalesced first.
+  pub fn lock(mut self, max_free_regions: usize) -> LockedAllocator {
+    self.coalesce_all();
+    let max_free_regions = max_free_regions.max(self.location_map.len());
+    let mut locations = Vec::with_capacity(max_free_regions);
+    let mut sizes = Vec::with_capacity(max_free_regions);
+    for (&location, &size) in &self.location_map {
+      locations.push(location);
+      sizes.push(size.get());
+    }
+
+    LockedAllocator {
+      allocator: self,
+      locations,
+      sizes,
+    }
+  }
+}
+
+impl LockedAllocator {
+  /// Try to allocate a region with the provided size
+  ///
+  /// See [`alloc_with_align`](Self::alloc_with_align).
+  pub fn alloc(&mut self, size: Size) -> Result<Allocation, RealtimeError> {
+    self.alloc_with_align(size, 1)
+  }
+
+  /// Try to allocate a region with the provided size & alignment
+  ///
+  /// Uses a *best-fit* strategy, choosing the smallest free-region which can
+  /// fit the aligned allocation.
+  ///
+  /// Returns:
+  /// - `Err(RealtimeError::InsufficientSpace)` if there is no free-region
+  ///   large enough, or the allocation would exceed the
+  ///   [watermark](Allocator::set_watermark).
+  /// - `Err(RealtimeError::WouldAllocate)` if the allocation would need to
+  ///   split a free-region, but there is no room left to track the extra
+  ///   free-region.
+  /// - `Err(RealtimeError::Invalid)` if `size == 0` or `align == 0`, or the
+  ///   combined alignment overflows.
+  pub fn alloc_with_align(
+    &mut self,
+    size: Size,
+    align: Size,
+  ) -> Result<Allocation, RealtimeError> {
+    let size = NonZero::new(self.allocator.size_class(size))
+      .ok_or(RealtimeError::Invalid)?;
+    let align = NonZero::new(align)
+      .and_then(|align| self.allocator.effective_align(align))
+      .ok_or(RealtimeError::Invalid)?;
+    if !self.allocator.within_watermark(size.get()) {
+      return Err(RealtimeError::InsufficientSpace);
+    }
+    let (index, offset) = best_fit(&self.locations, &self.sizes, size, align)
+      .ok_or(RealtimeError::InsufficientSpace)?;
+
+    let location = self.locations[index];
+    let end = location + self.sizes[index];
+    let leading = offset - location;
+    let trailing = end - (offset + size.get());
+    match (leading > 0, trailing > 0) {
+      (false, false) => {
+        self.locations.remove(index);
+        self.sizes.remove(index);
+      },
+      (true, false) => self.sizes[index] = leading,
+      (false, true) => {
+        self.locations[index] = offset + size.get();
+        self.sizes[index] = trailing;
+      },
+      (true, true) => {
+        self.insert(index + 1, offset + size.get(), trailing)?;
+        self.sizes[index] = leading;
+      },
+    }
+    self.allocator.available -= size.get();
+
+    Ok(Allocation { offset, size })
+  }
+
+  /// Free the given allocation
+  ///
+  /// Returns:
+  /// - `Err(RealtimeError::Invalid)` if the allocation is out of bounds, or
+  ///   overlaps a free-region; i.e. a double free.
+  /// - `Err(RealtimeError::WouldAllocate)` if the allocation cannot be
+  ///   coalesced with any neighbouring free-region, and there is no room left
+  ///   to track another free-region. In this case nothing is freed, so the
+  ///   allocation can be freed again after unlocking.
+  pub fn free(&mut self, alloc: Allocation) -> Result<(), RealtimeError> {
+    let offset = alloc.offset;
+    let size = alloc.size.get();
+    let end = offset
+      .checked_add(size)
+      .filter(|&end| end <= self.allocator.capacity())
+      .ok_or(RealtimeError::Invalid)?;
+
+    let index = self.locations.partition_point(|&l| l < offset);
+    let previous = index
+      .checked_sub(1)
+      .map(|i| (self.locations[i], self.sizes[i]));
+    let following = self
+      .locations
+      .get(index)
+      .map(|&location| (location, self.sizes[index]));
+
+    let coalesce_previous = match previous {
+      Some((location, size)) if location + size > offset => {
+        return Err(RealtimeError::Invalid);
+      },
+      Some((location, size)) => location + size == offset,
+      None => false,
+    };
+    let coalesce_following = match following {
+      Some((location, _)) if location < end => {
+        return Err(RealtimeError::Invalid);
+      },
+      Some((location, _)) => location == end,
+      None => false,
+    };
+
+    match (coalesce_previous, coalesce_following) {
+      (true, true) => {
+        self.sizes[index - 1] += size + self.sizes[index];
+        self.locations.remove(index);
+        self.sizes.remove(index);
+      },
+      (true, false) => self.sizes[index - 1] += size,
+      (false, true) => {
+        self.locations[index] = offset;
+        self.sizes[index] += size;
+      },
+      (false, false) => self.insert(index, offset, size)?,
+    }
+    self.allocator.available += size;
+
+    Ok(())
+  }
+
+  /// Add new free space at the end of the pool
+  ///
+  /// Returns:
+  /// - `Err(RealtimeError::WouldAllocate)` if the last free-region doesn't
+  ///   reach the end of the pool, so the new space needs another
+  ///   free-region, and there is no room left to track it.
+  /// - `Err(RealtimeError::Invalid)` if `self.capacity() + additional` would
+  ///   overflow.
+  pub fn grow_capacity(
+    &mut self,
+    additional: Size,
+  ) -> Result<(), RealtimeError> {
+    let capacity = self.allocator.capacity;
+    let Some(new_capacity) = capacity.checked_add(additional) else {
+      return Err(RealtimeError::Invalid);
+    };
+    if additional == 0 {
+      return Ok(());
+    }
+
+    let last = self.locations.len().checked_sub(1);
+    match last {
+      Some(last)
+        if self.locations[last] + self.sizes[last] == capacity.get() =>
+      {
+        self.sizes[last] += additional;
+      },
+      _ => self.insert(self.locations.len(), capacity.get(), additional)?,
+    }
+    self.allocator.capacity = new_capacity;
+    self.allocator.available += additional;
+    Ok(())
+  }
+
+  /// Unlock the allocator, returning to the normal mode
+  ///
+  /// This rebuilds the free-lists, so touches the heap.
+  pub fn unlock(self) -> Allocator {
+    let LockedAllocator {
+      mut allocator,
+      locations,
+      sizes,
+    } = self;
+    let regions = locations
+      .into_iter()
+      .zip(sizes)
+      .map(|(location, size)| FreeRegion {
+        location,
+        size: NonZero::new(size).unwrap_or_else(|| unreachable!()),
+      })
+      .collect();
+    allocator.replace_free_regions(regions);
+    // note: the pool may have grown while locked
+    #[cfg(feature = "wear-stats")]
+    allocator.resize_wear();
+    allocator
+  }
+
+  /// Get the total capacity of the pool
+  pub fn capacity(&self) -> Size {
+    self.allocator.capacity()
+  }
+
+  /// Get the total available memory in the pool
+  ///
+  /// Note: The memory may be fragmented, so it may not be possible to
+  /// allocate an object of this size.
+  pub fn total_available(&self) -> Size {
+    self.allocator.total_available()
+  }
+
+  /// Get the size of the largest available memory region in the pool
+  ///
+  /// This is linear in the number of free-regions.
+  pub fn largest_available(&self) -> Size {
+    self.sizes.iter().copied().max().unwrap_or(0)
+  }
+
+  /// Get the number of separate free-regions
+  pub fn free_regions(&self) -> usize {
+    self.locations.len()
+  }
+
+  /// Get the number of free-regions there is room to track
+  pub fn max_free_regions(&self) -> usize {
+    self.locations.capacity().min(self.sizes.capacity())
+  }
+
+  /// Insert a free-region at `index`, shifting later free-regions along
+  fn insert(
+    &mut self,
+    index: usize,
+    location: Location,
+    size: Size,
+  ) -> Result<(), RealtimeError> {
+    if self.locations.len() == self.max_free_regions() {
+      return Err(RealtimeError::WouldAllocate);
+    }
+    // note: there is spare capacity, so these don't reallocate
+    self.locations.insert(index, location);
+    self.sizes.insert(index, size);
+    Ok(())
+  }
+}
+
+impl fmt::Debug for LockedAllocator {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("LockedAllocator")
+      .field("capacity", &self.capacity())
+      .field("total_available", &self.total_available())
+      .field("free_regions", &self.free_regions())
+      .field("max_free_regions", &self.max_free_regions())
+      .finish()
+  }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RealtimeError {
+  /// There is no free-region large enough
+  InsufficientSpace,
+  /// The operation would need more room to track free-regions than was
+  /// preallocated when locking
+  WouldAllocate,
+  /// The request or allocation was invalid
+  Invalid,
+}
+
+impl Error for RealtimeError {}
+impl fmt::Display for RealtimeError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      RealtimeError::InsufficientSpace => {
+        f.write_str("InsufficientSpace Error: No free-region is large enough")
+      },
+      RealtimeError::WouldAllocate => f.write_str(
+        "WouldAllocate Error: No room left to track another free-region \
+          without touching the heap",
+      ),
+      RealtimeError::Invalid => {
+        f.write_str("Invalid request or allocation, or double free")
+      },
+    }
+  }
+}
@@ -0,0 +1,60 @@
+use {
+  crate::Allocator,
+  ::core::{fmt, ops::Deref},
+};
+
+/// A frozen, read-only [`Allocator`]
+///
+/// Created by [`Allocator::seal`]. This dereferences to the underlying
+/// allocator, so all of its queries & reports keep working, but methods which
+/// would modify it are unavailable; an accidental write is a compile error
+/// rather than a runtime one:
+/// ```compile_fail
+/// # use ::orderly_allocator::Allocator;
+/// let mut sealed = Allocator::new(1_000).seal();
+/// sealed.alloc(100);
+/// ```
+///
+/// This is useful for publishing a finalized layout, e.g. a baked asset pack.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SealedAllocator {
+  allocator: Allocator,
+}
+
+impl Allocator {
+  /// Freeze the allocator, so that it can no longer be modified
+  ///
+  /// See [`SealedAllocator`].
+  pub fn seal(self) -> SealedAllocator {
+    SealedAllocator { allocator: self }
+  }
+}
+
+impl SealedAllocator {
+  /// Unfreeze the allocator, allowing it to be modified again
+  pub fn unseal(self) -> Allocator {
+    self.allocator
+  }
+}
+
+impl Deref for SealedAllocator {
+  type Target = Allocator;
+
+  fn deref(&self) -> &Allocator {
+    &self.allocator
+  }
+}
+
+impl AsRef<Allocator> for SealedAllocator {
+  fn as_ref(&self) -> &Allocator {
+    &self.allocator
+  }
+}
+
+impl fmt::Debug for SealedAllocator {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_tuple("SealedAllocator")
+      .field(&self.allocator)
+      .finish()
+  }
+}
@@ -0,0 +1,286 @@
+use {
+  crate::{Allocation, Allocator, Location, Overflow, Size},
+  ::alloc::{collections::BTreeSet, vec::Vec},
+  ::core::{fmt, num::NonZero, ops::Range},
+};
+
+/// An allocator for very large pools, which are split into fixed-size
+/// segments each with their own free-lists
+///
+/// A directory tracks the largest free-region in each segment, so an
+/// allocation only searches the free-lists of a segment which can actually
+/// fit it, and freeing only touches the segment the allocation came from. The
+/// cost of each operation then depends on the number of free-regions in one
+/// segment, rather than in the whole pool, so it stays flat as pools grow to
+/// hundreds of thousands of free-regions.
+///
+/// The trade-off is that allocations can't span segments, so no allocation
+/// can be larger than the segment size, and free-regions are not coalesced
+/// across segment boundaries.
+#[derive(Clone)]
+pub struct SegmentedAllocator {
+  segment_size: Size,
+  segments: Vec<Allocator>,
+  /// The size of the largest free-region in each segment, & the segment's
+  /// index
+  directory: BTreeSet<(Size, usize)>,
+}
+
+impl SegmentedAllocator {
+  /// Create a new allocator to manage a pool of memory, split into segments
+  /// of `segment_size`
+  ///
+  /// The last segment is smaller if `capacity` is not a multiple of
+  /// `segment_size`.
+  ///
+  /// Panics:
+  /// - Panics if `capacity == 0`
+  /// - Panics if `segment_size` is not a power of two
+  pub fn new(capacity: Size, segment_size: Size) -> Self {
+    assert!(capacity != 0, "`capacity == 0`");
+    assert!(
+      segment_size.is_power_of_two(),
+      "`segment_size` must be a power of two"
+    );
+
+    let segments: Vec<Allocator> = (0..capacity.div_ceil(segment_size))
+      .map(|index| {
+        Allocator::new(segment_size.min(capacity - index * segment_size))
+      })
+      .collect();
+    let directory = segments
+      .iter()
+      .enumerate()
+      .map(|(index, segment)| (segment.largest_available(), index))
+      .collect();
+
+    SegmentedAllocator {
+      segment_size,
+      segments,
+      directory,
+    }
+  }
+
+  /// Try to allocate a region with the provided size
+  ///
+  /// See [`alloc_with_align`](Self::alloc_with_align).
+  pub fn alloc(&mut self, size: Size) -> Option<Allocation> {
+    self.alloc_with_align(size, 1)
+  }
+
+  /// Try to allocate a region with the provided size & alignment
+  ///
+  /// The segment with the smallest largest free-region which can fit `size`
+  /// is tried first, then the next smallest, etc. Alignments larger than the
+  /// segment size, or which are not powers of two, can only be satisfied by
+  /// segments which start at a multiple of `align`.
+  ///
+  /// Returns `None` if:
+  /// - there are no free-regions which can fit the aligned allocation, or
+  /// - `size == 0`, or
+  /// - `align == 0`.
+  pub fn alloc_with_align(
+    &mut self,
+    size: Size,
+    align: Size,
+  ) -> Option<Allocation> {
+    if size == 0 || align == 0 {
+      return None;
+    }
+
+    let mut from = (size, 0);
+    while let Some(&(largest, index)) = self.directory.range(from..).next() {
+      from = (largest, index + 1);
+      let base = self.base(index);
+      if !base.is_multiple_of(align) {
+        continue;
+      }
+      if let Some(allocation) =
+        self.segments[index].alloc_with_align(size, align)
+      {
+        self.update_directory(index, largest);
+        return Some(Allocation {
+          offset: base + allocation.offset,
+          size: allocation.size,
+        });
+      }
+    }
+
+    None
+  }
+
+  /// Free the given allocation
+  ///
+  /// Panics:
+  /// - Panics if the allocation is not within the pool
+  /// - May panic if the allocation's location gets freed twice, see
+  ///   [`Allocator::free`]
+  pub fn free(&mut self, alloc: Allocation) {
+    let index = (alloc.offset / self.segment_size) as usize;
+    assert!(
+      index < self.segments.len(),
+      "{alloc:?} is not within the pool"
+    );
+
+    let base = self.base(index);
+    let largest = self.segments[index].largest_available();
+    self.segments[index].free(Allocation {
+      offset: alloc.offset - base,
+      size: alloc.size,
+    });
+    self.update_directory(index, largest);
+  }
+
+  /// Free ***all*** allocations
+  pub fn reset(&mut self) {
+    for segment in &mut self.segments {
+      segment.reset();
+    }
+    self.directory = self
+      .segments
+      .iter()
+      .enumerate()
+      .map(|(index, segment)| (segment.largest_available(), index))
+      .collect();
+  }
+
+  /// Add new free space at the end of the pool
+  ///
+  /// The last segment is filled up to the segment size first, then new
+  /// segments are added.
+  ///
+  /// Returns `Err(Overflow)` if `self.capacity() + additional` would
+  /// overflow.
+  pub fn grow_capacity(&mut self, additional: Size) -> Result<(), Overflow> {
+    let Some(additional) = NonZero::new(additional) else {
+      return Ok(()); // `additional` is zero, so do nothing
+    };
+    let current_capacity =
+      NonZero::new(self.capacity()).unwrap_or_else(|| unreachable!());
+    if current_capacity.checked_add(additional.get()).is_none() {
+      return Err(Overflow {
+        current_capacity,
+        additional,
+      });
+    }
+
+    let mut remaining = additional.get();
+    let last = self.segments.len() - 1;
+    let room = self.segment_size - self.segments[last].capacity();
+    if room > 0 {
+      let size = room.min(remaining);
+      let largest = self.segments[last].largest_available();
+      // note: a segment is never larger than `segment_size`, so this can't
+      // overflow
+      self.segments[last]
+        .grow_capacity(size)
+        .unwrap_or_else(|_| unreachable!());
+      self.update_directory(last, largest);
+      remaining -= size;
+    }
+    while remaining > 0 {
+      let segment = Allocator::new(self.segment_size.min(remaining));
+      remaining -= segment.capacity();
+      self
+        .directory
+        .insert((segment.largest_available(), self.segments.len()));
+      self.segments.push(segment);
+    }
+    Ok(())
+  }
+
+  /// Returns an iterator over the unallocated regions which overlap `range`
+  ///
+  /// Only the segments overlapping `range` are visited. The regions are
+  /// reported a segment at a time, in order of location, and smallest first
+  /// within each segment. As with [`Allocator::report_free_regions`], this
+  /// is intended for debugging.
+  pub fn report_free_regions(
+    &self,
+    range: Range<Location>,
+  ) -> impl Iterator<Item = Allocation> + use<'_> {
+    let first = (range.start / self.segment_size) as usize;
+    let last = (range.end.div_ceil(self.segment_size) as usize)
+      .min(self.segments.len());
+    (first..last).flat_map(move |index| {
+      let base = self.base(index);
+      let range = range.clone();
+      self.segments[index]
+        .report_free_regions()
+        .map(move |region| Allocation {
+          offset: base + region.offset,
+          size: region.size,
+        })
+        .filter(move |region| {
+          region.offset < range.end
+            && (range.start as usize) < region.range().end
+        })
+    })
+  }
+
+  /// Get the segment containing `offset`, & the location it starts at
+  ///
+  /// The segment's own [`Allocator`] can be used to query e.g. its available
+  /// space; note that its offsets are relative to the start of the segment.
+  ///
+  /// Returns `None` if `offset` is not within the pool.
+  pub fn segment(&self, offset: Location) -> Option<(Location, &Allocator)> {
+    let index = (offset / self.segment_size) as usize;
+    Some((self.base(index), self.segments.get(index)?))
+  }
+
+  /// Get the number of segments
+  pub fn segment_count(&self) -> usize {
+    self.segments.len()
+  }
+
+  /// Get the size of each segment
+  pub fn segment_size(&self) -> Size {
+    self.segment_size
+  }
+
+  /// Get the total capacity of the pool
+  pub fn capacity(&self) -> Size {
+    self.segments.iter().map(Allocator::capacity).sum()
+  }
+
+  /// Get the total available memory in the pool
+  pub fn total_available(&self) -> Size {
+    self.segments.iter().map(Allocator::total_available).sum()
+  }
+
+  /// Get the size of the largest free-region in the pool
+  pub fn largest_available(&self) -> Size {
+    self.directory.last().map_or(0, |&(largest, _)| largest)
+  }
+
+  /// Returns true if there are no allocations
+  pub fn is_empty(&self) -> bool {
+    self.segments.iter().all(Allocator::is_empty)
+  }
+
+  /// Get the location the segment with the given index starts at
+  fn base(&self, index: usize) -> Location {
+    index as Location * self.segment_size
+  }
+
+  /// Update the directory entry of a segment, after its largest free-region
+  /// may have changed from `largest`
+  fn update_directory(&mut self, index: usize, largest: Size) {
+    self.directory.remove(&(largest, index));
+    self
+      .directory
+      .insert((self.segments[index].largest_available(), index));
+  }
+}
+
+impl fmt::Debug for SegmentedAllocator {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("SegmentedAllocator")
+      .field("segment_size", &self.segment_size)
+      .field("segments", &self.segments.len())
+      .field("capacity", &self.capacity())
+      .field("total_available", &self.total_available())
+      .finish()
+  }
+}
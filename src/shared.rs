@@ -0,0 +1,290 @@
+use {
+  crate::{Allocation, FixedFreeError, InBandError, InBandPool, Size},
+  ::core::{
+    fmt, hint,
+    num::NonZero,
+    ptr::NonNull,
+    slice,
+    sync::atomic::{AtomicU32, Ordering},
+  },
+};
+
+/// The size of the shared header, before the [`InBandPool`]
+///
+/// This holds the lock word & the sequence counter, padded so that the pool
+/// keeps its alignment.
+const SHARED_HEADER: usize = 16;
+
+/// An [`InBandPool`] in shared memory, which several processes can
+/// suballocate from at once, e.g. a producer & consumer sharing a staging
+/// area
+///
+/// The mapping starts with a lock word & a sequence counter, followed by the
+/// pool itself. Every operation takes the lock, which records the `owner` of
+/// the handle holding it; typically a process id. If a process dies while
+/// holding the lock, another can take it over with
+/// [`recover`](Self::recover). The sequence counter is bumped by every
+/// allocation & free, so it can be polled to notice changes without taking
+/// the lock.
+///
+/// Allocations' offsets are relative to the start of the mapping, so they
+/// can be passed between processes even if the mapping is at a different
+/// address in each one.
+///
+/// The bookkeeping is checked in full by [`open`](Self::open) &
+/// [`recover`](Self::recover). Other operations only check the header, so
+/// they stay cheap; this is enough to keep them within the bookkeeping, but
+/// not to notice a damaged free-region table.
+pub struct SharedPool {
+  memory: NonNull<u8>,
+  len: usize,
+  owner: NonZero<u32>,
+}
+
+// SAFETY: the memory is only accessed through atomics, or while holding the
+// lock
+unsafe impl Send for SharedPool {}
+unsafe impl Sync for SharedPool {}
+
+impl SharedPool {
+  /// Format a shared mapping as an empty pool, with room to track up to
+  /// `max_free_regions` separate free-regions
+  ///
+  /// See [`InBandPool::format`].
+  ///
+  /// # Safety
+  ///
+  /// - `memory` must be valid for reads & writes of `len` bytes for as long as
+  ///   the returned handle lives, and aligned to 4 bytes.
+  /// - Once formatted, the mapping must only be accessed through
+  ///   `SharedPool`s, apart from the contents of live allocations.
+  /// - No other process may use the mapping until this returns.
+  pub unsafe fn format(
+    memory: NonNull<u8>,
+    len: usize,
+    max_free_regions: Size,
+    owner: NonZero<u32>,
+  ) -> Result<Self, InBandError> {
+    debug_assert!(memory.as_ptr().cast::<u32>().is_aligned());
+    let pool = SharedPool { memory, len, owner };
+    let pool_len = len
+      .checked_sub(SHARED_HEADER)
+      .ok_or(InBandError::TooSmall)?;
+    // SAFETY: nobody else is using the mapping yet, & it is valid for `len`
+    // bytes
+    unsafe {
+      memory.as_ptr().write_bytes(0, SHARED_HEADER);
+      let bytes = slice::from_raw_parts_mut(
+        memory.as_ptr().add(SHARED_HEADER),
+        pool_len,
+      );
+      InBandPool::format(bytes, max_free_regions)?;
+    }
+    Ok(pool)
+  }
+
+  /// Open a shared mapping previously formatted by
+  /// [`format`](Self::format), possibly in another process
+  ///
+  /// See [`InBandPool::open`].
+  ///
+  /// # Safety
+  ///
+  /// - `memory` must be valid for reads & writes of `len` bytes for as long as
+  ///   the returned handle lives, and aligned to 4 bytes.
+  /// - The mapping must only be accessed through `SharedPool`s, apart from
+  ///   the contents of live allocations.
+  /// - `owner` must not be used by any other live handle to the mapping.
+  pub unsafe fn open(
+    memory: NonNull<u8>,
+    len: usize,
+    owner: NonZero<u32>,
+  ) -> Result<Self, InBandError> {
+    debug_assert!(memory.as_ptr().cast::<u32>().is_aligned());
+    if len < SHARED_HEADER {
+      return Err(InBandError::Corrupt);
+    }
+    let pool = SharedPool { memory, len, owner };
+    pool.with_pool(|pool| pool.validate())??;
+    Ok(pool)
+  }
+
+  /// Try to allocate a region with the provided size
+  ///
+  /// See [`alloc_with_align`](Self::alloc_with_align).
+  pub fn alloc(&self, size: Size) -> Option<Allocation> {
+    self.alloc_with_align(size, 1)
+  }
+
+  /// Try to allocate a region with the provided size & alignment
+  ///
+  /// See [`InBandPool::alloc_with_align`]. Also returns `None` if the pool
+  /// has been corrupted.
+  pub fn alloc_with_align(
+    &self,
+    size: Size,
+    align: Size,
+  ) -> Option<Allocation> {
+    let allocation = self
+      .with_pool(|pool| {
+        let allocation = pool.alloc_with_align(size, align)?;
+        self.sequence_word().fetch_add(1, Ordering::Release);
+        Some(allocation)
+      })
+      .ok()??;
+    Some(Allocation {
+      offset: allocation.offset + SHARED_HEADER as Size,
+      size: allocation.size,
+    })
+  }
+
+  /// Free the given allocation
+  ///
+  /// See [`InBandPool::free`]. Also returns `Err(FixedFreeError::Invalid)`
+  /// if the pool has been corrupted.
+  pub fn free(&self, alloc: Allocation) -> Result<(), FixedFreeError> {
+    let offset = (alloc.offset as usize)
+      .checked_sub(SHARED_HEADER)
+      .ok_or(FixedFreeError::Invalid)?;
+    let alloc = Allocation {
+      offset: offset as Size,
+      size: alloc.size,
+    };
+    self
+      .with_pool(|pool| {
+        pool.free(alloc)?;
+        self.sequence_word().fetch_add(1, Ordering::Release);
+        Ok(())
+      })
+      .map_err(|_| FixedFreeError::Invalid)?
+  }
+
+  /// Get the total available memory in the pool
+  ///
+  /// Returns `0` if the pool has been corrupted.
+  pub fn total_available(&self) -> Size {
+    self.with_pool(|pool| pool.total_available()).unwrap_or(0)
+  }
+
+  /// Get the size of the largest available memory region in the pool
+  ///
+  /// Returns `0` if the pool has been corrupted.
+  pub fn largest_available(&self) -> Size {
+    self.with_pool(|pool| pool.largest_available()).unwrap_or(0)
+  }
+
+  /// Get the number of allocations & frees made in the pool, by any process
+  ///
+  /// This doesn't take the lock, so it is cheap to poll for changes. It wraps
+  /// on overflow.
+  pub fn sequence(&self) -> u32 {
+    self.sequence_word().load(Ordering::Acquire)
+  }
+
+  /// Get the owner of the handle which currently holds the lock, if any
+  pub fn lock_owner(&self) -> Option<NonZero<u32>> {
+    NonZero::new(self.lock_word().load(Ordering::Relaxed))
+  }
+
+  /// Take over the lock from a handle whose process has died while holding
+  /// it, & check that the pool is still consistent
+  ///
+  /// Returns `Ok(false)` if `dead_owner` doesn't hold the lock, & so nothing
+  /// was done.
+  ///
+  /// Returns `Err(InBandError::Corrupt)` if the dead process left the pool
+  /// half-modified. The lock is released either way.
+  ///
+  /// # Safety
+  ///
+  /// The process using `dead_owner` must really have died, so that it can't
+  /// still be modifying the pool.
+  pub unsafe fn recover(
+    &self,
+    dead_owner: NonZero<u32>,
+  ) -> Result<bool, InBandError> {
+    let taken = self
+      .lock_word()
+      .compare_exchange(
+        dead_owner.get(),
+        self.owner.get(),
+        Ordering::Acquire,
+        Ordering::Relaxed,
+      )
+      .is_ok();
+    if !taken {
+      return Ok(false);
+    }
+    // SAFETY: we hold the lock
+    let result = unsafe { self.open_locked() }
+      .and_then(|pool| pool.validate())
+      .map(|_| true);
+    self.lock_word().store(0, Ordering::Release);
+    result
+  }
+
+  /// Run `f` on the pool, while holding the lock
+  fn with_pool<R>(
+    &self,
+    f: impl FnOnce(&mut InBandPool<'_>) -> R,
+  ) -> Result<R, InBandError> {
+    while self
+      .lock_word()
+      .compare_exchange_weak(
+        0,
+        self.owner.get(),
+        Ordering::Acquire,
+        Ordering::Relaxed,
+      )
+      .is_err()
+    {
+      hint::spin_loop();
+    }
+    // SAFETY: we hold the lock
+    let result = unsafe { self.open_locked() }.map(|mut pool| f(&mut pool));
+    self.lock_word().store(0, Ordering::Release);
+    result
+  }
+
+  /// Open the pool itself, checking only its header
+  ///
+  /// The pool only ever borrows its bookkeeping, never the live allocations,
+  /// which other processes may be using at the same time.
+  ///
+  /// # Safety
+  ///
+  /// The caller must hold the lock until the returned pool is dropped.
+  unsafe fn open_locked(&self) -> Result<InBandPool<'_>, InBandError> {
+    // SAFETY: the mapping is valid for `len` bytes, & holding the lock gives
+    // exclusive access to the bookkeeping
+    let pool = unsafe {
+      InBandPool::from_raw(
+        self.memory.add(SHARED_HEADER),
+        self.len - SHARED_HEADER,
+      )
+    };
+    pool.validate_header()?;
+    Ok(pool)
+  }
+
+  fn lock_word(&self) -> &AtomicU32 {
+    // SAFETY: the mapping is aligned, & this word is only accessed atomically
+    unsafe { AtomicU32::from_ptr(self.memory.as_ptr().cast()) }
+  }
+
+  fn sequence_word(&self) -> &AtomicU32 {
+    // SAFETY: the mapping is aligned, & this word is only accessed atomically
+    unsafe { AtomicU32::from_ptr(self.memory.as_ptr().cast::<u32>().add(1)) }
+  }
+}
+
+impl fmt::Debug for SharedPool {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("SharedPool")
+      .field("len", &self.len)
+      .field("owner", &self.owner)
+      .field("sequence", &self.sequence())
+      .field("lock_owner", &self.lock_owner())
+      .finish()
+  }
+}
@@ -0,0 +1,153 @@
+use {
+  crate::{Allocation, Allocator, Overflow, Size},
+  ::alloc::sync::Arc,
+  ::core::fmt,
+};
+
+/// An [`Allocator`] with cheap snapshots, which are copied in full on their
+/// first modification
+///
+/// Taking a [`snapshot`](Self::snapshot) is O(1); the snapshot shares the
+/// allocator's state until either of them is modified, at which point the
+/// modified one takes its own copy. This allows speculative branches, e.g.
+/// two candidate packing plans, to be explored from the same starting state
+/// before one of them is committed, while snapshots which are never modified
+/// cost almost nothing.
+///
+/// note: The state is shared as a whole, not node-by-node as in a persistent
+/// data structure with path-copying. So the first modification after a
+/// snapshot copies the whole allocator, as with [`Clone`], which is O(n) in
+/// the number of free-regions; only later modifications are as cheap as
+/// usual. Branches which diverge by a few operations each still pay for a
+/// full copy.
+///
+/// Snapshots are detached from the allocator's event sender, threshold
+/// callback & [hooks](Allocator::set_hooks), so speculative branches don't
+/// report their operations, or poison the external buffer. They are detached
+/// when the snapshot is first modified, or taken with
+/// [`into_allocator`](Self::into_allocator); set them up again on a snapshot
+/// which is committed.
+#[derive(Clone)]
+pub struct SnapshotAllocator {
+  allocator: Arc<Allocator>,
+  /// Whether this is a snapshot, whose state must be detached from the
+  /// original's sinks before it is modified
+  detached: bool,
+}
+
+impl SnapshotAllocator {
+  /// Create a new allocator to manage a pool of memory
+  ///
+  /// Panics:
+  /// - Panics if `capacity == 0`
+  pub fn new(capacity: Size) -> Self {
+    Allocator::new(capacity).into()
+  }
+
+  /// Take a snapshot of the allocator's current state
+  ///
+  /// The snapshot is itself a `SnapshotAllocator`, so it can be modified
+  /// independently, or kept to roll back to later.
+  pub fn snapshot(&self) -> Self {
+    SnapshotAllocator {
+      allocator: Arc::clone(&self.allocator),
+      detached: true,
+    }
+  }
+
+  /// Try to allocate a region with the provided size
+  ///
+  /// See [`Allocator::alloc`].
+  pub fn alloc(&mut self, size: Size) -> Option<Allocation> {
+    self.alloc_with_align(size, 1)
+  }
+
+  /// Try to allocate a region with the provided size & alignment
+  ///
+  /// See [`Allocator::alloc_with_align`].
+  pub fn alloc_with_align(
+    &mut self,
+    size: Size,
+    align: Size,
+  ) -> Option<Allocation> {
+    // note: don't copy a shared allocator just to fail
+    self.allocator.peek_best_fit(size, align)?;
+    self.allocator_mut().alloc_with_align(size, align)
+  }
+
+  /// Free the given allocation
+  ///
+  /// See [`Allocator::free`].
+  pub fn free(&mut self, alloc: Allocation) {
+    self.allocator_mut().free(alloc);
+  }
+
+  /// Add new free space at the end of the pool
+  ///
+  /// See [`Allocator::grow_capacity`].
+  pub fn grow_capacity(&mut self, additional: Size) -> Result<(), Overflow> {
+    self.allocator_mut().grow_capacity(additional)
+  }
+
+  /// Returns true if the state is shared with a snapshot, so the next
+  /// modification will copy it
+  pub fn is_shared(&self) -> bool {
+    Arc::strong_count(&self.allocator) > 1
+  }
+
+  /// Get the underlying [`Allocator`]
+  ///
+  /// This can be used to query e.g. the available space in the pool.
+  pub fn allocator(&self) -> &Allocator {
+    &self.allocator
+  }
+
+  /// Get mutable access to the underlying [`Allocator`], copying its state
+  /// first if it is shared with a snapshot
+  pub fn allocator_mut(&mut self) -> &mut Allocator {
+    let allocator = Arc::make_mut(&mut self.allocator);
+    if self.detached {
+      allocator.detach_sinks();
+      self.detached = false;
+    }
+    allocator
+  }
+
+  /// Take the underlying [`Allocator`], copying its state if it is shared
+  /// with a snapshot
+  pub fn into_allocator(self) -> Allocator {
+    let mut allocator = Arc::unwrap_or_clone(self.allocator);
+    if self.detached {
+      allocator.detach_sinks();
+    }
+    allocator
+  }
+}
+
+impl From<Allocator> for SnapshotAllocator {
+  fn from(allocator: Allocator) -> Self {
+    SnapshotAllocator {
+      allocator: Arc::new(allocator),
+      detached: false,
+    }
+  }
+}
+
+impl fmt::Debug for SnapshotAllocator {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("SnapshotAllocator")
+      .field("shared", &self.is_shared())
+      .field("allocator", &self.allocator)
+      .finish()
+  }
+}
+
+impl Allocator {
+  /// Stop sending events, invoking the threshold callback & invoking hooks
+  fn detach_sinks(&mut self) {
+    #[cfg(feature = "std")]
+    self.set_event_sender(None);
+    self.clear_thresholds();
+    self.clear_hooks();
+  }
+}
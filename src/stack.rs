@@ -0,0 +1,126 @@
+use {
+  crate::{Allocation, Location, Size},
+  ::core::num::NonZero,
+};
+
+/// A LIFO allocator for strictly nested scratch lifetimes
+///
+/// Allocations are pushed onto the top of the stack, and freed in bulk by
+/// rolling the stack back to a [`StackMarker`]. There are no free-lists, so
+/// allocating & freeing are constant time, and this type can be constructed
+/// in a `const` context.
+///
+/// For example:
+/// ```
+/// # use ::orderly_allocator::StackAllocator;
+/// let mut stack = StackAllocator::new(1_000);
+/// let frame = stack.mark();
+/// stack.alloc(100).unwrap();
+/// let b = stack.alloc_with_align(100, 64).unwrap();
+/// assert_eq!(b.offset(), 128);
+/// stack.free_to_mark(frame);
+/// assert!(stack.is_empty());
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct StackAllocator {
+  capacity: Size,
+  /// The end of the topmost allocation
+  top: Location,
+}
+
+/// A position in a [`StackAllocator`], to roll it back to
+///
+/// See [`StackAllocator::mark`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct StackMarker(Location);
+
+impl StackAllocator {
+  /// Create a new allocator to manage a pool of memory
+  ///
+  /// A `capacity` of 0 results in an allocator which can never allocate.
+  pub const fn new(capacity: Size) -> Self {
+    StackAllocator { capacity, top: 0 }
+  }
+
+  /// Try to allocate a region with the provided size
+  ///
+  /// See [`alloc_with_align`](Self::alloc_with_align).
+  pub fn alloc(&mut self, size: Size) -> Option<Allocation> {
+    self.alloc_with_align(size, 1)
+  }
+
+  /// Try to allocate a region with the provided size & alignment, on the top
+  /// of the stack
+  ///
+  /// Any padding needed to reach the alignment is skipped over, and is
+  /// reclaimed when the stack is rolled back past it.
+  ///
+  /// Returns `None` if:
+  /// - there is not enough space left on the stack, or
+  /// - `size == 0`, or
+  /// - `align == 0`.
+  pub fn alloc_with_align(
+    &mut self,
+    size: Size,
+    align: Size,
+  ) -> Option<Allocation> {
+    let size = NonZero::new(size)?;
+    if align == 0 {
+      return None;
+    }
+    let offset = self.top.checked_next_multiple_of(align)?;
+    let end = offset
+      .checked_add(size.get())
+      .filter(|&end| end <= self.capacity)?;
+    self.top = end;
+
+    Some(Allocation { offset, size })
+  }
+
+  /// Get a marker for the current top of the stack
+  ///
+  /// Pass it to [`free_to_mark`](Self::free_to_mark) to free everything
+  /// allocated since.
+  pub fn mark(&self) -> StackMarker {
+    StackMarker(self.top)
+  }
+
+  /// Free every allocation made since `marker` was taken
+  ///
+  /// Panics:
+  /// - Panics if the stack has already been rolled back below `marker`
+  pub fn free_to_mark(&mut self, marker: StackMarker) {
+    assert!(
+      marker.0 <= self.top,
+      "The stack has already been rolled back below {marker:?}"
+    );
+    self.top = marker.0;
+  }
+
+  /// Free ***all*** allocations
+  pub fn reset(&mut self) {
+    self.top = 0;
+  }
+
+  /// Get the total capacity of the pool
+  pub fn capacity(&self) -> Size {
+    self.capacity
+  }
+
+  /// Get the space used by the stack, including padding
+  pub fn used(&self) -> Size {
+    self.top
+  }
+
+  /// Get the space left above the top of the stack
+  ///
+  /// Note: Aligned allocations may need some of this for padding.
+  pub fn total_available(&self) -> Size {
+    self.capacity - self.top
+  }
+
+  /// Returns true if there are no allocations
+  pub fn is_empty(&self) -> bool {
+    self.top == 0
+  }
+}
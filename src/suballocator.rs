@@ -0,0 +1,203 @@
+#[cfg(target_has_atomic = "ptr")]
+use crate::SnapshotAllocator;
+use {
+  crate::{
+    Allocation, Allocator, BitmapAllocator, FixedAllocator, FixedFreeError,
+    GuardedAllocator, LockedAllocator, Overflow, RealtimeError,
+    SegmentedAllocator, Size,
+  },
+  ::core::error::Error,
+};
+
+/// The operations common to the allocators in this crate
+///
+/// This allows downstream code to be generic over the allocation strategy,
+/// e.g. to swap between [`Allocator`] & [`FixedAllocator`], or to benchmark
+/// them against each other. Allocators which take more configuration than a
+/// capacity are created by [`with_capacity`](Self::with_capacity) with the
+/// defaults noted on each implementation; create them with their own
+/// constructors to choose otherwise.
+pub trait Suballocator: Sized {
+  /// The error returned when [`grow`](Self::grow) fails
+  type GrowError: Error;
+
+  /// Create a new allocator to manage a pool of `capacity` bytes
+  ///
+  /// Panics:
+  /// - May panic if `capacity == 0`, depending on the implementation
+  fn with_capacity(capacity: Size) -> Self;
+
+  /// Try to allocate a region with the provided size & alignment
+  ///
+  /// Returns `None` if the request cannot be satisfied, or if `size == 0` or
+  /// `align == 0`.
+  fn allocate(&mut self, size: Size, align: Size) -> Option<Allocation>;
+
+  /// Free the given allocation
+  ///
+  /// Panics:
+  /// - May panic if the allocation is invalid, e.g. a double free
+  fn deallocate(&mut self, allocation: Allocation);
+
+  /// Add `additional` bytes of free space at the end of the pool
+  fn grow(&mut self, additional: Size) -> Result<(), Self::GrowError>;
+}
+
+impl Suballocator for Allocator {
+  type GrowError = Overflow;
+
+  fn with_capacity(capacity: Size) -> Self {
+    Allocator::new(capacity)
+  }
+
+  fn allocate(&mut self, size: Size, align: Size) -> Option<Allocation> {
+    self.alloc_with_align(size, align)
+  }
+
+  fn deallocate(&mut self, allocation: Allocation) {
+    self.free(allocation)
+  }
+
+  fn grow(&mut self, additional: Size) -> Result<(), Overflow> {
+    self.grow_capacity(additional)
+  }
+}
+
+/// Note: Freeing through [`deallocate`](Suballocator::deallocate) panics on
+/// [`FixedFreeError::Invalid`], but leaks the allocation on
+/// [`FixedFreeError::TableFull`], like [`FixedAllocator::free`].
+impl<const N: usize> Suballocator for FixedAllocator<N> {
+  type GrowError = FixedFreeError;
+
+  fn with_capacity(capacity: Size) -> Self {
+    FixedAllocator::new(capacity)
+  }
+
+  fn allocate(&mut self, size: Size, align: Size) -> Option<Allocation> {
+    self.alloc_with_align(size, align)
+  }
+
+  fn deallocate(&mut self, allocation: Allocation) {
+    if let Err(FixedFreeError::Invalid) = self.free(allocation) {
+      panic!("invalid allocation, or double free");
+    }
+  }
+
+  fn grow(&mut self, additional: Size) -> Result<(), FixedFreeError> {
+    self.grow_capacity(additional)
+  }
+}
+
+/// Note: [`with_capacity`](Suballocator::with_capacity) uses blocks of 1
+/// byte.
+impl Suballocator for BitmapAllocator {
+  type GrowError = Overflow;
+
+  fn with_capacity(capacity: Size) -> Self {
+    BitmapAllocator::new(capacity, 1)
+  }
+
+  fn allocate(&mut self, size: Size, align: Size) -> Option<Allocation> {
+    self.alloc_with_align(size, align)
+  }
+
+  fn deallocate(&mut self, allocation: Allocation) {
+    self.free(allocation)
+  }
+
+  fn grow(&mut self, additional: Size) -> Result<(), Overflow> {
+    self.grow_capacity(additional)
+  }
+}
+
+/// Note: [`with_capacity`](Suballocator::with_capacity) uses segments of
+/// 16 MiB.
+impl Suballocator for SegmentedAllocator {
+  type GrowError = Overflow;
+
+  fn with_capacity(capacity: Size) -> Self {
+    SegmentedAllocator::new(capacity, 1 << 24)
+  }
+
+  fn allocate(&mut self, size: Size, align: Size) -> Option<Allocation> {
+    self.alloc_with_align(size, align)
+  }
+
+  fn deallocate(&mut self, allocation: Allocation) {
+    self.free(allocation)
+  }
+
+  fn grow(&mut self, additional: Size) -> Result<(), Overflow> {
+    self.grow_capacity(additional)
+  }
+}
+
+/// Note: [`with_capacity`](Suballocator::with_capacity) uses guard bands of
+/// 64 bytes.
+impl Suballocator for GuardedAllocator {
+  type GrowError = Overflow;
+
+  fn with_capacity(capacity: Size) -> Self {
+    GuardedAllocator::new(capacity, 64)
+  }
+
+  fn allocate(&mut self, size: Size, align: Size) -> Option<Allocation> {
+    self.alloc_with_align(size, align)
+  }
+
+  fn deallocate(&mut self, allocation: Allocation) {
+    self.free(allocation)
+  }
+
+  fn grow(&mut self, additional: Size) -> Result<(), Overflow> {
+    self.grow_capacity(additional)
+  }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl Suballocator for SnapshotAllocator {
+  type GrowError = Overflow;
+
+  fn with_capacity(capacity: Size) -> Self {
+    SnapshotAllocator::new(capacity)
+  }
+
+  fn allocate(&mut self, size: Size, align: Size) -> Option<Allocation> {
+    self.alloc_with_align(size, align)
+  }
+
+  fn deallocate(&mut self, allocation: Allocation) {
+    self.free(allocation)
+  }
+
+  fn grow(&mut self, additional: Size) -> Result<(), Overflow> {
+    self.grow_capacity(additional)
+  }
+}
+
+/// Note: [`with_capacity`](Suballocator::with_capacity) makes room for 64
+/// free-regions. [`allocate`](Suballocator::allocate) returns `None` on any
+/// [`RealtimeError`], and [`deallocate`](Suballocator::deallocate) panics on
+/// [`RealtimeError::Invalid`], but leaks the allocation on
+/// [`RealtimeError::WouldAllocate`], like [`FixedAllocator`].
+impl Suballocator for LockedAllocator {
+  type GrowError = RealtimeError;
+
+  fn with_capacity(capacity: Size) -> Self {
+    Allocator::new(capacity).lock(64)
+  }
+
+  fn allocate(&mut self, size: Size, align: Size) -> Option<Allocation> {
+    self.alloc_with_align(size, align).ok()
+  }
+
+  fn deallocate(&mut self, allocation: Allocation) {
+    if let Err(RealtimeError::Invalid) = self.free(allocation) {
+      panic!("invalid allocation, or double free");
+    }
+  }
+
+  fn grow(&mut self, additional: Size) -> Result<(), RealtimeError> {
+    self.grow_capacity(additional)
+  }
+}
@@ -0,0 +1,92 @@
+use {
+  crate::Allocator,
+  ::alloc::{sync::Arc, vec::Vec},
+};
+
+/// A usage threshold being crossed, see [`Allocator::on_threshold_crossed`]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ThresholdCrossing {
+  /// Usage rose to or above the given percentage of the capacity
+  Rising(u8),
+  /// Usage fell below the given percentage of the capacity
+  Falling(u8),
+}
+
+/// The registered usage thresholds & their callback
+#[derive(Clone)]
+pub(crate) struct Thresholds {
+  /// The threshold percentages, in ascending order
+  percents: Vec<u8>,
+  callback: Arc<dyn Fn(ThresholdCrossing) + Send + Sync>,
+}
+
+impl Allocator {
+  /// Register usage thresholds, and a callback to invoke whenever usage
+  /// crosses them in either direction
+  ///
+  /// `thresholds` are percentages of the capacity. The callback is invoked
+  /// with [`ThresholdCrossing::Rising`] when an operation takes usage to or
+  /// above a threshold, and [`ThresholdCrossing::Falling`] when it drops back
+  /// below. If one operation crosses several thresholds, the callback is
+  /// invoked for each of them, in the order they were crossed.
+  ///
+  /// This can be used to e.g. trim caches or grow the pool before allocations
+  /// start failing. Any previously registered thresholds are replaced.
+  ///
+  /// Note: The callback is shared with clones of the allocator.
+  ///
+  /// Panics:
+  /// - Panics if any threshold is greater than `100`
+  pub fn on_threshold_crossed(
+    &mut self,
+    thresholds: &[u8],
+    callback: impl Fn(ThresholdCrossing) + Send + Sync + 'static,
+  ) {
+    assert!(
+      thresholds.iter().all(|&percent| percent <= 100),
+      "thresholds must be at most 100"
+    );
+    let mut percents = thresholds.to_vec();
+    percents.sort_unstable();
+    percents.dedup();
+
+    self.thresholds = Some(Thresholds {
+      percents,
+      callback: Arc::new(callback),
+    });
+  }
+
+  /// Remove the usage thresholds & their callback
+  ///
+  /// See [`on_threshold_crossed`](Self::on_threshold_crossed).
+  pub fn clear_thresholds(&mut self) {
+    self.thresholds = None;
+  }
+
+  /// Invoke the threshold callback for any thresholds crossed since the usage
+  /// was `(used, capacity)`
+  pub(crate) fn notify_thresholds(&self, (used, capacity): (u64, u64)) {
+    let Some(Thresholds { percents, callback }) = &self.thresholds else {
+      return;
+    };
+    let (used_now, capacity_now) = self.usage();
+    let above = |used: u64, capacity: u64, percent: u8| {
+      used * 100 >= capacity * percent as u64
+    };
+
+    for &percent in percents {
+      if !above(used, capacity, percent)
+        && above(used_now, capacity_now, percent)
+      {
+        callback(ThresholdCrossing::Rising(percent));
+      }
+    }
+    for &percent in percents.iter().rev() {
+      if above(used, capacity, percent)
+        && !above(used_now, capacity_now, percent)
+      {
+        callback(ThresholdCrossing::Falling(percent));
+      }
+    }
+  }
+}
@@ -0,0 +1,218 @@
+use {
+  crate::{Allocation, Allocator, DropCheck, Size},
+  ::alloc::vec::Vec,
+  ::core::{error::Error, fmt, str::FromStr},
+};
+
+/// A recorded stream of allocator operations, for offline analysis
+///
+/// A trace can be built up op-by-op while running a workload, or ingested
+/// from text; one op per line, `alloc <size> <align>` or `free <index>`,
+/// where `index` is the index of the `alloc` op being freed. Blank lines &
+/// lines starting with `#` are ignored. [`Display`](fmt::Display) writes the
+/// same format.
+///
+/// [`replay`](Self::replay) runs the trace against an [`Allocator`], and
+/// produces a time series of [`TraceSample`]s; this can be used to compare
+/// capacities & strategies against a production workload.
+/// ```
+/// # use ::orderly_allocator::{Allocator, Trace};
+/// let trace: Trace = "alloc 100 1\nalloc 100 1\nfree 0".parse().unwrap();
+/// let samples = trace.replay(Allocator::new(1_000));
+///
+/// assert_eq!(samples[2].allocated, 100);
+/// assert_eq!(samples[2].largest_available, 800);
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Trace {
+  ops: Vec<TraceOp>,
+}
+
+/// A single operation in a [`Trace`]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum TraceOp {
+  /// An allocation with the given size & alignment
+  Alloc { size: Size, align: Size },
+  /// Free the allocation made by the op at `index`
+  Free { index: usize },
+}
+
+/// The state of the allocator after replaying an op of a [`Trace`]
+///
+/// See [`Trace::replay`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct TraceSample {
+  /// Whether this op was an allocation which could not be satisfied
+  pub failed: bool,
+  /// The total size of all live allocations
+  pub allocated: Size,
+  /// The total free space in the pool
+  pub total_available: Size,
+  /// The size of the largest free-region
+  pub largest_available: Size,
+  /// The number of separate free-regions
+  pub free_regions: usize,
+}
+
+impl TraceSample {
+  /// Get the fraction of the free space which is not part of the largest
+  /// free-region
+  ///
+  /// This is `0.0` when all free space is contiguous (or there is none), and
+  /// approaches `1.0` as the free space is split into many small regions.
+  pub fn fragmentation(&self) -> f64 {
+    if self.total_available == 0 {
+      return 0.;
+    }
+    1. - self.largest_available as f64 / self.total_available as f64
+  }
+
+  /// Get the fraction of the pool which is allocated
+  pub fn occupancy(&self) -> f64 {
+    let capacity = self.allocated as f64 + self.total_available as f64;
+    self.allocated as f64 / capacity
+  }
+}
+
+impl Trace {
+  /// Create an empty trace
+  pub fn new() -> Self {
+    Trace::default()
+  }
+
+  /// Record an allocation with the given size & alignment
+  ///
+  /// Returns the index of the op, to be passed to [`free`](Self::free).
+  pub fn alloc(&mut self, size: Size, align: Size) -> usize {
+    self.ops.push(TraceOp::Alloc { size, align });
+    self.ops.len() - 1
+  }
+
+  /// Record freeing the allocation made by the op at `index`
+  ///
+  /// Panics:
+  /// - Panics if the op at `index` is not an `alloc`
+  pub fn free(&mut self, index: usize) {
+    assert!(
+      matches!(self.ops.get(index), Some(TraceOp::Alloc { .. })),
+      "op {index} is not an alloc"
+    );
+    self.ops.push(TraceOp::Free { index });
+  }
+
+  /// Get the recorded ops
+  pub fn ops(&self) -> &[TraceOp] {
+    &self.ops
+  }
+
+  /// Run the trace against `allocator`, sampling its state after each op
+  ///
+  /// Failed allocations are recorded in the samples, and freeing them is a
+  /// no-op; so a trace can be replayed against a pool which is too small.
+  /// Freeing an allocation a second time is also a no-op. Allocations the
+  /// trace leaves outstanding are dropped along with `allocator`, so its
+  /// [drop check](Allocator::set_drop_check) is turned off.
+  pub fn replay(&self, mut allocator: Allocator) -> Vec<TraceSample> {
+    allocator.set_drop_check(DropCheck::Off);
+    let mut live: Vec<Option<Allocation>> = Vec::with_capacity(self.ops.len());
+    let mut samples = Vec::with_capacity(self.ops.len());
+
+    for &op in &self.ops {
+      let mut failed = false;
+      match op {
+        TraceOp::Alloc { size, align } => {
+          let allocation = allocator.alloc_with_align(size, align);
+          failed = allocation.is_none();
+          live.push(allocation);
+        },
+        TraceOp::Free { index } => {
+          if let Some(allocation) = live[index].take() {
+            allocator.free(allocation);
+          }
+          live.push(None);
+        },
+      }
+
+      let stats = allocator.stats();
+      samples.push(TraceSample {
+        failed,
+        allocated: stats.capacity - stats.total_available,
+        total_available: stats.total_available,
+        largest_available: stats.largest_available,
+        free_regions: stats.free_regions,
+      });
+    }
+
+    samples
+  }
+}
+
+impl fmt::Display for Trace {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for op in &self.ops {
+      match op {
+        TraceOp::Alloc { size, align } => {
+          f.write_fmt(format_args!("alloc {size} {align}\n"))?
+        },
+        TraceOp::Free { index } => {
+          f.write_fmt(format_args!("free {index}\n"))?
+        },
+      }
+    }
+    Ok(())
+  }
+}
+
+impl FromStr for Trace {
+  type Err = ParseTraceError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut trace = Trace::new();
+
+    for (line_index, line) in s.lines().enumerate() {
+      let error = ParseTraceError {
+        line: line_index + 1,
+      };
+      let line = line.trim();
+      if line.is_empty() || line.starts_with('#') {
+        continue;
+      }
+
+      let mut words = line.split_whitespace();
+      let op = match (words.next(), words.next(), words.next()) {
+        (Some("alloc"), Some(size), Some(align)) => TraceOp::Alloc {
+          size: size.parse().map_err(|_| error)?,
+          align: align.parse().map_err(|_| error)?,
+        },
+        (Some("free"), Some(index), None) => {
+          let index = index.parse().map_err(|_| error)?;
+          if !matches!(trace.ops.get(index), Some(TraceOp::Alloc { .. })) {
+            return Err(error);
+          }
+          TraceOp::Free { index }
+        },
+        _ => return Err(error),
+      };
+      if words.next().is_some() {
+        return Err(error);
+      }
+      trace.ops.push(op);
+    }
+
+    Ok(trace)
+  }
+}
+
+/// The error returned when a [`Trace`] could not be parsed
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ParseTraceError {
+  /// The line number of the invalid op, starting at `1`
+  pub line: usize,
+}
+
+impl Error for ParseTraceError {}
+impl fmt::Display for ParseTraceError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_fmt(format_args!("Invalid trace op on line {}", self.line))
+  }
+}
@@ -0,0 +1,748 @@
+#[cfg(target_has_atomic = "ptr")]
+use ::alloc::sync::Arc;
+use {
+  crate::{
+    Allocation, Allocator, DropCheck, Location, MigrationCopy, MigrationError,
+    MigrationPlan, Size,
+  },
+  ::alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+  },
+  ::core::{fmt, num::NonZero},
+};
+
+/// An [`Allocator`] which keeps track of its live allocations, each with some
+/// attached user data
+///
+/// This avoids keeping a parallel `HashMap<offset, T>` next to the allocator;
+/// the data can be retrieved using the [`Allocation`] as a handle, and is
+/// handed back when the allocation is freed.
+///
+/// Each allocation is also given a stable [`AllocationId`], which can be
+/// stored in place of the [`Allocation`] and resolved on demand. Ids survive
+/// [`compact`](Self::compact), so a pool addressed only through ids can be
+/// defragmented without patching any offsets.
+///
+/// Tracking also makes freeing safer; freeing something which is not a live
+/// allocation panics, rather than corrupting the free-lists. Allocations which
+/// are still live when the allocator is dropped can be reported, see
+/// [`on_leak`](Self::on_leak).
+#[derive(Clone)]
+pub struct TrackedAllocator<T = ()> {
+  allocator: Allocator,
+  allocations: BTreeMap<Location, Entry<T>>,
+  /// The location of the allocation with each id
+  ids: IdTable,
+  /// The number of allocations made so far, used to measure their age
+  clock: u64,
+  #[cfg(target_has_atomic = "ptr")]
+  leak_sink: Option<LeakSink>,
+}
+
+/// A sink for [`LeakReport`]s, see [`TrackedAllocator::on_leak`]
+#[cfg(target_has_atomic = "ptr")]
+type LeakSink = Arc<dyn Fn(&LeakReport) + Send + Sync>;
+
+/// A live allocation tracked by a [`TrackedAllocator`]
+#[derive(Clone)]
+struct Entry<T> {
+  size: NonZero<Size>,
+  /// The alignment requested for the allocation, kept when compacting
+  align: NonZero<Size>,
+  data: T,
+  id: AllocationId,
+  /// The value of the clock when this was allocated
+  born: u64,
+  #[cfg(feature = "debug-names")]
+  name: Option<&'static str>,
+}
+
+/// A stable handle to an allocation in a [`TrackedAllocator`]
+///
+/// This is a slot-map key; a dense 32-bit [`index`](Self::index), plus a
+/// generation which is bumped whenever the slot is reused. Ids can be stored
+/// long-term, e.g. in scene data, and resolved to an [`Allocation`] with
+/// [`TrackedAllocator::resolve`]; an id whose allocation has since been freed
+/// is rejected, even if its index has been reused.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct AllocationId {
+  index: u32,
+  generation: u32,
+}
+
+impl AllocationId {
+  /// Get the dense index of this id
+  ///
+  /// Indices of live allocations are unique & reused after a free, so this
+  /// can be stored in 4 bytes by e.g. GPU-side structures, or used to index a
+  /// side table, and resolved with [`TrackedAllocator::resolve_index`].
+  /// Unlike the full id, it can't detect stale handles.
+  pub fn index(self) -> u32 {
+    self.index
+  }
+
+  /// Get the id as a plain `u64`, e.g. to serialize it
+  pub fn to_bits(self) -> u64 {
+    (self.generation as u64) << 32 | self.index as u64
+  }
+
+  /// Reconstruct an id from [`to_bits`](Self::to_bits)
+  pub fn from_bits(bits: u64) -> Self {
+    AllocationId {
+      index: bits as u32,
+      generation: (bits >> 32) as u32,
+    }
+  }
+}
+
+/// The slot map from [`AllocationId`]s to locations
+#[derive(Clone, Debug, Default)]
+struct IdTable {
+  slots: Vec<Slot>,
+  /// The indices of the empty slots
+  vacant: Vec<u32>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Slot {
+  generation: u32,
+  location: Option<Location>,
+}
+
+impl IdTable {
+  fn insert(&mut self, location: Location) -> AllocationId {
+    let index = match self.vacant.pop() {
+      Some(index) => index,
+      None => {
+        self.slots.push(Slot {
+          generation: 0,
+          location: None,
+        });
+        // note: there can't be more than `u32::MAX` live allocations, since
+        // each one takes at least one byte of a `u32`-sized pool
+        (self.slots.len() - 1) as u32
+      },
+    };
+    let slot = &mut self.slots[index as usize];
+    slot.location = Some(location);
+    AllocationId {
+      index,
+      generation: slot.generation,
+    }
+  }
+
+  fn remove(&mut self, id: AllocationId) {
+    let slot = &mut self.slots[id.index as usize];
+    slot.location = None;
+    // note: a generation would have to be reused ~4 billion times before a
+    // stale id could be mistaken for a live one
+    slot.generation = slot.generation.wrapping_add(1);
+    self.vacant.push(id.index);
+  }
+
+  fn get(&self, id: AllocationId) -> Option<Location> {
+    let slot = self.slots.get(id.index as usize)?;
+    (slot.generation == id.generation).then_some(slot.location)?
+  }
+
+  /// Get the location of a live index, whatever its generation
+  fn get_index(&self, index: u32) -> Option<Location> {
+    self.slots.get(index as usize)?.location
+  }
+
+  /// Update the location of a live id
+  fn relocate(&mut self, id: AllocationId, location: Location) {
+    self.slots[id.index as usize].location = Some(location);
+  }
+}
+
+impl<T> Entry<T> {
+  /// Get the data, if `size` matches the live allocation
+  fn check(&self, size: NonZero<Size>) -> Option<&Self> {
+    (self.size == size).then_some(self)
+  }
+}
+
+impl<T> TrackedAllocator<T> {
+  /// Create a new allocator to manage a pool of memory
+  ///
+  /// Panics:
+  /// - Panics if `capacity == 0`
+  pub fn new(capacity: Size) -> Self {
+    TrackedAllocator::from_allocator(Allocator::new(capacity))
+  }
+
+  /// Track the allocations of a configured allocator, e.g. one made with
+  /// [`Allocator::builder`]
+  ///
+  /// The allocator's [drop check](Allocator::set_drop_check) is turned off;
+  /// leaked allocations are reported through [`on_leak`](Self::on_leak)
+  /// instead.
+  ///
+  /// Panics:
+  /// - Panics if the allocator has any live allocations
+  pub fn from_allocator(mut allocator: Allocator) -> Self {
+    assert!(allocator.is_empty(), "The allocator has live allocations");
+    allocator.set_drop_check(DropCheck::Off);
+    TrackedAllocator {
+      allocator,
+      allocations: BTreeMap::new(),
+      ids: IdTable::default(),
+      clock: 0,
+      #[cfg(target_has_atomic = "ptr")]
+      leak_sink: None,
+    }
+  }
+
+  /// Try to allocate a region with the provided size, attaching `data` to it
+  ///
+  /// See [`Allocator::alloc`].
+  pub fn alloc(&mut self, size: Size, data: T) -> Option<Allocation> {
+    self.alloc_with_align(size, 1, data)
+  }
+
+  /// Try to allocate a region with the provided size & alignment, attaching
+  /// `data` to it
+  ///
+  /// See [`Allocator::alloc_with_align`].
+  pub fn alloc_with_align(
+    &mut self,
+    size: Size,
+    align: Size,
+    data: T,
+  ) -> Option<Allocation> {
+    let allocation = self.allocator.alloc_with_align(size, align)?;
+    self.track(allocation, NonZero::new(align)?, data);
+
+    Some(allocation)
+  }
+
+  /// Free the given allocation, returning its data
+  ///
+  /// Panics:
+  /// - Panics if the allocation is not live
+  pub fn free(&mut self, alloc: Allocation) -> T {
+    match self.allocations.remove(&alloc.offset) {
+      Some(entry) if entry.size == alloc.size => {
+        self.allocator.free(alloc);
+        self.ids.remove(entry.id);
+        entry.data
+      },
+      _ => panic!("not a live allocation: {alloc:?}"),
+    }
+  }
+
+  /// Split a live allocation into two parts, which can then be freed or
+  /// reallocated independently
+  ///
+  /// The first part is the first `at` bytes of `alloc`, and keeps its data,
+  /// id & name. The second part is the rest, and is given `data` & a new id.
+  /// See [`Allocator::split_allocation`].
+  ///
+  /// Panics:
+  /// - Panics if the allocation is not live
+  /// - Panics if `at == 0` or `at >= alloc.size()`
+  pub fn split_allocation(
+    &mut self,
+    alloc: Allocation,
+    at: Size,
+    data: T,
+  ) -> (Allocation, Allocation) {
+    let Some(entry) = self
+      .allocations
+      .get_mut(&alloc.offset)
+      .filter(|entry| entry.size == alloc.size)
+    else {
+      panic!("not a live allocation: {alloc:?}");
+    };
+    let (first, second) = self.allocator.split_allocation(alloc, at);
+    entry.size = first.size;
+    self.track(second, NonZero::<Size>::MIN, data);
+
+    (first, second)
+  }
+
+  /// Merge two exactly adjacent live allocations into one
+  ///
+  /// The merged allocation keeps the data, id & name of whichever allocation
+  /// comes first in the pool; the other allocation's data is returned
+  /// alongside it, and its id becomes stale. See
+  /// [`Allocator::merge_adjacent`].
+  ///
+  /// Returns `None` if the allocations are not exactly adjacent.
+  ///
+  /// Panics:
+  /// - Panics if either allocation is not live
+  pub fn merge_adjacent(
+    &mut self,
+    a: Allocation,
+    b: Allocation,
+  ) -> Option<(Allocation, T)> {
+    for alloc in [a, b] {
+      if self.get(alloc).is_none() {
+        panic!("not a live allocation: {alloc:?}");
+      }
+    }
+    let merged = self.allocator.merge_adjacent(a, b)?;
+    let second = if a.offset == merged.offset { b } else { a };
+    let entry = self
+      .allocations
+      .remove(&second.offset)
+      .unwrap_or_else(|| unreachable!());
+    self.ids.remove(entry.id);
+    if let Some(first) = self.allocations.get_mut(&merged.offset) {
+      first.size = merged.size;
+    }
+
+    Some((merged, entry.data))
+  }
+
+  /// Free the allocation with the given id, returning its data
+  ///
+  /// Panics:
+  /// - Panics if the id does not belong to a live allocation
+  pub fn free_id(&mut self, id: AllocationId) -> T {
+    let alloc = self.resolve(id).expect("not a live allocation id");
+    self.free(alloc)
+  }
+
+  /// Get the id of a live allocation
+  ///
+  /// Returns `None` if `alloc` is not live.
+  pub fn id(&self, alloc: Allocation) -> Option<AllocationId> {
+    Some(self.allocations.get(&alloc.offset)?.check(alloc.size)?.id)
+  }
+
+  /// Get the allocation with the given id
+  ///
+  /// Returns `None` if the id does not belong to a live allocation.
+  pub fn resolve(&self, id: AllocationId) -> Option<Allocation> {
+    let offset = self.ids.get(id)?;
+    let size = self.allocations[&offset].size;
+    Some(Allocation { offset, size })
+  }
+
+  /// Get the allocation with the given [index](AllocationId::index)
+  ///
+  /// This is for compact 32-bit handles. The generation isn't checked, so if
+  /// the allocation has been freed & its index reused, this returns the
+  /// allocation which reused it; use [`resolve`](Self::resolve) to reject
+  /// stale handles.
+  ///
+  /// Returns `None` if no live allocation has the index.
+  pub fn resolve_index(&self, index: u32) -> Option<Allocation> {
+    let offset = self.ids.get_index(index)?;
+    let size = self.allocations[&offset].size;
+    Some(Allocation { offset, size })
+  }
+
+  /// Free every allocation for which `keep` returns false
+  ///
+  /// `keep` is called for each live allocation in order of location, and can
+  /// inspect & modify its data. This can be used to e.g. evict stale entries
+  /// from a cache.
+  pub fn retain(&mut self, mut keep: impl FnMut(Allocation, &mut T) -> bool) {
+    let allocator = &mut self.allocator;
+    let ids = &mut self.ids;
+    self.allocations.retain(|&offset, entry| {
+      let allocation = Allocation {
+        offset,
+        size: entry.size,
+      };
+      let retain = keep(allocation, &mut entry.data);
+      if !retain {
+        allocator.free(allocation);
+        ids.remove(entry.id);
+      }
+      retain
+    });
+  }
+
+  /// Get the data attached to a live allocation
+  ///
+  /// Returns `None` if `alloc` is not live.
+  pub fn get(&self, alloc: Allocation) -> Option<&T> {
+    let entry = self.allocations.get(&alloc.offset)?.check(alloc.size)?;
+    Some(&entry.data)
+  }
+
+  /// Mutably get the data attached to a live allocation
+  ///
+  /// Returns `None` if `alloc` is not live.
+  pub fn get_mut(&mut self, alloc: Allocation) -> Option<&mut T> {
+    match self.allocations.get_mut(&alloc.offset) {
+      Some(entry) if entry.size == alloc.size => Some(&mut entry.data),
+      _ => None,
+    }
+  }
+
+  /// Returns an iterator over the live allocations & their data, in order of
+  /// location
+  pub fn iter(&self) -> impl Iterator<Item = (Allocation, &T)> + use<'_, T> {
+    self.allocations.iter().map(|(&offset, entry)| {
+      let allocation = Allocation {
+        offset,
+        size: entry.size,
+      };
+      (allocation, &entry.data)
+    })
+  }
+
+  /// Attach a debug name to a live allocation, replacing any previous name
+  ///
+  /// Names are included in [`dump`](Self::dump) & in leak reports.
+  ///
+  /// Panics:
+  /// - Panics if the allocation is not live
+  #[cfg(feature = "debug-names")]
+  pub fn set_name(&mut self, alloc: Allocation, name: &'static str) {
+    match self.allocations.get_mut(&alloc.offset) {
+      Some(entry) if entry.size == alloc.size => entry.name = Some(name),
+      _ => panic!("not a live allocation: {alloc:?}"),
+    }
+  }
+
+  /// Get the debug name of a live allocation
+  ///
+  /// Returns `None` if `alloc` is not live, or has not been named.
+  #[cfg(feature = "debug-names")]
+  pub fn name(&self, alloc: Allocation) -> Option<&'static str> {
+    self.allocations.get(&alloc.offset)?.check(alloc.size)?.name
+  }
+
+  /// Get a human-readable listing of the live allocations, in order of
+  /// location
+  ///
+  /// Each allocation is written on its own line as `offset..end (size)`,
+  /// followed by its debug name if it has one.
+  pub fn dump(&self) -> impl fmt::Display + use<'_, T> {
+    Dump(self)
+  }
+
+  /// List the live allocations, as they would be reported if the allocator
+  /// were dropped now
+  pub fn leak_report(&self) -> LeakReport {
+    let leaks = self
+      .allocations
+      .iter()
+      .map(|(&offset, entry)| Leak {
+        allocation: Allocation {
+          offset,
+          size: entry.size,
+        },
+        age: self.clock - entry.born,
+        #[cfg(feature = "debug-names")]
+        name: entry.name,
+      })
+      .collect();
+
+    LeakReport { leaks }
+  }
+
+  /// Set a sink to receive a [`LeakReport`] if the allocator is dropped while
+  /// allocations are still live
+  ///
+  /// Leaked suballocations are otherwise invisible. The sink can e.g. log
+  /// the report, or panic in tests. Any previous sink is replaced.
+  ///
+  /// Note: The sink is shared with clones of the allocator.
+  #[cfg(target_has_atomic = "ptr")]
+  pub fn on_leak(
+    &mut self,
+    sink: impl Fn(&LeakReport) + Send + Sync + 'static,
+  ) {
+    self.leak_sink = Some(Arc::new(sink));
+  }
+
+  /// Get the number of live allocations
+  pub fn len(&self) -> usize {
+    self.allocations.len()
+  }
+
+  /// Returns true if there are no live allocations
+  pub fn is_empty(&self) -> bool {
+    self.allocations.is_empty()
+  }
+
+  /// Get the underlying [`Allocator`]
+  ///
+  /// This can be used to query e.g. the available space in the pool.
+  pub fn allocator(&self) -> &Allocator {
+    &self.allocator
+  }
+
+  /// Plan moving live allocations into another pool, e.g. to demote cold
+  /// resources from device memory to host memory
+  ///
+  /// Each move is an allocation's id & the alignment it needs in
+  /// `destination`. The destination locations are chosen as if the moves
+  /// were allocated in order, but nothing is changed until the plan is
+  /// committed with [`commit_migration`](Self::commit_migration); copy the
+  /// data as described by [`MigrationPlan::copies`] first.
+  ///
+  /// Returns:
+  /// - `Err(MigrationError::InvalidId)` if an id does not belong to a live
+  ///   allocation, or appears more than once.
+  /// - `Err(MigrationError::InsufficientSpace)` if `destination` can't fit
+  ///   every move.
+  pub fn plan_migration(
+    &self,
+    destination: &TrackedAllocator<T>,
+    moves: &[(AllocationId, Size)],
+  ) -> Result<MigrationPlan, MigrationError> {
+    // note: the simulation mustn't notify anyone, nor complain when dropped
+    let mut simulated = destination.allocator.clone();
+    #[cfg(target_has_atomic = "ptr")]
+    simulated.clear_thresholds();
+    #[cfg(feature = "std")]
+    simulated.set_event_sender(None);
+    simulated.set_drop_check(DropCheck::Off);
+
+    let mut seen = BTreeSet::new();
+    let mut copies = Vec::with_capacity(moves.len());
+    for &(id, align) in moves {
+      let from = self
+        .resolve(id)
+        .filter(|_| seen.insert(id))
+        .ok_or(MigrationError::InvalidId(id))?;
+      let to = simulated
+        .alloc_with_align(from.size(), align)
+        .ok_or(MigrationError::InsufficientSpace)?;
+      copies.push(MigrationCopy { id, from, to });
+    }
+
+    Ok(MigrationPlan {
+      copies,
+      aligns: moves.iter().map(|&(_, align)| align).collect(),
+      source_hash: self.allocator.state_hash(),
+      destination_hash: destination.allocator.state_hash(),
+    })
+  }
+
+  /// Apply the bookkeeping of a [`MigrationPlan`]
+  ///
+  /// This is all-or-nothing; either every allocation is moved into
+  /// `destination`, keeping its data & name but with a new id, or neither
+  /// allocator is changed. Returns the new ids, in the same order as the
+  /// plan's [`copies`](MigrationPlan::copies).
+  ///
+  /// Returns `Err(MigrationError::Stale)` if either allocator has changed
+  /// since the plan was made.
+  pub fn commit_migration(
+    &mut self,
+    destination: &mut TrackedAllocator<T>,
+    plan: MigrationPlan,
+  ) -> Result<Vec<AllocationId>, MigrationError> {
+    let is_current = self.allocator.state_hash() == plan.source_hash
+      && destination.allocator.state_hash() == plan.destination_hash
+      && plan
+        .copies
+        .iter()
+        .all(|copy| self.resolve(copy.id) == Some(copy.from));
+    if !is_current {
+      return Err(MigrationError::Stale);
+    }
+
+    // note: this replays the plan's simulation, so should reach the same
+    // locations; if not, e.g. with `Placement::Random`, undo & bail
+    for (index, (copy, &align)) in
+      plan.copies.iter().zip(&plan.aligns).enumerate()
+    {
+      let to = destination
+        .allocator
+        .alloc_with_align(copy.from.size(), align);
+      if to != Some(copy.to) {
+        // note: these frees bypass deferred coalescing, so the destination's
+        // free-lists are left as they were
+        to.inspect(|&to| {
+          destination.allocator.free_now(to);
+        });
+        for copy in plan.copies[..index].iter().rev() {
+          destination.allocator.free_now(copy.to);
+        }
+        return Err(MigrationError::Stale);
+      }
+    }
+
+    let ids = plan
+      .copies
+      .iter()
+      .zip(&plan.aligns)
+      .map(|(copy, &align)| {
+        let entry = self
+          .allocations
+          .remove(&copy.from.offset)
+          .unwrap_or_else(|| unreachable!());
+        self.allocator.free(copy.from);
+        self.ids.remove(entry.id);
+        let align = NonZero::new(align).unwrap_or_else(|| unreachable!());
+        let id = destination.track(copy.to, align, entry.data);
+        #[cfg(feature = "debug-names")]
+        if let Some(moved) = destination.allocations.get_mut(&copy.to.offset) {
+          moved.name = entry.name;
+        }
+        id
+      })
+      .collect();
+
+    Ok(ids)
+  }
+
+  /// Slide every live allocation down towards the start of the pool,
+  /// closing the gaps between them, & return the copies needed to move their
+  /// data
+  ///
+  /// Each allocation keeps its id, data & name, and the alignment it was
+  /// allocated with. Callers which hold [`AllocationId`]s rather than
+  /// [`Allocation`]s don't need to patch anything; [`resolve`](Self::resolve)
+  /// returns the new locations. This makes defragmentation transparent to
+  /// everything but the code which owns the memory.
+  ///
+  /// The copies are within this pool, in order of location, & should be
+  /// applied in that order. A copy's source & destination may overlap, so
+  /// use a copy which allows that, e.g. [`slice::copy_within`]. Frees
+  /// deferred by [`Allocator::set_deferred_coalescing`] are coalesced first.
+  pub fn compact(&mut self) -> Vec<MigrationCopy> {
+    self.allocator.coalesce_all();
+    let mut copies = Vec::new();
+    let mut cursor: Location = 0;
+    let locations: Vec<Location> = self.allocations.keys().copied().collect();
+    for location in locations {
+      let entry = &self.allocations[&location];
+      let from = Allocation {
+        offset: location,
+        size: entry.size,
+      };
+      // note: the alignment was valid when allocated, so is still valid
+      let align = self
+        .allocator
+        .effective_align(entry.align)
+        .unwrap_or_else(|| unreachable!());
+      let target = cursor
+        .checked_next_multiple_of(align.get())
+        .filter(|&target| target < location);
+      let Some(target) = target else {
+        cursor = location + from.size.get();
+        continue;
+      };
+
+      let to = self.allocator.relocate(from, target);
+      let entry = self
+        .allocations
+        .remove(&location)
+        .unwrap_or_else(|| unreachable!());
+      self.ids.relocate(entry.id, target);
+      copies.push(MigrationCopy {
+        id: entry.id,
+        from,
+        to,
+      });
+      self.allocations.insert(target, entry);
+      cursor = target + from.size.get();
+    }
+
+    copies
+  }
+
+  /// Start tracking a new allocation, returning its id
+  fn track(
+    &mut self,
+    allocation: Allocation,
+    align: NonZero<Size>,
+    data: T,
+  ) -> AllocationId {
+    let id = self.ids.insert(allocation.offset);
+    self.allocations.insert(
+      allocation.offset,
+      Entry {
+        size: allocation.size,
+        align,
+        data,
+        id,
+        born: self.clock,
+        #[cfg(feature = "debug-names")]
+        name: None,
+      },
+    );
+    self.clock += 1;
+    id
+  }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T> Drop for TrackedAllocator<T> {
+  fn drop(&mut self) {
+    if let Some(sink) = &self.leak_sink {
+      if !self.allocations.is_empty() {
+        sink(&self.leak_report());
+      }
+    }
+  }
+}
+
+/// The allocations which were still live when a [`TrackedAllocator`] was
+/// dropped
+///
+/// See [`TrackedAllocator::on_leak`].
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct LeakReport {
+  /// The leaked allocations, in order of location
+  pub leaks: Vec<Leak>,
+}
+
+/// A leaked allocation, see [`LeakReport`]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Leak {
+  /// The leaked allocation
+  pub allocation: Allocation,
+  /// The number of allocations made after this one
+  pub age: u64,
+  /// The debug name of the allocation, if any
+  #[cfg(feature = "debug-names")]
+  pub name: Option<&'static str>,
+}
+
+impl fmt::Display for LeakReport {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_fmt(format_args!("{} leaked allocations\n", self.leaks.len()))?;
+    for leak in &self.leaks {
+      let Allocation { offset, size } = leak.allocation;
+      let end = offset as u64 + size.get() as u64;
+      f.write_fmt(format_args!("{offset}..{end} ({size})"))?;
+      #[cfg(feature = "debug-names")]
+      if let Some(name) = leak.name {
+        f.write_fmt(format_args!(" {name}"))?;
+      }
+      f.write_fmt(format_args!(", age {}\n", leak.age))?;
+    }
+    Ok(())
+  }
+}
+
+/// See [`TrackedAllocator::dump`]
+struct Dump<'a, T>(&'a TrackedAllocator<T>);
+
+impl<T> fmt::Display for Dump<'_, T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (&offset, entry) in &self.0.allocations {
+      let end = offset as u64 + entry.size.get() as u64;
+      f.write_fmt(format_args!("{offset}..{end} ({})", entry.size))?;
+      #[cfg(feature = "debug-names")]
+      if let Some(name) = entry.name {
+        f.write_fmt(format_args!(" {name}"))?;
+      }
+      f.write_str("\n")?;
+    }
+    Ok(())
+  }
+}
+
+impl<T> fmt::Debug for TrackedAllocator<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("TrackedAllocator")
+      .field("allocations", &self.allocations.len())
+      .field("allocator", &self.allocator)
+      .finish()
+  }
+}
@@ -0,0 +1,177 @@
+use {
+  crate::{Allocation, Allocator, Size},
+  ::core::{alloc::Layout, fmt, marker::PhantomData, mem},
+};
+
+/// Types which can be safely reinterpreted from any sequence of bytes
+///
+/// This is what allows [`PoolBox`] to hand out references into a plain byte
+/// buffer.
+///
+/// # Safety
+///
+/// Implementors must:
+/// - be valid for every possible bit pattern, and
+/// - contain no padding bytes.
+pub unsafe trait Plain: Copy + 'static {}
+
+macro_rules! impl_plain {
+  ($($t:ty),* $(,)?) => {
+    $(
+      // SAFETY: primitive numeric types are valid for any bit pattern, and
+      // contain no padding
+      unsafe impl Plain for $t {}
+    )*
+  };
+}
+impl_plain!(
+  u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64,
+);
+// SAFETY: arrays have no padding between their elements
+unsafe impl<T: Plain, const N: usize> Plain for [T; N] {}
+
+/// A typed handle to an allocation holding a single `T`
+///
+/// This only records where the value lives; the memory itself belongs to a
+/// buffer managed by the user. Use [`get`](Self::get) &
+/// [`get_mut`](Self::get_mut) to resolve the handle into a reference within
+/// that buffer, and [`Allocator::free_typed`] to free it.
+pub struct PoolBox<T> {
+  allocation: Allocation,
+  marker: PhantomData<fn() -> T>,
+}
+
+impl<T> PoolBox<T> {
+  /// Get the underlying [`Allocation`]
+  pub fn allocation(&self) -> Allocation {
+    self.allocation
+  }
+
+  /// Get the offset of the value within the buffer
+  pub fn offset(&self) -> Size {
+    self.allocation.offset
+  }
+
+  /// Resolve the handle into a reference to the value within `buffer`
+  ///
+  /// Returns `None` if:
+  /// - `buffer` is too short to contain the value, or
+  /// - the value's address within `buffer` is not aligned for `T`.
+  pub fn get<'b>(&self, buffer: &'b [u8]) -> Option<&'b T>
+  where
+    T: Plain,
+  {
+    let ptr = buffer.get(self.allocation.range())?.as_ptr().cast::<T>();
+    // SAFETY: The pointer is in bounds of `buffer` for `size_of::<T>()` bytes,
+    // and any bytes are a valid `T`
+    ptr.is_aligned().then(|| unsafe { &*ptr })
+  }
+
+  /// Resolve the handle into a mutable reference to the value within `buffer`
+  ///
+  /// Returns `None` if:
+  /// - `buffer` is too short to contain the value, or
+  /// - the value's address within `buffer` is not aligned for `T`.
+  pub fn get_mut<'b>(&self, buffer: &'b mut [u8]) -> Option<&'b mut T>
+  where
+    T: Plain,
+  {
+    let ptr = buffer
+      .get_mut(self.allocation.range())?
+      .as_mut_ptr()
+      .cast::<T>();
+    // SAFETY: The pointer is in bounds of `buffer` for `size_of::<T>()` bytes,
+    // and any bytes are a valid `T`. `T` contains no padding, so any value
+    // written through the reference leaves the bytes initialised.
+    ptr.is_aligned().then(|| unsafe { &mut *ptr })
+  }
+}
+
+impl<T> fmt::Debug for PoolBox<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("PoolBox")
+      .field("type", &::core::any::type_name::<T>())
+      .field("allocation", &self.allocation)
+      .finish()
+  }
+}
+
+impl Allocation {
+  /// Get the number of whole elements of type `T` which fit in the
+  /// allocation
+  ///
+  /// This is the inverse of [`Allocator::alloc_elements`]. The allocation may
+  /// hold more elements than were requested, if its size was rounded up e.g.
+  /// by [size classes](Allocator::set_size_classes).
+  ///
+  /// Returns `0` if `T` is zero-sized.
+  pub fn element_count<T>(&self) -> usize {
+    (self.size() as usize)
+      .checked_div(mem::size_of::<T>())
+      .unwrap_or(0)
+  }
+}
+
+impl Allocator {
+  /// Try to allocate a region with the size & alignment of a `T`
+  ///
+  /// Returns `None` if:
+  /// - there are no free-regions with enough space, or
+  /// - `T` is zero-sized, or
+  /// - the size or alignment of `T` does not fit in a `u32`.
+  pub fn alloc_typed<T>(&mut self) -> Option<PoolBox<T>> {
+    let size = Size::try_from(mem::size_of::<T>()).ok()?;
+    let align = Size::try_from(mem::align_of::<T>()).ok()?;
+
+    let allocation = self.alloc_with_align(size, align)?;
+
+    Some(PoolBox {
+      allocation,
+      marker: PhantomData,
+    })
+  }
+
+  /// Try to allocate a region for an array of `count` elements of type `T`
+  ///
+  /// The size is `count * size_of::<T>()`, & the alignment is
+  /// `align_of::<T>()`. Use [`Allocation::element_count`] to recover the
+  /// number of elements from the result.
+  ///
+  /// Returns `None` if:
+  /// - there are no free-regions with enough space, or
+  /// - `count == 0`, or `T` is zero-sized, or
+  /// - the size or alignment does not fit in a `u32`.
+  pub fn alloc_elements<T>(&mut self, count: usize) -> Option<Allocation> {
+    let size = mem::size_of::<T>().checked_mul(count)?;
+    let size = Size::try_from(size).ok()?;
+    let align = Size::try_from(mem::align_of::<T>()).ok()?;
+
+    self.alloc_with_align(size, align)
+  }
+
+  /// Try to allocate a region with the size & alignment of `layout`
+  ///
+  /// This lets code built around [`Layout`], e.g. custom containers or FFI
+  /// shims, use the allocator directly.
+  ///
+  /// Returns `None` if:
+  /// - there are no free-regions with enough space, or
+  /// - `layout` has a size of zero, or
+  /// - the size or alignment does not fit in a `u32`.
+  pub fn alloc_layout(&mut self, layout: Layout) -> Option<Allocation> {
+    let size = Size::try_from(layout.size()).ok()?;
+    let align = Size::try_from(layout.align()).ok()?;
+
+    self.alloc_with_align(size, align)
+  }
+
+  /// Free the given typed allocation
+  ///
+  /// # Panics
+  ///
+  /// - May panic if the allocation's location gets freed twice, see
+  ///   [`free`](Self::free).
+  pub fn free_typed<T>(&mut self, pool_box: PoolBox<T>) {
+    self.free(pool_box.allocation)
+  }
+}
@@ -0,0 +1,168 @@
+//! Bounded model checks of the [`Allocator`]'s core invariants, for [Kani]
+//!
+//! Experimental: these harnesses have only been type-checked. They have not
+//! been run under `cargo kani` yet, so it isn't known whether they terminate,
+//! or whether the unwind bound is enough to cover the B-tree & `Vec` loops.
+//! They prove nothing until they have been run & pass.
+//!
+//! Each harness explores every sequence of operations on a small pool, up to
+//! a fixed length, and checks after every step that:
+//! - the accounting is correct; the free-regions add up to
+//!   `total_available()`, and together with the live allocations add up to
+//!   the capacity,
+//! - no free-region or live allocation overlaps another, or the end of the
+//!   pool,
+//! - the free-lists agree with each other, and
+//! - coalescing is total; no two free-regions are adjacent.
+//!
+//! The pools are kept small so the B-trees should stay within a single node,
+//! to keep the checks tractable.
+//!
+//! [Kani]: https://model-checking.github.io/kani/
+
+use {
+  crate::{Allocation, Allocator, FreeRegion, Size},
+  ::alloc::vec::Vec,
+};
+
+/// The largest pool checked
+const MAX_CAPACITY: Size = 16;
+/// The number of operations in each sequence
+const STEPS: usize = 4;
+
+/// Check every invariant, given the allocations which should be live
+fn check_invariants(allocator: &Allocator, live: &[Allocation]) {
+  let capacity = allocator.capacity.get();
+  let free: Vec<FreeRegion> = allocator
+    .location_map
+    .iter()
+    .map(|(&location, &size)| FreeRegion { location, size })
+    .collect();
+
+  // the free-lists agree
+  assert_eq!(allocator.free.len(), free.len());
+  for region in &free {
+    assert!(allocator.free.contains(region));
+  }
+
+  // accounting
+  let free_total: Size = free.iter().map(|region| region.size.get()).sum();
+  let live_total: Size = live.iter().map(Allocation::size).sum();
+  assert_eq!(free_total, allocator.available);
+  assert_eq!(free_total + live_total, capacity);
+
+  // no overlaps; the free-regions are sorted by location, so only neighbours
+  // need comparing, and coalescing is total
+  for pair in free.windows(2) {
+    assert!(pair[0].location + pair[0].size.get() < pair[1].location);
+  }
+  if let Some(last) = free.last() {
+    assert!(last.location + last.size.get() <= capacity);
+  }
+  for (i, a) in live.iter().enumerate() {
+    assert!(a.offset + a.size.get() <= capacity);
+    for region in &free {
+      assert!(!overlaps(
+        a.offset,
+        a.size(),
+        region.location,
+        region.size.get()
+      ));
+    }
+    for b in &live[i + 1..] {
+      assert!(!overlaps(a.offset, a.size(), b.offset, b.size()));
+    }
+  }
+}
+
+fn overlaps(a: Size, a_size: Size, b: Size, b_size: Size) -> bool {
+  a < b + b_size && b < a + a_size
+}
+
+/// Allocate or free, chosen nondeterministically
+fn step(allocator: &mut Allocator, live: &mut Vec<Allocation>) {
+  if live.is_empty() || kani::any() {
+    let size: Size = kani::any_where(|&size| size <= MAX_CAPACITY);
+    let shift: u32 = kani::any_where(|&shift| shift <= 3);
+    let result = allocator.alloc_with_align(size, 1 << shift);
+    match result {
+      Some(allocation) => {
+        assert_eq!(allocation.size(), size);
+        assert!(allocation.offset.is_multiple_of(1 << shift));
+        live.push(allocation);
+      },
+      // a free-region of at least `size + align - 1` always fits the aligned
+      // allocation, so one is found if it exists
+      None => assert!(
+        size == 0 || allocator.largest_available() < size + (1 << shift) - 1
+      ),
+    }
+  } else {
+    let index: usize = kani::any_where(|&index| index < live.len());
+    allocator.free(live.swap_remove(index));
+  }
+}
+
+/// Any sequence of allocations & frees keeps every invariant
+#[kani::proof]
+#[kani::unwind(17)]
+fn alloc_and_free() {
+  let capacity: Size =
+    kani::any_where(|&capacity| 0 < capacity && capacity <= MAX_CAPACITY);
+  let mut allocator = Allocator::new(capacity);
+  let mut live = Vec::new();
+  for _ in 0..STEPS {
+    step(&mut allocator, &mut live);
+    check_invariants(&allocator, &live);
+  }
+}
+
+/// Freeing everything, in any order, always coalesces back into a single
+/// free-region
+#[kani::proof]
+#[kani::unwind(17)]
+fn coalescing_is_total() {
+  let capacity: Size =
+    kani::any_where(|&capacity| 0 < capacity && capacity <= MAX_CAPACITY);
+  let mut allocator = Allocator::new(capacity);
+  let mut live = Vec::new();
+  for _ in 0..STEPS {
+    step(&mut allocator, &mut live);
+  }
+  while !live.is_empty() {
+    let index: usize = kani::any_where(|&index| index < live.len());
+    allocator.free(live.swap_remove(index));
+    check_invariants(&allocator, &live);
+  }
+
+  assert!(allocator.is_empty());
+  assert_eq!(allocator.location_map.len(), 1);
+  assert_eq!(allocator.largest_available(), capacity);
+}
+
+/// Deferred frees keep the accounting correct, and `coalesce_all` restores
+/// total coalescing
+#[kani::proof]
+#[kani::unwind(17)]
+fn deferred_coalescing() {
+  let capacity: Size =
+    kani::any_where(|&capacity| 0 < capacity && capacity <= MAX_CAPACITY);
+  let mut allocator = Allocator::new(capacity);
+  let mut live = Vec::new();
+  for _ in 0..STEPS - 1 {
+    step(&mut allocator, &mut live);
+  }
+  allocator.set_deferred_coalescing(true);
+  let mut pending = 0;
+  while !live.is_empty() {
+    let index: usize = kani::any_where(|&index| index < live.len());
+    let allocation = live.swap_remove(index);
+    pending += allocation.size();
+    allocator.free(allocation);
+  }
+  assert_eq!(allocator.available + pending, capacity);
+
+  allocator.coalesce_all();
+  check_invariants(&allocator, &live);
+  assert!(allocator.is_empty());
+}
@@ -0,0 +1,146 @@
+use {
+  crate::{Allocation, Allocator, ReallocateError, Size},
+  ::core::num::NonZero,
+  ::wasm_bindgen::prelude::{wasm_bindgen, JsError},
+};
+
+/// A JavaScript-friendly wrapper around [`Allocator`]
+///
+/// This is exported to JavaScript as `Allocator`. Numbers are passed in & out
+/// directly, failed allocations are reported as `undefined`, and other errors
+/// are thrown as `Error`s carrying the same message as the Rust error.
+#[wasm_bindgen(js_name = Allocator)]
+#[derive(Clone, Debug)]
+pub struct WasmAllocator {
+  allocator: Allocator,
+}
+
+/// A JavaScript-friendly mirror of [`Allocation`]
+///
+/// This is exported to JavaScript as `Allocation`.
+#[wasm_bindgen(js_name = Allocation)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct WasmAllocation {
+  /// The location of this allocation within the buffer
+  pub offset: Size,
+  /// The size of this allocation
+  pub size: Size,
+}
+
+impl From<Allocation> for WasmAllocation {
+  fn from(allocation: Allocation) -> Self {
+    WasmAllocation {
+      offset: allocation.offset,
+      size: allocation.size(),
+    }
+  }
+}
+
+impl TryFrom<WasmAllocation> for Allocation {
+  type Error = ReallocateError;
+
+  fn try_from(allocation: WasmAllocation) -> Result<Self, Self::Error> {
+    Ok(Allocation {
+      offset: allocation.offset,
+      size: NonZero::new(allocation.size).ok_or(ReallocateError::Invalid)?,
+    })
+  }
+}
+
+#[wasm_bindgen(js_class = Allocator)]
+impl WasmAllocator {
+  /// Create a new allocator to manage a pool of memory
+  ///
+  /// Throws if `capacity == 0`.
+  #[wasm_bindgen(constructor)]
+  pub fn new(capacity: Size) -> Result<WasmAllocator, JsError> {
+    if capacity == 0 {
+      return Err(JsError::new("capacity must not be 0"));
+    }
+
+    Ok(WasmAllocator {
+      allocator: Allocator::new(capacity),
+    })
+  }
+
+  /// Try to allocate a region with the provided size
+  ///
+  /// Returns `undefined` on failure. See [`Allocator::alloc`].
+  pub fn alloc(&mut self, size: Size) -> Option<WasmAllocation> {
+    self.allocator.alloc(size).map(Into::into)
+  }
+
+  /// Try to allocate a region with the provided size & alignment
+  ///
+  /// Returns `undefined` on failure. See [`Allocator::alloc_with_align`].
+  #[wasm_bindgen(js_name = allocWithAlign)]
+  pub fn alloc_with_align(
+    &mut self,
+    size: Size,
+    align: Size,
+  ) -> Option<WasmAllocation> {
+    self.allocator.alloc_with_align(size, align).map(Into::into)
+  }
+
+  /// Free the given allocation
+  ///
+  /// Throws if the allocation has a size of 0. See [`Allocator::free`].
+  pub fn free(&mut self, allocation: WasmAllocation) -> Result<(), JsError> {
+    self.allocator.free(allocation.try_into()?);
+    Ok(())
+  }
+
+  /// Try to re-size an existing allocation in-place
+  ///
+  /// Throws if there is not enough space. See [`Allocator::try_reallocate`].
+  #[wasm_bindgen(js_name = tryReallocate)]
+  pub fn try_reallocate(
+    &mut self,
+    allocation: WasmAllocation,
+    new_size: Size,
+  ) -> Result<WasmAllocation, JsError> {
+    Ok(
+      self
+        .allocator
+        .try_reallocate(allocation.try_into()?, new_size)?
+        .into(),
+    )
+  }
+
+  /// Add new free space at the end of the allocator
+  ///
+  /// Throws if the capacity would overflow. See [`Allocator::grow_capacity`].
+  #[wasm_bindgen(js_name = growCapacity)]
+  pub fn grow_capacity(&mut self, additional: Size) -> Result<(), JsError> {
+    Ok(self.allocator.grow_capacity(additional)?)
+  }
+
+  /// Free ***all*** allocations
+  pub fn reset(&mut self) {
+    self.allocator.reset()
+  }
+
+  /// Get the total capacity of the pool
+  #[wasm_bindgen(getter)]
+  pub fn capacity(&self) -> Size {
+    self.allocator.capacity()
+  }
+
+  /// Get the total available memory in this pool
+  #[wasm_bindgen(getter, js_name = totalAvailable)]
+  pub fn total_available(&self) -> Size {
+    self.allocator.total_available()
+  }
+
+  /// Get the size of the largest available memory region in this pool
+  #[wasm_bindgen(getter, js_name = largestAvailable)]
+  pub fn largest_available(&self) -> Size {
+    self.allocator.largest_available()
+  }
+
+  /// Returns true if there are no allocations
+  #[wasm_bindgen(getter, js_name = isEmpty)]
+  pub fn is_empty(&self) -> bool {
+    self.allocator.is_empty()
+  }
+}
@@ -0,0 +1,128 @@
+use {
+  crate::{Allocation, Allocator, Location, Size},
+  ::alloc::{vec, vec::Vec},
+  ::core::num::NonZero,
+};
+
+/// Coarse-grained counters of how many times each stripe of the pool has been
+/// allocated
+///
+/// The pool is divided into fixed-size stripes, and each allocation
+/// increments the counter of every stripe it touches. This can be used to
+/// monitor the endurance of flash-backed pools, or to inform wear-leveling
+/// placement decisions.
+///
+/// See [`Allocator::enable_wear_stats`].
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct WearStats {
+  stripe: NonZero<Size>,
+  capacity: NonZero<Size>,
+  counts: Vec<u64>,
+}
+
+impl WearStats {
+  /// Get the size of each stripe
+  pub fn stripe(&self) -> Size {
+    self.stripe.get()
+  }
+
+  /// Get the counter of every stripe, ordered by location
+  ///
+  /// The stripe at index `i` covers the locations from `i * stripe` up to
+  /// `(i + 1) * stripe`. The last stripe may be cut short by the end of the
+  /// pool.
+  pub fn counts(&self) -> &[u64] {
+    &self.counts
+  }
+
+  /// Get the counter of the stripe containing `offset`
+  ///
+  /// Returns `None` if `offset` is out of bounds.
+  pub fn count_at(&self, offset: Location) -> Option<u64> {
+    if offset >= self.capacity.get() {
+      return None;
+    }
+    Some(self.counts[self.index(offset)])
+  }
+
+  /// Get the highest counter of any stripe
+  pub fn max(&self) -> u64 {
+    self.counts.iter().copied().max().unwrap_or(0)
+  }
+
+  /// Set all counters back to zero
+  pub fn clear(&mut self) {
+    self.counts.fill(0);
+  }
+
+  /// Increment the counter of every stripe touched by `alloc`
+  fn record(&mut self, alloc: Allocation) {
+    let first = self.index(alloc.offset);
+    let last = self.index(alloc.offset + (alloc.size() - 1));
+    for count in &mut self.counts[first..=last] {
+      *count += 1;
+    }
+  }
+
+  /// Add stripes to cover a pool of `capacity`
+  fn resize(&mut self, capacity: NonZero<Size>) {
+    self.capacity = capacity;
+    let len = capacity.get().div_ceil(self.stripe.get()) as usize;
+    self.counts.resize(len, 0);
+  }
+
+  fn index(&self, offset: Location) -> usize {
+    (offset / self.stripe) as usize
+  }
+}
+
+impl Allocator {
+  /// Start counting how many times each `stripe`-sized stripe of the pool is
+  /// allocated
+  ///
+  /// Any existing counters are discarded. See [`WearStats`].
+  ///
+  /// Panics:
+  /// - Panics if `stripe == 0`
+  pub fn enable_wear_stats(&mut self, stripe: Size) {
+    let stripe = NonZero::new(stripe).expect("`stripe == 0`");
+    let mut wear = WearStats {
+      stripe,
+      capacity: self.capacity,
+      counts: vec![],
+    };
+    wear.resize(self.capacity);
+    self.wear = Some(wear);
+  }
+
+  /// Stop counting allocations per stripe, discarding the counters
+  pub fn disable_wear_stats(&mut self) {
+    self.wear = None;
+  }
+
+  /// Get the per-stripe allocation counters
+  ///
+  /// Returns `None` if they have not been enabled, see
+  /// [`enable_wear_stats`](Self::enable_wear_stats).
+  pub fn wear_stats(&self) -> Option<&WearStats> {
+    self.wear.as_ref()
+  }
+
+  /// Mutably get the per-stripe allocation counters, e.g. to
+  /// [`clear`](WearStats::clear) them
+  pub fn wear_stats_mut(&mut self) -> Option<&mut WearStats> {
+    self.wear.as_mut()
+  }
+
+  pub(crate) fn record_wear(&mut self, alloc: Allocation) {
+    if let Some(wear) = &mut self.wear {
+      wear.record(alloc);
+    }
+  }
+
+  pub(crate) fn resize_wear(&mut self) {
+    if let Some(wear) = &mut self.wear {
+      wear.resize(self.capacity);
+    }
+  }
+}
@@ -0,0 +1,148 @@
+use {
+  crate::{Allocation, Allocator, Size},
+  ::core::fmt,
+  ::wgpu::{
+    Buffer, BufferAddress, BufferBinding, BufferSize, BufferSlice,
+    BufferUsages, Limits, COPY_BUFFER_ALIGNMENT,
+  },
+};
+
+/// Sub-allocates regions of a single [`wgpu::Buffer`]
+///
+/// Enforces the alignment rules required to use the regions directly:
+/// - sizes are rounded up to a multiple of [`COPY_BUFFER_ALIGNMENT`], and
+/// - offsets are aligned to [`COPY_BUFFER_ALIGNMENT`], and to the device's
+///   uniform and/or storage buffer offset alignment if the buffer has the
+///   corresponding usage.
+pub struct BufferSuballocator {
+  buffer: Buffer,
+  allocator: Allocator,
+  alignment: Size,
+}
+
+/// A region of the buffer managed by a [`BufferSuballocator`]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct BufferAllocation {
+  allocation: Allocation,
+}
+
+impl BufferAllocation {
+  /// Get the offset of the region within the buffer, in bytes
+  pub fn offset(&self) -> BufferAddress {
+    self.allocation.offset as BufferAddress
+  }
+
+  /// Get the size of the region, in bytes
+  ///
+  /// This is the requested size, rounded up to a multiple of
+  /// [`COPY_BUFFER_ALIGNMENT`].
+  pub fn size(&self) -> BufferSize {
+    BufferSize::from(self.allocation.size)
+  }
+
+  /// Get the underlying [`Allocation`]
+  pub fn allocation(&self) -> Allocation {
+    self.allocation
+  }
+}
+
+impl BufferSuballocator {
+  /// Create a suballocator to manage the entirety of `buffer`
+  ///
+  /// `limits` should be the limits of the device the buffer was created with.
+  ///
+  /// Panics:
+  /// - Panics if the buffer's size is 0, or greater than `u32::MAX`
+  pub fn new(buffer: Buffer, limits: &Limits) -> Self {
+    let capacity = Size::try_from(buffer.size())
+      .expect("the buffer's size must not be greater than `u32::MAX`");
+    let alignment = Self::required_alignment(buffer.usage(), limits);
+
+    BufferSuballocator {
+      buffer,
+      allocator: Allocator::new(capacity),
+      alignment,
+    }
+  }
+
+  /// Get the offset alignment that is required for a buffer with the given
+  /// usage
+  pub fn required_alignment(usage: BufferUsages, limits: &Limits) -> Size {
+    let mut alignment = COPY_BUFFER_ALIGNMENT as Size;
+    if usage.contains(BufferUsages::UNIFORM) {
+      alignment = alignment.max(limits.min_uniform_buffer_offset_alignment);
+    }
+    if usage.contains(BufferUsages::STORAGE) {
+      alignment = alignment.max(limits.min_storage_buffer_offset_alignment);
+    }
+    alignment
+  }
+
+  /// Try to allocate a region of the buffer with at least `size` bytes
+  ///
+  /// Returns `None` if:
+  /// - there is not enough free space in the buffer, or
+  /// - `size == 0`.
+  pub fn alloc(&mut self, size: BufferAddress) -> Option<BufferAllocation> {
+    let size =
+      Size::try_from(size.next_multiple_of(COPY_BUFFER_ALIGNMENT)).ok()?;
+    let allocation = self.allocator.alloc_with_align(size, self.alignment)?;
+
+    Some(BufferAllocation { allocation })
+  }
+
+  /// Free the given region of the buffer
+  ///
+  /// See [`Allocator::free`].
+  pub fn free(&mut self, alloc: BufferAllocation) {
+    self.allocator.free(alloc.allocation)
+  }
+
+  /// Get a [`BufferSlice`] of the given region
+  pub fn slice(&self, alloc: &BufferAllocation) -> BufferSlice<'_> {
+    let start = alloc.offset();
+    self.buffer.slice(start..start + alloc.size().get())
+  }
+
+  /// Get a [`BufferBinding`] of the given region, for use in a bind group
+  pub fn binding(&self, alloc: &BufferAllocation) -> BufferBinding<'_> {
+    BufferBinding {
+      buffer: &self.buffer,
+      offset: alloc.offset(),
+      size: Some(alloc.size()),
+    }
+  }
+
+  /// Get the underlying buffer
+  pub fn buffer(&self) -> &Buffer {
+    &self.buffer
+  }
+
+  /// Get the offset alignment enforced for this buffer
+  pub fn alignment(&self) -> Size {
+    self.alignment
+  }
+
+  /// Get the underlying [`Allocator`]
+  ///
+  /// This can be used to query e.g. the available space in the buffer.
+  pub fn allocator(&self) -> &Allocator {
+    &self.allocator
+  }
+}
+
+impl fmt::Debug for BufferSuballocator {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("BufferSuballocator")
+      .field("buffer", &self.buffer)
+      .field("alignment", &self.alignment)
+      .field("allocator", &self.allocator)
+      .finish()
+  }
+}
+
+impl From<BufferAllocation> for Allocation {
+  fn from(alloc: BufferAllocation) -> Self {
+    alloc.allocation
+  }
+}
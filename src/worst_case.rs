@@ -0,0 +1,200 @@
+use crate::{
+  Allocator, FixedAllocator, LockedAllocator, Placement, ALIGNED_BINS,
+  EXACT_FIT_PROBES,
+};
+
+/// Upper bounds on the work done by one kind of operation
+///
+/// See [`WorstCase`]. The units match those of `OpCosts`, with the
+/// `op-costs` feature, so measured costs can be checked against these bounds.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub struct OpBound {
+  /// The most searches of the free-lists
+  pub lookups: usize,
+  /// The most free-regions examined, across all searches
+  pub regions_visited: usize,
+  /// The most free-regions inserted into or removed from the free-lists
+  pub tree_updates: usize,
+}
+
+/// An estimate of the most work any single operation can do, given the
+/// current configuration
+///
+/// This is intended for realtime users reasoning about latency bounds; it
+/// counts the steps which grow with the number of free-regions, rather than
+/// time. Threshold callbacks & event subscribers are not included.
+///
+/// See [`Allocator::worst_case`], [`LockedAllocator::worst_case`] &
+/// [`FixedAllocator::worst_case`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub struct WorstCase {
+  /// The bound for an allocation, where `size + align - 1` doesn't overflow
+  pub alloc: OpBound,
+  /// The bound for an allocation with a huge alignment, where
+  /// `size + align - 1` overflows
+  pub huge_align_alloc: OpBound,
+  /// The bound for a free
+  pub free: OpBound,
+  /// The most nodes visited by each lookup or update of a B-tree; `0` if the
+  /// free-regions are not kept in B-trees
+  pub tree_depth: usize,
+  /// The most entries shifted along by each update of a sorted table; `0` if
+  /// the free-regions are not kept in tables
+  pub table_shift: usize,
+  /// Whether operations may allocate or free heap memory
+  pub may_touch_heap: bool,
+}
+
+impl WorstCase {
+  /// The bounds for free-regions kept in tables sorted by location, with
+  /// room for `max_free_regions`
+  const fn table(max_free_regions: usize) -> WorstCase {
+    let alloc = OpBound {
+      lookups: 1,
+      regions_visited: max_free_regions,
+      tree_updates: 1,
+    };
+    WorstCase {
+      alloc,
+      huge_align_alloc: alloc,
+      free: OpBound {
+        lookups: 1,
+        // a binary search, then both neighbours
+        regions_visited: (usize::BITS - max_free_regions.leading_zeros())
+          as usize
+          + 2,
+        tree_updates: 1,
+      },
+      tree_depth: 0,
+      table_shift: max_free_regions.saturating_sub(1),
+      may_touch_heap: false,
+    }
+  }
+}
+
+impl Allocator {
+  /// Estimate the most work any single [`alloc_with_align`] or [`free`] can
+  /// do, if there are never more than `max_free_regions` free-regions
+  ///
+  /// Free-regions are always separated by allocated memory, so there can
+  /// never be more than half the capacity; larger values are clamped. The
+  /// bounds depend on the [placement](Self::set_placement), & whether
+  /// [exact fits are checked first](Self::set_exact_fit_first).
+  ///
+  /// Note: The B-trees may allocate or free nodes on any update; see
+  /// [`lock`](Self::lock) for a mode which never touches the heap. If
+  /// [coalescing is deferred](Self::set_deferred_coalescing), frees do no
+  /// searches, but [`coalesce_all`](Self::coalesce_all) does the work of a
+  /// free for each pending allocation.
+  ///
+  /// [`alloc_with_align`]: Self::alloc_with_align
+  /// [`free`]: Self::free
+  pub fn worst_case(&self, max_free_regions: usize) -> WorstCase {
+    let n = max_free_regions.min(self.capacity.get().div_ceil(2) as usize);
+    // the search of every free-region which can fit the allocation
+    let scan = OpBound {
+      lookups: 1,
+      regions_visited: n,
+      tree_updates: 0,
+    };
+    let (search, huge_align_search) = match self.placement {
+      Placement::BestFit => {
+        let probes = if self.exact_fit_first { 1 } else { 0 };
+        // the exact fit, the padded search, then each aligned bin
+        let lookups = probes + 1 + ALIGNED_BINS.len();
+        let search = OpBound {
+          lookups,
+          regions_visited: lookups - probes + probes * EXACT_FIT_PROBES,
+          tree_updates: 0,
+        };
+        (search, scan)
+      },
+      Placement::PreserveLargest => {
+        let search = OpBound {
+          lookups: 2,
+          regions_visited: n + 1,
+          tree_updates: 0,
+        };
+        (search, scan)
+      },
+      Placement::Hot | Placement::Cold => (scan, scan),
+      Placement::Random { .. } => {
+        // counting the candidates, then choosing one
+        let search = OpBound {
+          regions_visited: 2 * n,
+          ..scan
+        };
+        (search, search)
+      },
+    };
+    // removing the chosen free-region, then inserting the padding & leftover
+    let carve = |search: OpBound| OpBound {
+      tree_updates: 3,
+      ..search
+    };
+
+    WorstCase {
+      alloc: carve(search),
+      huge_align_alloc: carve(huge_align_search),
+      free: OpBound {
+        lookups: 2,
+        regions_visited: 2,
+        tree_updates: 3,
+      },
+      tree_depth: btree_depth(n),
+      table_shift: 0,
+      may_touch_heap: true,
+    }
+  }
+}
+
+impl LockedAllocator {
+  /// Estimate the most work any single [`alloc_with_align`] or [`free`] can
+  /// do
+  ///
+  /// See [`WorstCase`]. The bounds follow from
+  /// [`max_free_regions`](Self::max_free_regions).
+  ///
+  /// [`alloc_with_align`]: Self::alloc_with_align
+  /// [`free`]: Self::free
+  pub fn worst_case(&self) -> WorstCase {
+    WorstCase::table(self.max_free_regions())
+  }
+}
+
+impl<const N: usize> FixedAllocator<N> {
+  /// Estimate the most work any single [`alloc_with_align`] or [`free`] can
+  /// do
+  ///
+  /// See [`WorstCase`]. The bounds follow from `N`.
+  ///
+  /// [`alloc_with_align`]: Self::alloc_with_align
+  /// [`free`]: Self::free
+  pub const fn worst_case(&self) -> WorstCase {
+    WorstCase::table(N)
+  }
+}
+
+/// The most levels of a B-tree holding `len` entries
+///
+/// This follows the standard library's B-trees, in which every node but the
+/// root holds at least 5 entries, & so every internal node but the root has
+/// at least 6 children. A tree of depth `d` then holds at least
+/// `2 * 6^(d - 1) - 1` entries.
+fn btree_depth(len: usize) -> usize {
+  let mut depth = 0;
+  let mut min_len: usize = 1;
+  while min_len <= len {
+    depth += 1;
+    match min_len
+      .checked_add(1)
+      .and_then(|min_len| min_len.checked_mul(6))
+    {
+      Some(next) => min_len = next - 1,
+      None => break,
+    }
+  }
+  depth
+}
@@ -0,0 +1,63 @@
+use ::orderly_allocator::BitmapAllocator;
+
+#[test]
+fn alloc_and_free() {
+  let mut allocator = BitmapAllocator::new(1_000, 64);
+  assert_eq!(allocator.capacity(), 960);
+  assert_eq!(allocator.largest_available(), 960);
+
+  // sizes are rounded up to whole blocks, & placed first-fit
+  let a = allocator.alloc(100).unwrap();
+  assert_eq!((a.offset(), a.size()), (0, 128));
+  let b = allocator.alloc(64).unwrap();
+  assert_eq!(b.offset(), 128);
+  let c = allocator.alloc_with_align(64, 256).unwrap();
+  assert_eq!(c.offset(), 256);
+  // the gap before `c` is a multiple of 96
+  let d = allocator.alloc_with_align(64, 96).unwrap();
+  assert_eq!(d.offset(), 192);
+  assert_eq!(allocator.total_available(), 960 - 320);
+  assert_eq!(allocator.alloc(961), None);
+  assert_eq!(allocator.alloc(0), None);
+
+  allocator.free(b);
+  let regions: Vec<_> = allocator
+    .report_free_regions()
+    .map(|region| region.range())
+    .collect();
+  assert_eq!(regions, [128..192, 320..960]);
+  assert_eq!(allocator.largest_available(), 640);
+
+  for allocation in [a, c, d] {
+    allocator.free(allocation);
+  }
+  assert!(allocator.is_empty());
+  assert_eq!(allocator.report_free_regions().count(), 1);
+}
+
+#[test]
+fn spans_words() {
+  // 200 blocks, so the bitmap spans several words
+  let mut allocator = BitmapAllocator::new(200, 1);
+  let a = allocator.alloc(63).unwrap();
+  let b = allocator.alloc(70).unwrap();
+  let c = allocator.alloc(67).unwrap();
+  assert_eq!((a.offset(), b.offset(), c.offset()), (0, 63, 133));
+  assert_eq!(allocator.alloc(1), None);
+
+  allocator.free(b);
+  assert_eq!(allocator.largest_available(), 70);
+  let d = allocator.alloc_with_align(60, 64).unwrap();
+  assert_eq!(d.offset(), 64);
+  allocator.reset();
+  assert_eq!(allocator.alloc(200).unwrap().offset(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Double free")]
+fn double_free() {
+  let mut allocator = BitmapAllocator::new(1_024, 16);
+  let a = allocator.alloc(32).unwrap();
+  allocator.free(a);
+  allocator.free(a);
+}
@@ -0,0 +1,28 @@
+use ::orderly_allocator::BufferPool;
+
+#[test]
+fn buffer_pool() {
+  const CAPACITY: u32 = 1_024;
+  let mut pool = BufferPool::new(CAPACITY);
+
+  let (a, slice) = pool.alloc_slice(5).unwrap();
+  slice.copy_from_slice(b"hello");
+  let b = pool.alloc_with_align(8, 8).unwrap();
+  pool.get_mut(b).copy_from_slice(b"orderly!");
+
+  assert_eq!(pool.get(a), b"hello");
+  assert_eq!(pool.get(b), b"orderly!");
+  assert_eq!(pool.allocator().total_available(), CAPACITY - 13);
+
+  let [x, y] = pool.get_disjoint_mut([a, b]).unwrap();
+  x.swap_with_slice(&mut y[..5]);
+  assert_eq!(pool.get(a), b"order");
+  assert!(
+    pool.get_disjoint_mut([a, a]).is_none(),
+    "Refuses to hand out overlapping slices"
+  );
+
+  pool.free(a);
+  pool.free(b);
+  assert!(pool.allocator().is_empty());
+}
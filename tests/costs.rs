@@ -0,0 +1,54 @@
+#![cfg(feature = "op-costs")]
+
+use ::orderly_allocator::{Allocator, Placement};
+
+#[test]
+fn costs_per_operation() {
+  let mut allocator = Allocator::new(10_000);
+  let before = allocator.op_costs();
+  let a = allocator.alloc(100).unwrap();
+  let cost = allocator.op_costs() - before;
+  assert!(cost.lookups >= 1);
+  assert_eq!(cost.splits, 1);
+  // the whole pool is removed, & the leftover inserted
+  assert_eq!(cost.tree_updates, 2);
+
+  let before = allocator.op_costs();
+  allocator.free(a);
+  let cost = allocator.op_costs() - before;
+  assert_eq!(cost.splits, 0);
+  // `a` & the leftover are merged into one region
+  assert_eq!(cost.tree_updates, 2);
+
+  // queries which search the free-lists count too
+  let before = allocator.op_costs();
+  assert!(allocator.is_range_free(0, 10_000));
+  assert_eq!((allocator.op_costs() - before).lookups, 1);
+}
+
+#[test]
+fn scans_grow_with_fragmentation() {
+  let mut allocator = Allocator::new(100_000);
+  let allocations: Vec<_> =
+    (0..100).map(|_| allocator.alloc(100).unwrap()).collect();
+  for &allocation in allocations.iter().skip(1).step_by(2) {
+    allocator.free(allocation);
+  }
+  // fill the remainder, so that only the 100 byte holes are left
+  let rest = allocator.take_largest_free_region().unwrap();
+
+  // best-fit visits at most a single region..
+  let before = allocator.op_costs();
+  let a = allocator.alloc(100).unwrap();
+  assert!((allocator.op_costs() - before).regions_visited <= 1);
+
+  // ..but random placement counts every candidate
+  allocator.set_placement(Placement::Random { seed: 1 });
+  let before = allocator.op_costs();
+  let b = allocator.alloc(100).unwrap();
+  assert!((allocator.op_costs() - before).regions_visited >= 49);
+
+  allocator.free_many([a, b, rest]);
+  allocator.free_many(allocations.into_iter().step_by(2));
+  assert!(allocator.is_empty());
+}
@@ -0,0 +1,46 @@
+use ::orderly_allocator::{DualZoneAllocator, Zone};
+
+#[test]
+fn zones() {
+  let mut allocator = DualZoneAllocator::new(1_000, 500);
+  let persistent = allocator.alloc(100, 1, Zone::Persistent).unwrap();
+  assert_eq!(persistent.offset(), 0, "Persistent data grows up");
+  let transient = allocator.alloc(100, 1, Zone::Transient).unwrap();
+  assert_eq!(transient.offset(), 900, "Transient data grows down");
+  assert_eq!(allocator.zone(900), Some(Zone::Transient));
+  assert_eq!(allocator.zone(0), Some(Zone::Persistent));
+  assert_eq!(allocator.zone(1), None);
+  assert_eq!(allocator.boundary(), 500);
+
+  // persistent pressure pushes the boundary up
+  let large = allocator.alloc(600, 1, Zone::Persistent).unwrap();
+  assert_eq!(large.offset(), 100);
+  assert_eq!(allocator.boundary(), 700);
+  assert_eq!(allocator.used(Zone::Persistent), 700);
+
+  // & transient pressure can't cross persistent allocations
+  assert_eq!(allocator.alloc(300, 1, Zone::Transient), None);
+  let transient_2 = allocator.alloc(200, 1, Zone::Transient).unwrap();
+  assert_eq!(transient_2.offset(), 700);
+  assert_eq!(allocator.alloc(1, 1, Zone::Persistent), None);
+  assert_eq!(allocator.allocator().total_available(), 0);
+
+  allocator.free(large);
+  let transient_3 = allocator.alloc(300, 1, Zone::Transient).unwrap();
+  assert_eq!(transient_3.offset(), 400);
+  assert_eq!(allocator.boundary(), 400);
+
+  allocator.free_transient();
+  assert_eq!(allocator.used(Zone::Transient), 0);
+  allocator.free(persistent);
+  assert!(allocator.allocator().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "not a live allocation")]
+fn double_free() {
+  let mut allocator = DualZoneAllocator::new(1_000, 500);
+  let allocation = allocator.alloc(100, 1, Zone::Transient).unwrap();
+  allocator.free(allocation);
+  allocator.free(allocation);
+}
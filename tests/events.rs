@@ -0,0 +1,81 @@
+#![cfg(feature = "std")]
+
+use {
+  ::orderly_allocator::{Allocator, AllocatorEvent},
+  ::std::{sync::mpsc::channel, thread},
+};
+
+#[test]
+fn event_channel() {
+  let (sender, receiver) = channel();
+  let monitor = thread::spawn(move || receiver.iter().collect::<Vec<_>>());
+
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_event_sender(Some(sender));
+  let a = allocator.alloc(100).unwrap();
+  let b = allocator.try_reallocate(a, 50).unwrap();
+  assert_eq!(allocator.alloc(2_000), None);
+  allocator.free(b);
+  allocator.reset();
+  allocator.set_event_sender(None);
+  let c = allocator.alloc(100).unwrap();
+  allocator.free(c);
+
+  assert_eq!(
+    monitor.join().unwrap(),
+    [
+      AllocatorEvent::Alloc(a),
+      AllocatorEvent::Reallocate { old: a, new: b },
+      AllocatorEvent::Failed {
+        size: 2_000,
+        align: 1
+      },
+      AllocatorEvent::Free(b),
+      AllocatorEvent::Reset,
+    ]
+  );
+}
+
+#[test]
+fn alloc_near_events() {
+  let (sender, receiver) = channel();
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_event_sender(Some(sender));
+  let a = allocator.alloc_near(500, 100).unwrap();
+  assert_eq!(allocator.alloc_near(0, 2_000), None);
+  allocator.free(a);
+  allocator.set_event_sender(None);
+
+  assert_eq!(
+    receiver.iter().collect::<Vec<_>>(),
+    [
+      AllocatorEvent::Alloc(a),
+      AllocatorEvent::Failed {
+        size: 2_000,
+        align: 1
+      },
+      AllocatorEvent::Free(a),
+    ]
+  );
+  assert_eq!(allocator.stats().failed_allocs, 1);
+}
+
+#[test]
+fn take_largest_free_region_events() {
+  let (sender, receiver) = channel();
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_event_sender(Some(sender));
+  let a = allocator.take_largest_free_region().unwrap();
+  assert_eq!(allocator.take_largest_free_region(), None);
+  allocator.free(a);
+  allocator.set_event_sender(None);
+
+  assert_eq!(
+    receiver.iter().collect::<Vec<_>>(),
+    [
+      AllocatorEvent::Alloc(a),
+      AllocatorEvent::Failed { size: 0, align: 1 },
+      AllocatorEvent::Free(a),
+    ]
+  );
+}
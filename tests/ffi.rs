@@ -0,0 +1,33 @@
+#![cfg(feature = "ffi")]
+
+use ::orderly_allocator::ffi::*;
+
+#[test]
+fn alloc_realloc_and_free() {
+  assert!(orderly_allocator_create(0).is_null());
+
+  let allocator = orderly_allocator_create(1_000);
+  unsafe {
+    let a = orderly_allocator_alloc(allocator, 100, 16);
+    assert_eq!(a.size, 100);
+    let b = orderly_allocator_alloc(allocator, 100, 1);
+    assert_eq!(orderly_allocator_alloc(allocator, 2_000, 1).size, 0);
+
+    let a = orderly_allocator_realloc(allocator, a, 300, 16);
+    assert_eq!(a.size, 300);
+    assert_ne!(a.offset, 0, "Moved, since `b` was in the way");
+
+    orderly_allocator_free(allocator, a);
+    orderly_allocator_free(allocator, b);
+    assert_eq!(
+      orderly_allocator_stats(allocator),
+      OrderlyStats {
+        capacity: 1_000,
+        total_available: 1_000,
+        largest_available: 1_000,
+      }
+    );
+
+    orderly_allocator_destroy(allocator);
+  }
+}
@@ -0,0 +1,61 @@
+use ::orderly_allocator::{Allocator, DropCheck, FileHeaderError};
+
+#[test]
+fn round_trip() {
+  const HEADER_LEN: usize = 256;
+  let mut allocator = Allocator::new(10_000);
+  allocator.set_drop_check(DropCheck::Off);
+  // the header region itself is reserved at the start of the file
+  let _header = allocator.alloc(HEADER_LEN as u32).unwrap();
+  let a = allocator.alloc(1_000).unwrap();
+  let _b = allocator.alloc(1_000).unwrap();
+  allocator.free(a);
+
+  let mut header = [0xFF; HEADER_LEN];
+  let len = allocator.write_file_header(&mut header).unwrap();
+  assert_eq!(len, Allocator::file_header_len(2));
+  assert!(header[len..].iter().all(|&byte| byte == 0));
+
+  let mut loaded = Allocator::read_file_header(&header, 10_000).unwrap();
+  loaded.set_drop_check(DropCheck::Off);
+  assert_eq!(loaded, allocator);
+  assert_eq!(loaded.total_available(), allocator.total_available());
+  assert_eq!(loaded.state_hash(), allocator.state_hash());
+}
+
+#[test]
+fn validation() {
+  let mut allocator = Allocator::new(10_000);
+  allocator.set_drop_check(DropCheck::Off);
+  allocator.alloc(100).unwrap();
+  let mut header = [0; 64];
+  let len = allocator.write_file_header(&mut header).unwrap();
+  assert_eq!(
+    allocator.write_file_header(&mut header[..len - 1]),
+    Err(FileHeaderError::TooSmall { required: len })
+  );
+
+  assert_eq!(
+    Allocator::read_file_header(&header, 9_999).err(),
+    Some(FileHeaderError::Truncated {
+      capacity: 10_000,
+      file_len: 9_999
+    })
+  );
+  assert_eq!(
+    Allocator::read_file_header(&header[..len - 1], 10_000).err(),
+    Some(FileHeaderError::TooSmall { required: len })
+  );
+  assert_eq!(
+    Allocator::read_file_header(&[0; 64], 10_000).err(),
+    Some(FileHeaderError::Unrecognized)
+  );
+
+  // a torn write, or a bit flip
+  let mut damaged = header;
+  damaged[len - 1] ^= 0x10;
+  assert_eq!(
+    Allocator::read_file_header(&damaged, 10_000).err(),
+    Some(FileHeaderError::ChecksumMismatch)
+  );
+}
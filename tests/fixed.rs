@@ -0,0 +1,94 @@
+use ::orderly_allocator::{FixedAllocator, FixedFreeError};
+
+#[test]
+fn alloc_and_free() {
+  const CAPACITY: u32 = 10_000;
+  let mut allocator = FixedAllocator::<8>::new(CAPACITY);
+
+  let a = allocator.alloc(1_000).unwrap();
+  let b = allocator.alloc_with_align(1_000, 256).unwrap();
+  let c = allocator.alloc(1_000).unwrap();
+  assert_eq!(b.offset() % 256, 0, "Allocation align is as requested");
+  assert_eq!(allocator.total_available(), CAPACITY - 3_000);
+
+  allocator.free(b).unwrap();
+  assert_eq!(
+    allocator.free(b),
+    Err(FixedFreeError::Invalid),
+    "Detects double frees"
+  );
+  allocator.free(a).unwrap();
+  allocator.free(c).unwrap();
+  assert!(allocator.is_empty());
+  assert_eq!(
+    allocator.largest_available(),
+    CAPACITY,
+    "Coalesces neighbouring free regions when freeing"
+  );
+}
+
+#[test]
+fn best_fit() {
+  let mut allocator = FixedAllocator::<8>::new(10_000);
+
+  // [-free:2_000-][a][free:500][b][-------free-------]
+  let small = allocator.alloc(2_000).unwrap();
+  let _a = allocator.alloc(100).unwrap();
+  let smaller = allocator.alloc(500).unwrap();
+  let _b = allocator.alloc(100).unwrap();
+  allocator.free(small).unwrap();
+  allocator.free(smaller).unwrap();
+
+  assert_eq!(allocator.alloc(400).unwrap().offset(), smaller.offset());
+  assert_eq!(allocator.alloc(1_500).unwrap().offset(), small.offset());
+}
+
+#[test]
+fn best_fit_many_regions() {
+  let mut allocator = FixedAllocator::<16>::new(u32::MAX);
+
+  // [hole][sep][hole][sep]...[---------free---------]
+  let holes: Vec<_> = [900, 700, 500, 800, 600, 400, 1_000, 300, 650]
+    .into_iter()
+    .map(|size| {
+      let hole = allocator.alloc(size).unwrap();
+      allocator.alloc(10).unwrap();
+      hole
+    })
+    .collect();
+  for &hole in &holes {
+    allocator.free(hole).unwrap();
+  }
+
+  assert_eq!(allocator.alloc(350).unwrap().offset(), holes[5].offset());
+  assert_eq!(allocator.alloc(290).unwrap().offset(), holes[7].offset());
+  assert_eq!(allocator.alloc(640).unwrap().offset(), holes[8].offset());
+  // sizes above `i32::MAX` are compared as unsigned
+  let large = allocator.alloc(3_000_000_000).unwrap();
+  assert!(large.offset() > holes[8].offset());
+}
+
+#[test]
+fn table_full() {
+  let mut allocator = FixedAllocator::<2>::new(10_000);
+
+  // [a][b][c][--free--]
+  let a = allocator.alloc(1_000).unwrap();
+  let _b = allocator.alloc(1_000).unwrap();
+  let c = allocator.alloc(1_000).unwrap();
+
+  // [free][b][c][--free--]
+  allocator.free(a).unwrap();
+  assert_eq!(
+    allocator.free(c),
+    Ok(()),
+    "Freeing next to a free region doesn't need more room"
+  );
+
+  // [free][b][-free-][d][--free--]
+  assert!(
+    allocator.alloc_with_align(1_500, 4_096).is_none(),
+    "Refuses allocations which would need more free regions than fit"
+  );
+  assert_eq!(allocator.report_free_regions().count(), 2);
+}
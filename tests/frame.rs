@@ -0,0 +1,48 @@
+use ::orderly_allocator::FrameAllocator;
+
+#[test]
+fn linear_then_fallback() {
+  let mut allocator = FrameAllocator::new(1_000, 256);
+  assert_eq!(allocator.linear_capacity(), 256);
+  assert_eq!(allocator.allocator().total_available(), 1_000 - 256);
+
+  let a = allocator.alloc(100).unwrap();
+  let b = allocator.alloc_with_align(100, 128).unwrap();
+  assert_eq!((a.offset(), b.offset()), (0, 128));
+  assert_eq!(allocator.linear_used(), 228);
+  assert_eq!(allocator.overflowed(), 0);
+
+  let c = allocator.alloc(100).unwrap();
+  assert_eq!(c.offset(), 256, "Falls back to the general allocator");
+  assert_eq!(allocator.overflowed(), 100);
+  let d = allocator.alloc(28).unwrap();
+  assert_eq!(d.offset(), 228, "The linear region is still used first");
+  assert_eq!(allocator.alloc(1_000), None);
+
+  allocator.reset_frame();
+  assert_eq!(allocator.linear_used(), 0);
+  assert_eq!(allocator.overflowed(), 0);
+  assert_eq!(allocator.allocator().total_available(), 1_000 - 256);
+}
+
+#[test]
+fn long_lived_allocations() {
+  let mut allocator = FrameAllocator::new(1_000, 100);
+  let long_lived = allocator.allocator_mut().alloc(500).unwrap();
+  allocator.alloc(200).unwrap();
+  allocator.reset_frame();
+  assert_eq!(allocator.allocator().total_available(), 400);
+  allocator.allocator_mut().free(long_lived);
+  assert_eq!(allocator.allocator().total_available(), 900);
+}
+
+#[test]
+fn drop_returns_the_linear_region() {
+  use ::orderly_allocator::DropCheck;
+
+  let mut allocator = FrameAllocator::new(1_000, 100);
+  allocator.allocator_mut().set_drop_check(DropCheck::Panic);
+  allocator.alloc(80).unwrap();
+  allocator.alloc(80).unwrap();
+  drop(allocator);
+}
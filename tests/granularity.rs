@@ -0,0 +1,36 @@
+use ::orderly_allocator::{GranularityAllocator, ResourceKind};
+
+#[test]
+fn same_kind_is_packed() {
+  let mut allocator = GranularityAllocator::new(65_536, 1_024);
+
+  let a = allocator.alloc(100, 4, ResourceKind::Linear).unwrap();
+  let b = allocator.alloc(100, 4, ResourceKind::Linear).unwrap();
+  assert_eq!(b.offset(), a.offset() + a.size());
+  assert_eq!(b.size(), 100);
+  allocator.free(a);
+  allocator.free(b);
+}
+
+#[test]
+fn different_kinds_do_not_share_a_page() {
+  let mut allocator = GranularityAllocator::new(65_536, 1_024);
+
+  let linear = allocator.alloc(100, 4, ResourceKind::Linear).unwrap();
+  let image = allocator.alloc(100, 4, ResourceKind::NonLinear).unwrap();
+  assert_eq!(image.offset() % 1_024, 0);
+  assert_eq!(image.size(), 1_024, "Padded to whole pages");
+  assert_ne!(
+    linear.offset() / 1_024,
+    image.offset() / 1_024,
+    "Linear & non-linear resources are on separate pages"
+  );
+  assert_eq!(
+    allocator.kind(image.offset()),
+    Some(ResourceKind::NonLinear)
+  );
+
+  allocator.free(linear);
+  allocator.free(image);
+  assert!(allocator.allocator().is_empty());
+}
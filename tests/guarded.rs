@@ -0,0 +1,87 @@
+use ::orderly_allocator::GuardedAllocator;
+
+#[test]
+fn guard_bands_are_never_handed_out() {
+  const GUARD: u32 = 16;
+  let mut allocator = GuardedAllocator::new(10_000, GUARD);
+
+  let a = allocator.alloc(100).unwrap();
+  let b = allocator.alloc_with_align(100, 64).unwrap();
+  assert_eq!(b.offset() % 64, 0);
+
+  for allocation in [a, b] {
+    let [leading, trailing] = allocator.guard_bands(allocation).unwrap();
+    assert!(leading.len() >= GUARD as usize);
+    assert_eq!(leading.end, allocation.range().start);
+    assert_eq!(trailing.len(), GUARD as usize);
+    assert_eq!(trailing.start, allocation.range().end);
+  }
+  let [_, a_trailing] = allocator.guard_bands(a).unwrap();
+  let [b_leading, _] = allocator.guard_bands(b).unwrap();
+  assert!(
+    a_trailing.end <= b_leading.start,
+    "Guard bands don't overlap"
+  );
+
+  allocator.free(a);
+  allocator.free(b);
+  assert!(allocator.allocator().is_empty());
+}
+
+#[test]
+fn hooks_check_guard_bands_and_poison() {
+  use {
+    ::core::ops::Range,
+    ::orderly_allocator::Hooks,
+    ::std::sync::{
+      atomic::{AtomicBool, Ordering},
+      Arc, Mutex,
+    },
+  };
+
+  struct Poisoner {
+    buffer: Arc<Mutex<Vec<u8>>>,
+    intact: Arc<AtomicBool>,
+  }
+  impl Hooks for Poisoner {
+    fn on_free(&self, range: Range<usize>) {
+      self.buffer.lock().unwrap()[range].fill(0xDD);
+    }
+    fn check_guard_bands(
+      &self,
+      leading: Range<usize>,
+      trailing: Range<usize>,
+    ) {
+      let buffer = self.buffer.lock().unwrap();
+      let intact = buffer[leading].iter().all(|&b| b == 0xAA)
+        && buffer[trailing].iter().all(|&b| b == 0xAA);
+      self.intact.store(intact, Ordering::Relaxed);
+    }
+  }
+
+  let buffer = Arc::new(Mutex::new(vec![0_u8; 1_000]));
+  let intact = Arc::new(AtomicBool::new(true));
+  let mut allocator = GuardedAllocator::new(1_000, 8);
+  allocator.set_hooks(Poisoner {
+    buffer: Arc::clone(&buffer),
+    intact: Arc::clone(&intact),
+  });
+
+  let allocation = allocator.alloc(100).unwrap();
+  let [leading, trailing] = allocator.guard_bands(allocation).unwrap();
+  for band in [leading.clone(), trailing.clone()] {
+    buffer.lock().unwrap()[band].fill(0xAA);
+  }
+  // overrun the allocation by one byte
+  buffer.lock().unwrap()[allocation.range().end] = 0;
+
+  allocator.free(allocation);
+  assert!(!intact.load(Ordering::Relaxed), "The overrun is detected");
+  let buffer = buffer.lock().unwrap();
+  assert!(
+    buffer[leading.start..trailing.end]
+      .iter()
+      .all(|&b| b == 0xDD),
+    "The allocation & its guard bands are poisoned"
+  );
+}
@@ -0,0 +1,117 @@
+use ::core::num::NonZero;
+use ::orderly_allocator::{
+  Allocation, FixedFreeError, InBandError, InBandPool,
+};
+
+#[test]
+fn alloc_and_free() {
+  let mut memory = vec![0; 1_024];
+  let mut pool = InBandPool::format(&mut memory, 4).unwrap();
+  assert_eq!(pool.reserved(), 64);
+  assert_eq!(pool.total_available(), 1_024 - 64);
+
+  let a = pool.alloc(100).unwrap();
+  assert_eq!(a.offset(), 64, "Allocations start after the bookkeeping");
+  let b = pool.alloc_with_align(100, 256).unwrap();
+  assert_eq!(b.offset(), 256);
+  pool.get_mut(b).fill(0xAB);
+  assert_eq!(pool.report_free_regions().count(), 2);
+
+  assert_eq!(pool.free(b), Ok(()));
+  assert_eq!(
+    pool.free(b),
+    Err(FixedFreeError::Invalid),
+    "Detects double frees"
+  );
+  let header = Allocation {
+    offset: 0,
+    size: NonZero::new(64).unwrap(),
+  };
+  assert_eq!(
+    pool.free(header),
+    Err(FixedFreeError::Invalid),
+    "Can't free the bookkeeping"
+  );
+  assert_eq!(pool.free(a), Ok(()));
+  assert!(pool.is_empty());
+  assert_eq!(pool.largest_available(), 1_024 - 64);
+}
+
+#[test]
+fn persist_and_reopen() {
+  let mut memory = vec![0; 4_096];
+  let mut pool = InBandPool::format(&mut memory, 8).unwrap();
+  let a = pool.alloc(100).unwrap();
+  let b = pool.alloc(200).unwrap();
+  pool.get_mut(b).copy_from_slice(&[7; 200]);
+  pool.free(a).unwrap();
+
+  // the blob is position-independent, so a copy can be reopened elsewhere
+  let mut copy = pool.as_bytes().to_vec();
+  let mut reopened = InBandPool::open(&mut copy).unwrap();
+  assert_eq!(reopened.get(b), &[7; 200]);
+  assert_eq!(
+    reopened.report_free_regions().collect::<Vec<_>>(),
+    pool.report_free_regions().collect::<Vec<_>>()
+  );
+  reopened.free(b).unwrap();
+  assert!(reopened.is_empty());
+
+  copy[0] ^= 1;
+  assert_eq!(
+    InBandPool::open(&mut copy).err(),
+    Some(InBandError::Corrupt)
+  );
+  assert_eq!(
+    InBandPool::open(&mut memory[..2_048]).err(),
+    Some(InBandError::Corrupt)
+  );
+}
+
+#[test]
+fn seal() {
+  let mut memory = vec![0; 4_096];
+  let mut pool = InBandPool::format(&mut memory, 8).unwrap();
+  let a = pool.alloc(100).unwrap();
+  assert!(!pool.is_sealed());
+  pool.seal();
+  assert!(pool.is_sealed());
+
+  let mut copy = pool.as_bytes().to_vec();
+  let mut reopened = InBandPool::open(&mut copy).unwrap();
+  assert!(reopened.is_sealed());
+  reopened.free(a).unwrap();
+  assert!(!reopened.is_sealed(), "Modifying the pool unseals it");
+
+  // a bit flip which leaves the bookkeeping self-consistent
+  let mut damaged = pool.as_bytes().to_vec();
+  damaged[8] ^= 1;
+  assert_eq!(
+    InBandPool::open(&mut damaged).err(),
+    Some(InBandError::ChecksumMismatch)
+  );
+  // ...which would go unnoticed if the pool were not sealed
+  pool.free(a).unwrap();
+  let mut damaged = pool.as_bytes().to_vec();
+  damaged[8] ^= 1;
+  assert!(InBandPool::open(&mut damaged).is_ok());
+}
+
+#[test]
+fn table_full() {
+  let mut memory = vec![0; 1_024];
+  assert_eq!(
+    InBandPool::format(&mut memory, 0).err(),
+    Some(InBandError::TooSmall)
+  );
+  let mut pool = InBandPool::format(&mut memory, 2).unwrap();
+  let a = pool.alloc(100).unwrap();
+  let _b = pool.alloc(100).unwrap();
+  let c = pool.alloc(100).unwrap();
+  pool.free(a).unwrap();
+  assert_eq!(pool.free(c), Ok(()), "Coalescing doesn't need more room");
+  assert!(
+    pool.alloc_with_align(100, 512).is_none(),
+    "Refuses allocations which would need more free regions than fit"
+  );
+}
@@ -0,0 +1,27 @@
+#![cfg(feature = "offset-allocator")]
+
+use ::orderly_allocator::{Allocation, Allocator};
+
+#[test]
+fn from_offset_allocation() {
+  let mut other = ::offset_allocator::Allocator::<u32>::new(1_000);
+  let allocation = other.allocate(100).unwrap();
+
+  let converted =
+    Allocation::from_offset_allocation(allocation, &other).unwrap();
+  assert_eq!(converted.offset(), allocation.offset);
+  assert_eq!(converted.size(), other.allocation_size(allocation));
+
+  assert_eq!(Allocation::from_parts(converted.into_parts()), converted);
+}
+
+#[test]
+fn storage_report() {
+  let mut allocator = Allocator::with_max_allocs(1_000, 16);
+  let a = allocator.alloc(100).unwrap();
+
+  let report = allocator.storage_report();
+  assert_eq!(report.total_free_space, 900);
+  assert_eq!(report.largest_free_region, 900);
+  allocator.free(a);
+}
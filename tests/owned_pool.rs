@@ -0,0 +1,103 @@
+#![cfg_attr(feature = "unstable", feature(allocator_api))]
+
+use {::core::alloc::Layout, ::orderly_allocator::OwnedPool};
+
+#[test]
+fn allocate_and_deallocate() {
+  const CAPACITY: u32 = 4_096;
+  let pool = OwnedPool::new(CAPACITY, 64);
+
+  let layout = Layout::from_size_align(100, 16).unwrap();
+  let block = pool.allocate(layout).unwrap();
+  assert_eq!(block.len(), 100, "Block size is as requested");
+  assert_eq!(
+    block.cast::<u8>().addr().get() % 16,
+    0,
+    "Block align is as requested"
+  );
+  assert_eq!(pool.allocator().total_available(), CAPACITY - 100);
+
+  assert!(
+    pool
+      .allocate(Layout::from_size_align(8, 128).unwrap())
+      .is_none(),
+    "Fails when the alignment is greater than the buffer's alignment"
+  );
+
+  let empty = pool.allocate(Layout::new::<()>()).unwrap();
+  assert_eq!(empty.len(), 0);
+  assert_eq!(
+    pool.allocator().total_available(),
+    CAPACITY - 100,
+    "Zero-sized blocks don't consume any space"
+  );
+
+  unsafe {
+    pool.deallocate(block.cast(), layout);
+    pool.deallocate(empty.cast(), Layout::new::<()>());
+  }
+  assert!(pool.allocator().is_empty());
+}
+
+#[test]
+fn reallocate_in_place() {
+  let pool = OwnedPool::new(4_096, 8);
+
+  let old_layout = Layout::from_size_align(64, 8).unwrap();
+  let new_layout = Layout::from_size_align(256, 8).unwrap();
+  let block = pool.allocate(old_layout).unwrap();
+  unsafe { block.cast::<u8>().write_bytes(0xAB, 64) };
+
+  let grown =
+    unsafe { pool.reallocate(block.cast(), old_layout, new_layout) }.unwrap();
+  assert_eq!(grown.cast::<u8>(), block.cast::<u8>(), "Grows in-place");
+  assert_eq!(grown.len(), 256);
+  assert_eq!(unsafe { grown.as_ref() }[..64], [0xAB; 64]);
+
+  unsafe { pool.deallocate(grown.cast(), new_layout) };
+  assert!(pool.allocator().is_empty());
+}
+
+#[cfg(feature = "allocator-api2")]
+#[test]
+fn collections() {
+  use ::allocator_api2::{boxed::Box, vec::Vec};
+
+  let pool = OwnedPool::new(4_096, 64);
+  {
+    let mut vec = Vec::new_in(&pool);
+    vec.extend(0u32..100);
+    let boxed = Box::new_in(0xCAFE_u64, &pool);
+
+    assert_eq!(vec.iter().sum::<u32>(), 4_950);
+    assert_eq!(*boxed, 0xCAFE);
+    assert_eq!(
+      pool.allocator().total_available(),
+      4_096 - vec.capacity() as u32 * 4 - 8,
+      "Collections consume space from the pool"
+    );
+  }
+  assert!(
+    pool.allocator().is_empty(),
+    "Dropping collections returns their space to the pool"
+  );
+}
+
+#[cfg(feature = "unstable")]
+#[test]
+fn core_collections() {
+  let pool = OwnedPool::new(4_096, 64);
+  {
+    let mut vec = Vec::with_capacity_in(16, &pool);
+    vec.extend(0u32..100);
+    let boxed = Box::new_in(0xCAFE_u64, &pool);
+
+    assert_eq!(vec.iter().sum::<u32>(), 4_950);
+    assert_eq!(*boxed, 0xCAFE);
+    assert!(!pool.allocator().is_empty());
+  }
+  assert!(
+    pool.allocator().is_empty(),
+    "Dropping collections returns their space to the pool"
+  );
+}
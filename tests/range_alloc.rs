@@ -0,0 +1,27 @@
+use ::orderly_allocator::{RangeAllocationError, RangeAllocator};
+
+#[test]
+fn allocate_and_free_ranges() {
+  let mut allocator = RangeAllocator::new(100..1_100);
+
+  let a = allocator.allocate_range(400).unwrap();
+  let b = allocator.allocate_range(400).unwrap();
+  assert!(a.start >= 100 && a.end <= 1_100);
+  assert!(a.end <= b.start || b.end <= a.start, "Ranges don't overlap");
+  assert_eq!(
+    allocator.allocate_range(400),
+    Err(RangeAllocationError {
+      fragmented_free_length: 200
+    })
+  );
+
+  allocator.grow_to(1_300);
+  let c = allocator.allocate_range(400).unwrap();
+  assert_eq!(c.end - c.start, 400);
+
+  allocator.free_range(a);
+  allocator.free_range(b);
+  allocator.free_range(c);
+  assert!(allocator.is_empty());
+  assert_eq!(allocator.total_available(), 1_200);
+}
@@ -0,0 +1,72 @@
+use ::orderly_allocator::{Allocator, DropCheck, RealtimeError};
+
+#[test]
+fn lock_and_unlock() {
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_drop_check(DropCheck::Off);
+  let a = allocator.alloc(100).unwrap();
+  let _b = allocator.alloc(100).unwrap();
+  allocator.free(a);
+
+  let mut locked = allocator.lock(4);
+  assert_eq!(locked.free_regions(), 2);
+  assert!(locked.max_free_regions() >= 4);
+  assert_eq!(locked.total_available(), 900);
+  assert_eq!(locked.largest_available(), 800);
+
+  let c = locked.alloc(50).unwrap();
+  assert_eq!(c.offset(), 0, "Uses a best-fit strategy");
+  let d = locked.alloc_with_align(10, 64).unwrap();
+  assert_eq!(d.offset(), 64);
+  assert_eq!(locked.total_available(), 840);
+
+  assert_eq!(locked.free(c), Ok(()));
+  assert_eq!(locked.free(c), Err(RealtimeError::Invalid));
+  assert_eq!(locked.free(d), Ok(()));
+  assert_eq!(locked.free_regions(), 2);
+  assert_eq!(locked.alloc(0), Err(RealtimeError::Invalid));
+  assert_eq!(locked.alloc(1_000), Err(RealtimeError::InsufficientSpace));
+
+  let e = locked.alloc(20).unwrap();
+  let mut allocator = locked.unlock();
+  assert_eq!(allocator.total_available(), 880);
+  assert_eq!(allocator.report_free_regions().count(), 2);
+  allocator.free(e);
+  assert_eq!(allocator.largest_available(), 800);
+}
+
+#[test]
+fn would_allocate() {
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_drop_check(DropCheck::Off);
+  let mut locked = allocator.lock(2);
+  let max = locked.max_free_regions();
+
+  // each aligned allocation splits off another free-region
+  let mut allocations = Vec::new();
+  while locked.free_regions() < max {
+    allocations.push(locked.alloc_with_align(1, 100).unwrap());
+    allocations.push(locked.alloc(1).unwrap());
+  }
+  assert_eq!(
+    locked.alloc_with_align(1, 100),
+    Err(RealtimeError::WouldAllocate)
+  );
+  assert!(
+    locked.alloc(1).is_ok(),
+    "Allocations which don't split a free-region still succeed"
+  );
+
+  let available = locked.total_available();
+  let isolated = allocations[1];
+  assert_eq!(
+    locked.free(isolated),
+    Err(RealtimeError::WouldAllocate),
+    "Frees which can't be coalesced need another free-region"
+  );
+  assert_eq!(locked.total_available(), available, "Nothing was freed");
+
+  let mut allocator = locked.unlock();
+  allocator.free(isolated);
+  assert_eq!(allocator.total_available(), available + 1);
+}
@@ -0,0 +1,57 @@
+use ::orderly_allocator::SegmentedAllocator;
+
+#[test]
+fn allocations_stay_within_segments() {
+  let mut allocator = SegmentedAllocator::new(10_000, 1_024);
+  assert_eq!(allocator.segment_count(), 10);
+  assert_eq!(allocator.capacity(), 10_000);
+  assert_eq!(allocator.largest_available(), 1_024);
+
+  // the smallest segment (the last one, with 784 bytes) is tried first
+  let a = allocator.alloc(700).unwrap();
+  assert_eq!(a.offset(), 9 * 1_024);
+  let b = allocator.alloc(700).unwrap();
+  assert_eq!(b.offset(), 0);
+  let c = allocator.alloc(400).unwrap();
+  assert_eq!(c.offset(), 1_024);
+  assert_eq!(allocator.alloc(1_025), None);
+
+  let d = allocator.alloc_with_align(256, 256).unwrap();
+  assert_eq!(d.offset() % 256, 0);
+  let e = allocator.alloc_with_align(1_024, 2_048).unwrap();
+  assert_eq!(e.offset() % 2_048, 0);
+  assert_eq!(
+    allocator.total_available(),
+    10_000 - 700 - 700 - 400 - 1_280
+  );
+
+  for allocation in [a, b, c, d, e] {
+    allocator.free(allocation);
+  }
+  assert!(allocator.is_empty());
+  assert_eq!(allocator.largest_available(), 1_024);
+}
+
+#[test]
+fn report_free_regions_in_range() {
+  let mut allocator = SegmentedAllocator::new(4_096, 1_024);
+  let a = allocator.alloc(1_000).unwrap();
+  let b = allocator.alloc(1_000).unwrap();
+  assert_eq!((a.offset(), b.offset()), (0, 1_024));
+
+  let regions: Vec<_> = allocator
+    .report_free_regions(0..2_048)
+    .map(|region| region.range())
+    .collect();
+  assert_eq!(regions, [1_000..1_024, 2_024..2_048]);
+  assert_eq!(allocator.report_free_regions(1_010..1_020).count(), 1);
+  assert_eq!(allocator.report_free_regions(0..4_096).count(), 4);
+
+  let (base, segment) = allocator.segment(1_500).unwrap();
+  assert_eq!(base, 1_024);
+  assert_eq!(segment.total_available(), 24);
+  assert!(allocator.segment(4_096).is_none());
+
+  allocator.reset();
+  assert!(allocator.is_empty());
+}
@@ -0,0 +1,77 @@
+use {
+  ::core::{num::NonZero, ptr::NonNull},
+  ::orderly_allocator::{FixedFreeError, InBandError, SharedPool},
+  ::std::thread,
+};
+
+/// Leak a zeroed, 4-byte aligned mapping of `len` bytes
+fn mapping(len: usize) -> NonNull<u8> {
+  let words = vec![0u32; len / 4].leak();
+  NonNull::from(words).cast()
+}
+
+fn owner(id: u32) -> NonZero<u32> {
+  NonZero::new(id).unwrap()
+}
+
+#[test]
+fn processes_share_a_pool() {
+  const LEN: usize = 64 << 10;
+  let memory = mapping(LEN);
+  // SAFETY: the mapping is valid & aligned, & only used through these pools
+  let producer =
+    unsafe { SharedPool::format(memory, LEN, 64, owner(1)) }.unwrap();
+  let consumer = unsafe { SharedPool::open(memory, LEN, owner(2)) }.unwrap();
+  let available = producer.total_available();
+  assert_eq!(consumer.total_available(), available);
+
+  let a = producer.alloc(100).unwrap();
+  assert!(a.offset() >= 16, "Allocations are after the bookkeeping");
+  assert_eq!(consumer.sequence(), 1);
+  consumer.free(a).unwrap();
+  assert_eq!(producer.sequence(), 2);
+  assert_eq!(producer.free(a), Err(FixedFreeError::Invalid));
+
+  thread::scope(|scope| {
+    for pool in [&producer, &consumer] {
+      scope.spawn(move || {
+        for _ in 0..1_000 {
+          let allocation = pool.alloc_with_align(48, 16).unwrap();
+          pool.free(allocation).unwrap();
+        }
+      });
+    }
+  });
+  assert_eq!(producer.sequence(), 4_002);
+  assert_eq!(consumer.total_available(), available);
+  assert_eq!(consumer.lock_owner(), None);
+}
+
+#[test]
+fn recover_from_a_dead_owner() {
+  const LEN: usize = 4_096;
+  let memory = mapping(LEN);
+  // SAFETY: the mapping is valid & aligned, & only used through these pools
+  let pool = unsafe { SharedPool::format(memory, LEN, 8, owner(1)) }.unwrap();
+  let a = pool.alloc(100).unwrap();
+
+  // a process with owner 7 dies while holding the lock
+  // SAFETY: the lock word is the first word of the mapping
+  unsafe { memory.cast::<u32>().write(7) };
+  assert_eq!(pool.lock_owner(), Some(owner(7)));
+  // SAFETY: owner 7 is not really running
+  assert_eq!(unsafe { pool.recover(owner(3)) }, Ok(false));
+  assert_eq!(unsafe { pool.recover(owner(7)) }, Ok(true));
+  assert_eq!(pool.lock_owner(), None);
+  pool.free(a).unwrap();
+
+  // ...& this time leaves the pool half-modified
+  // SAFETY: the lock word is the first word of the mapping, & the pool's
+  // magic number starts after the 16-byte shared header
+  unsafe {
+    memory.cast::<u32>().write(7);
+    memory.add(16).write(0);
+  }
+  assert_eq!(unsafe { pool.recover(owner(7)) }, Err(InBandError::Corrupt));
+  assert_eq!(pool.alloc(100), None);
+}
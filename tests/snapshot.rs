@@ -0,0 +1,60 @@
+use ::orderly_allocator::{Allocator, DropCheck, SnapshotAllocator};
+
+#[test]
+fn speculative_branches() {
+  let mut allocator = SnapshotAllocator::new(1_000);
+  allocator.allocator_mut().set_drop_check(DropCheck::Off);
+  let base = allocator.alloc(100).unwrap();
+
+  let mut plan_a = allocator.snapshot();
+  let mut plan_b = allocator.snapshot();
+  assert!(allocator.is_shared());
+
+  plan_a.alloc(800).unwrap();
+  assert!(!plan_a.is_shared());
+  plan_b.alloc(100).unwrap();
+  plan_b.alloc(100).unwrap();
+  assert_eq!(allocator.allocator().total_available(), 900);
+  assert_eq!(plan_a.allocator().total_available(), 100);
+  assert_eq!(plan_b.allocator().total_available(), 700);
+
+  // failed allocations don't copy the state
+  let snapshot = allocator.snapshot();
+  assert_eq!(allocator.alloc(1_000), None);
+  assert!(allocator.is_shared());
+  drop(snapshot);
+
+  // commit plan b
+  allocator = plan_b;
+  allocator.free(base);
+  let committed: Allocator = allocator.into_allocator();
+  assert_eq!(committed.total_available(), 800);
+  drop(plan_a);
+}
+
+#[test]
+fn snapshots_are_detached() {
+  use ::std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+  };
+
+  let frees = Arc::new(AtomicUsize::new(0));
+  let mut allocator = Allocator::new(1_000);
+  let counter = Arc::clone(&frees);
+  allocator.set_hooks(move |_| {
+    counter.fetch_add(1, Ordering::Relaxed);
+  });
+  let mut allocator = SnapshotAllocator::from(allocator);
+  let a = allocator.alloc(100).unwrap();
+
+  let mut branch = allocator.snapshot();
+  branch.free(a);
+  assert_eq!(frees.load(Ordering::Relaxed), 0, "Branches are detached");
+  let mut copy = allocator.snapshot().into_allocator();
+  copy.free(a);
+  assert_eq!(frees.load(Ordering::Relaxed), 0);
+
+  allocator.free(a);
+  assert_eq!(frees.load(Ordering::Relaxed), 1);
+}
@@ -0,0 +1,43 @@
+use ::orderly_allocator::StackAllocator;
+
+#[test]
+fn nested_frames() {
+  let mut stack = StackAllocator::new(1_000);
+  let outer = stack.mark();
+  let a = stack.alloc(100).unwrap();
+  assert_eq!(a.offset(), 0);
+
+  let inner = stack.mark();
+  let b = stack.alloc_with_align(10, 256).unwrap();
+  assert_eq!(b.offset(), 256);
+  assert_eq!(stack.used(), 266);
+  assert_eq!(stack.alloc(800), None);
+  assert_eq!(stack.alloc(0), None);
+  assert_eq!(stack.alloc_with_align(1, 0), None);
+
+  stack.free_to_mark(inner);
+  assert_eq!(stack.used(), 100, "The padding is reclaimed too");
+  assert_eq!(stack.alloc(800).unwrap().offset(), 100);
+  assert_eq!(stack.total_available(), 100);
+
+  stack.free_to_mark(outer);
+  assert!(stack.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "rolled back below")]
+fn stale_marker() {
+  let mut stack = StackAllocator::new(1_000);
+  stack.alloc(100).unwrap();
+  let marker = stack.mark();
+  stack.reset();
+  stack.free_to_mark(marker);
+}
+
+#[test]
+fn const_construction() {
+  static STACK: StackAllocator = StackAllocator::new(64);
+  let mut stack = STACK;
+  assert_eq!(stack.alloc(64).unwrap().size(), 64);
+  assert_eq!(stack.alloc(1), None);
+}
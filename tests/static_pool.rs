@@ -0,0 +1,29 @@
+#![cfg(feature = "global-alloc")]
+
+use {
+  ::core::alloc::{GlobalAlloc, Layout},
+  ::orderly_allocator::StaticPool,
+};
+
+static POOL: StaticPool<4_096, 16> = StaticPool::new();
+
+#[test]
+fn global_alloc() {
+  let layout = Layout::from_size_align(100, 32).unwrap();
+  let a = unsafe { POOL.alloc(layout) };
+  let b = unsafe { POOL.alloc(layout) };
+  assert!(!a.is_null() && !b.is_null());
+  assert_eq!(a.addr() % 32, 0, "Allocation align is as requested");
+  assert_eq!(POOL.total_available(), 4_096 - 200);
+
+  let too_large = Layout::from_size_align(8_192, 8).unwrap();
+  assert!(unsafe { POOL.alloc(too_large) }.is_null());
+  let too_aligned = Layout::from_size_align(8, 128).unwrap();
+  assert!(unsafe { POOL.alloc(too_aligned) }.is_null());
+
+  unsafe {
+    POOL.dealloc(a, layout);
+    POOL.dealloc(b, layout);
+  }
+  assert_eq!(POOL.total_available(), 4_096);
+}
@@ -0,0 +1,35 @@
+use ::orderly_allocator::{
+  Allocator, BitmapAllocator, FixedAllocator, GuardedAllocator,
+  LockedAllocator, SegmentedAllocator, SnapshotAllocator, Suballocator,
+};
+
+fn exercise<A: Suballocator>() {
+  let mut allocator = A::with_capacity(1_000);
+
+  let a = allocator.allocate(400, 8).unwrap();
+  assert_eq!(a.offset() % 8, 0);
+  assert_eq!(a.size(), 400);
+  assert!(allocator.allocate(1_000, 1).is_none());
+
+  allocator.grow(1_000).unwrap();
+  let b = allocator.allocate(600, 1).unwrap();
+  assert!(
+    a.range().end <= b.range().start || b.range().end <= a.range().start
+  );
+
+  allocator.deallocate(a);
+  allocator.deallocate(b);
+  let c = allocator.allocate(1_800, 1).expect("Fully coalesced");
+  allocator.deallocate(c);
+}
+
+#[test]
+fn generic_over_strategy() {
+  exercise::<Allocator>();
+  exercise::<FixedAllocator<8>>();
+  exercise::<BitmapAllocator>();
+  exercise::<SegmentedAllocator>();
+  exercise::<GuardedAllocator>();
+  exercise::<SnapshotAllocator>();
+  exercise::<LockedAllocator>();
+}
@@ -0,0 +1,78 @@
+#![cfg(target_has_atomic = "ptr")]
+
+use {
+  ::orderly_allocator::{Allocator, ThresholdCrossing},
+  ::std::sync::{Arc, Mutex},
+};
+
+#[test]
+fn threshold_crossings() {
+  let crossings = Arc::new(Mutex::new(Vec::new()));
+  let mut allocator = Allocator::new(1_000);
+  allocator.on_threshold_crossed(&[90, 75], {
+    let crossings = crossings.clone();
+    move |crossing| crossings.lock().unwrap().push(crossing)
+  });
+  let take = || ::core::mem::take(&mut *crossings.lock().unwrap());
+
+  let a = allocator.alloc(700).unwrap();
+  assert_eq!(take(), []);
+  let b = allocator.alloc(50).unwrap();
+  assert_eq!(take(), [ThresholdCrossing::Rising(75)]);
+  let c = allocator.alloc(200).unwrap();
+  assert_eq!(take(), [ThresholdCrossing::Rising(90)]);
+
+  allocator.free(a);
+  assert_eq!(
+    take(),
+    [
+      ThresholdCrossing::Falling(90),
+      ThresholdCrossing::Falling(75)
+    ]
+  );
+  let a = allocator.alloc(700).unwrap();
+  assert_eq!(
+    take(),
+    [ThresholdCrossing::Rising(75), ThresholdCrossing::Rising(90)]
+  );
+
+  // growing the pool lowers the usage percentage
+  allocator.grow_capacity(1_000).unwrap();
+  assert_eq!(
+    take(),
+    [
+      ThresholdCrossing::Falling(90),
+      ThresholdCrossing::Falling(75)
+    ]
+  );
+
+  allocator.clear_thresholds();
+  allocator.free(a);
+  allocator.free(b);
+  allocator.free(c);
+  assert_eq!(take(), []);
+}
+
+#[test]
+fn coloring_jitter() {
+  use ::orderly_allocator::Coloring;
+
+  let crossings = Arc::new(Mutex::new(Vec::new()));
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_coloring(Some(Coloring {
+    stride: 64,
+    colors: 4,
+  }));
+  allocator.on_threshold_crossed(&[65], {
+    let crossings = crossings.clone();
+    move |crossing| crossings.lock().unwrap().push(crossing)
+  });
+
+  let a = allocator.alloc(300).unwrap();
+  // the jitter never counts towards the usage
+  let b = allocator.alloc(300).unwrap();
+  assert_eq!(b.offset(), 576);
+  assert_eq!(*crossings.lock().unwrap(), []);
+  allocator.free(a);
+  allocator.free(b);
+}
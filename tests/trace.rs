@@ -0,0 +1,55 @@
+use ::orderly_allocator::{Allocator, ParseTraceError, Trace, TraceOp};
+
+#[test]
+fn record_and_replay() {
+  let mut trace = Trace::new();
+  let a = trace.alloc(100, 1);
+  let _b = trace.alloc(100, 1);
+  let c = trace.alloc(100, 1);
+  trace.free(a);
+  trace.free(c);
+  trace.alloc(500, 1);
+
+  let samples = trace.replay(Allocator::new(700));
+  assert_eq!(samples.len(), 6);
+  assert_eq!(samples[2].allocated, 300);
+  assert_eq!(samples[2].occupancy(), 300. / 700.);
+  assert_eq!(samples[3].free_regions, 2);
+  assert_eq!(samples[3].fragmentation(), 1. - 400. / 500.);
+  assert_eq!(samples[4].largest_available, 500);
+  assert!(!samples[4].failed);
+  assert_eq!(samples[5].fragmentation(), 0.);
+
+  // the same trace fails against a smaller pool
+  let samples = trace.replay(Allocator::new(500));
+  assert!(samples[5].failed);
+}
+
+#[test]
+fn parse() {
+  let trace = Trace::new();
+  assert_eq!("".parse(), Ok(trace));
+
+  let text = "# a comment\nalloc 100 4\n\nfree 0\n";
+  let trace: Trace = text.parse().unwrap();
+  assert_eq!(
+    trace.ops(),
+    [
+      TraceOp::Alloc {
+        size: 100,
+        align: 4
+      },
+      TraceOp::Free { index: 0 },
+    ]
+  );
+  assert_eq!(trace.to_string().parse(), Ok(trace));
+
+  assert_eq!(
+    "alloc 100 1\nfree 1".parse::<Trace>(),
+    Err(ParseTraceError { line: 2 })
+  );
+  assert_eq!(
+    "alloc 100".parse::<Trace>(),
+    Err(ParseTraceError { line: 1 })
+  );
+}
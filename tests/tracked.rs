@@ -0,0 +1,292 @@
+use ::orderly_allocator::{
+  AllocationId, Allocator, MigrationError, TrackedAllocator,
+};
+
+#[test]
+fn user_data() {
+  let mut allocator = TrackedAllocator::new(1_000);
+  let a = allocator.alloc(100, "a").unwrap();
+  let b = allocator.alloc_with_align(100, 64, "b").unwrap();
+  assert_eq!(allocator.len(), 2);
+  assert_eq!(allocator.get(a), Some(&"a"));
+
+  *allocator.get_mut(b).unwrap() = "bee";
+  let live: Vec<_> = allocator.iter().collect();
+  assert_eq!(live, [(a, &"a"), (b, &"bee")]);
+
+  assert_eq!(allocator.free(a), "a");
+  assert_eq!(allocator.get(a), None);
+  assert_eq!(allocator.allocator().total_available(), 900);
+}
+
+#[test]
+fn retain() {
+  let mut allocator = TrackedAllocator::new(1_000);
+  for age in 0..10 {
+    allocator.alloc(100, age).unwrap();
+  }
+  allocator.retain(|_, age| {
+    *age += 1;
+    *age > 5
+  });
+
+  assert_eq!(allocator.len(), 5);
+  assert!(allocator.iter().all(|(_, &age)| age > 5));
+  assert_eq!(allocator.allocator().total_available(), 500);
+}
+
+#[test]
+#[should_panic]
+fn free_not_live() {
+  let mut allocator = TrackedAllocator::new(1_000);
+  let a = allocator.alloc(100, ()).unwrap();
+  allocator.free(a);
+  allocator.free(a);
+}
+
+#[test]
+fn dump() {
+  let mut allocator = TrackedAllocator::new(1_000);
+  let a = allocator.alloc(100, ()).unwrap();
+  allocator.alloc(50, ()).unwrap();
+  #[cfg(feature = "debug-names")]
+  {
+    allocator.set_name(a, "vertices");
+    assert_eq!(allocator.name(a), Some("vertices"));
+  }
+
+  let expected = if cfg!(feature = "debug-names") {
+    "0..100 (100) vertices\n100..150 (50)\n"
+  } else {
+    "0..100 (100)\n100..150 (50)\n"
+  };
+  assert_eq!(allocator.dump().to_string(), expected);
+  allocator.free(a);
+}
+
+#[test]
+fn leak_report() {
+  use ::std::sync::{Arc, Mutex};
+
+  let reports = Arc::new(Mutex::new(Vec::new()));
+  let mut allocator = TrackedAllocator::new(1_000);
+  allocator.on_leak({
+    let reports = reports.clone();
+    move |report| reports.lock().unwrap().push(report.clone())
+  });
+  let a = allocator.alloc(100, ()).unwrap();
+  let b = allocator.alloc(100, ()).unwrap();
+  allocator.alloc(100, ()).unwrap();
+  allocator.free(b);
+
+  let report = allocator.leak_report();
+  assert_eq!(report.leaks.len(), 2);
+  assert_eq!(report.leaks[0].allocation, a);
+  assert_eq!(report.leaks[0].age, 3);
+  assert_eq!(report.leaks[1].age, 1);
+
+  drop(allocator);
+  assert_eq!(*reports.lock().unwrap(), [report]);
+}
+
+#[test]
+fn allocation_ids() {
+  let mut allocator = TrackedAllocator::new(1_000);
+  let a = allocator.alloc(100, 'a').unwrap();
+  let b = allocator.alloc(100, 'b').unwrap();
+  let a_id = allocator.id(a).unwrap();
+  let b_id = allocator.id(b).unwrap();
+  assert_ne!(a_id, b_id);
+  assert_eq!(allocator.resolve(b_id), Some(b));
+  assert_eq!(allocator.resolve_index(b_id.index()), Some(b));
+
+  let bits = a_id.to_bits();
+  assert_eq!(allocator.free_id(AllocationId::from_bits(bits)), 'a');
+  assert_eq!(allocator.resolve(a_id), None);
+  assert_eq!(allocator.resolve_index(a_id.index()), None);
+  assert_eq!(allocator.id(a), None);
+
+  // indices stay dense, but stale ids are rejected
+  let c = allocator.alloc(100, 'c').unwrap();
+  let c_id = allocator.id(c).unwrap();
+  assert_eq!(c_id.index(), a_id.index());
+  assert_ne!(c_id, a_id);
+  assert_eq!(allocator.resolve(a_id), None);
+  assert_eq!(allocator.resolve(c_id), Some(c));
+  assert_eq!(
+    allocator.resolve_index(a_id.index()),
+    Some(c),
+    "Compact indices don't detect stale handles"
+  );
+  assert_eq!(allocator.resolve_index(u32::MAX), None);
+  allocator.retain(|_, &mut data| data == 'c');
+  assert_eq!(allocator.resolve(b_id), None);
+  allocator.free(c);
+}
+
+#[test]
+fn split_allocation() {
+  let mut allocator = TrackedAllocator::new(1_000);
+  let a = allocator.alloc(100, "a").unwrap();
+  let id = allocator.id(a).unwrap();
+
+  let (first, second) = allocator.split_allocation(a, 30, "b");
+  assert_eq!((first.offset(), first.size()), (0, 30));
+  assert_eq!((second.offset(), second.size()), (30, 70));
+  assert_eq!(allocator.len(), 2);
+  assert_eq!(allocator.resolve(id), Some(first));
+  assert_eq!(allocator.get(a), None);
+  assert_eq!(allocator.get(second), Some(&"b"));
+
+  assert_eq!(allocator.free(first), "a");
+  assert_eq!(allocator.allocator().total_available(), 930);
+  assert_eq!(allocator.free(second), "b");
+  assert!(allocator.allocator().is_empty());
+}
+
+#[test]
+fn merge_adjacent() {
+  let mut allocator = TrackedAllocator::new(1_000);
+  let a = allocator.alloc(100, "a").unwrap();
+  let b = allocator.alloc(50, "b").unwrap();
+  let c = allocator.alloc(50, "c").unwrap();
+  let id = allocator.id(a).unwrap();
+  let stale = allocator.id(b).unwrap();
+
+  assert_eq!(allocator.merge_adjacent(a, c), None);
+  let (merged, data) = allocator.merge_adjacent(b, a).unwrap();
+  assert_eq!(data, "b");
+  assert_eq!((merged.offset(), merged.size()), (0, 150));
+  assert_eq!(allocator.resolve(id), Some(merged));
+  assert_eq!(allocator.resolve(stale), None);
+  assert_eq!(allocator.len(), 2);
+
+  assert_eq!(allocator.free(merged), "a");
+  assert_eq!(allocator.free(c), "c");
+  assert!(allocator.allocator().is_empty());
+}
+
+#[test]
+fn migration() {
+  let mut vram = TrackedAllocator::new(1_000);
+  let mut host = TrackedAllocator::new(500);
+  let a = vram.alloc(100, "a").unwrap();
+  let b = vram.alloc(200, "b").unwrap();
+  let c = vram.alloc(150, "c").unwrap();
+  let _d = host.alloc(10, "d").unwrap();
+  let (a_id, c_id) = (vram.id(a).unwrap(), vram.id(c).unwrap());
+
+  let plan = vram
+    .plan_migration(&host, &[(c_id, 256), (a_id, 1)])
+    .unwrap();
+  let copies = plan.copies();
+  assert_eq!((copies[0].id, copies[0].from), (c_id, c));
+  assert_eq!(copies[0].to.offset(), 256);
+  assert_eq!((copies[1].from, copies[1].to.offset()), (a, 10));
+  assert_eq!(plan.bytes(), 250);
+  // nothing changes until the plan is committed
+  assert_eq!(vram.len(), 3);
+  assert_eq!(host.len(), 1);
+
+  let ids = vram.commit_migration(&mut host, plan.clone()).unwrap();
+  assert_eq!(vram.len(), 1);
+  assert_eq!(vram.resolve(a_id), None);
+  assert_eq!(vram.allocator().total_available(), 800);
+  assert_eq!(host.resolve(ids[0]), Some(copies[0].to));
+  assert_eq!(host.get(copies[1].to), Some(&"a"));
+  assert_eq!(host.allocator().total_available(), 500 - 260);
+
+  // the plan has already been applied
+  assert_eq!(
+    vram.commit_migration(&mut host, plan),
+    Err(MigrationError::Stale)
+  );
+  assert_eq!(vram.get(b), Some(&"b"));
+}
+
+#[test]
+fn migration_errors() {
+  let mut source = TrackedAllocator::new(1_000);
+  let mut destination = TrackedAllocator::new(100);
+  let a = source.alloc(60, ()).unwrap();
+  let b = source.alloc(60, ()).unwrap();
+  let (a_id, b_id) = (source.id(a).unwrap(), source.id(b).unwrap());
+
+  assert_eq!(
+    source
+      .plan_migration(&destination, &[(a_id, 1), (a_id, 1)])
+      .err(),
+    Some(MigrationError::InvalidId(a_id))
+  );
+  assert_eq!(
+    source
+      .plan_migration(&destination, &[(a_id, 1), (b_id, 1)])
+      .err(),
+    Some(MigrationError::InsufficientSpace)
+  );
+
+  // the destination changes between planning & committing
+  let plan = source.plan_migration(&destination, &[(b_id, 1)]).unwrap();
+  let c = destination.alloc(10, ()).unwrap();
+  assert_eq!(
+    source.commit_migration(&mut destination, plan),
+    Err(MigrationError::Stale)
+  );
+  assert_eq!(source.len(), 2);
+  assert_eq!(destination.len(), 1);
+  destination.free(c);
+  assert!(destination.allocator().is_empty());
+}
+
+#[test]
+fn migration_rollback_with_deferred_coalescing() {
+  let mut source = TrackedAllocator::new(1_000);
+  let a = source.alloc(100, "a").unwrap();
+  let a_id = source.id(a).unwrap();
+  let planned = TrackedAllocator::<&str>::new(1_000);
+  let plan = source.plan_migration(&planned, &[(a_id, 1)]).unwrap();
+
+  // the same free-regions, but sizes are rounded up, so the replay diverges
+  let mut allocator = Allocator::builder(1_000).size_classes(Some(2)).build();
+  allocator.set_deferred_coalescing(true);
+  let mut destination = TrackedAllocator::from_allocator(allocator);
+  assert_eq!(
+    source.commit_migration(&mut destination, plan),
+    Err(MigrationError::Stale)
+  );
+  assert_eq!(destination.allocator().pending_frees(), 0);
+  assert!(destination.allocator().is_empty());
+  assert_eq!(source.get(a), Some(&"a"));
+}
+
+#[test]
+fn compact() {
+  let mut allocator = TrackedAllocator::new(1_000);
+  let z = allocator.alloc(10, "z").unwrap();
+  let a = allocator.alloc(100, "a").unwrap();
+  let b = allocator.alloc_with_align(50, 64, "b").unwrap();
+  let c = allocator.alloc(30, "c").unwrap();
+  let (b_id, c_id) = (allocator.id(b).unwrap(), allocator.id(c).unwrap());
+  assert_eq!((b.offset(), c.offset()), (128, 178));
+  allocator.free(a);
+
+  let copies = allocator.compact();
+  assert_eq!(copies.len(), 2);
+  assert_eq!((copies[0].id, copies[0].from), (b_id, b));
+  // the alignment is kept
+  assert_eq!(copies[0].to.offset(), 64);
+  assert_eq!((copies[1].id, copies[1].from), (c_id, c));
+  assert_eq!(copies[1].to.offset(), 114);
+
+  // ids are stable, so handles resolve to the new locations
+  assert_eq!(allocator.resolve(b_id), Some(copies[0].to));
+  assert_eq!(allocator.get(copies[1].to), Some(&"c"));
+  assert_eq!(allocator.get(c), None);
+  assert_eq!(allocator.get(z), Some(&"z"));
+  assert_eq!(allocator.allocator().largest_available(), 856);
+  assert_eq!(allocator.allocator().total_available(), 910);
+
+  assert!(allocator.compact().is_empty());
+  allocator.retain(|_, _| false);
+  assert!(allocator.allocator().is_empty());
+}
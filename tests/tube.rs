@@ -1,4 +1,8 @@
-use ::orderly_allocator::{Allocation, Allocator};
+use ::core::num::NonZero;
+use ::orderly_allocator::{
+  Allocation, Allocator, Coloring, DropCheck, InvalidAllocation, Placement,
+  PressureAction, PressureLevel, SpanState,
+};
 use orderly_allocator::ReallocateError;
 
 #[test]
@@ -18,6 +22,7 @@ fn allocaton_type_size() {
 #[test]
 fn allocation_size_and_align() {
   let mut allocator = Allocator::new(1_000_000);
+  allocator.set_drop_check(DropCheck::Off);
   {
     let a = allocator.alloc(59).unwrap();
     assert_eq!(a.size(), 59, "Allocation size is as requested");
@@ -194,6 +199,7 @@ fn try_reallocate() {
   const CAPACITY: u32 = 10_000_000;
   const ALLOC_SIZE: u32 = 50_000;
   let mut allocator = Allocator::new(CAPACITY);
+  allocator.set_drop_check(DropCheck::Off);
   let a = allocator.alloc(ALLOC_SIZE).unwrap();
   let _b = allocator.alloc(3_000).unwrap();
   let _c = allocator.alloc(50_000).unwrap();
@@ -270,3 +276,1590 @@ fn try_reallocate() {
     new_a
   };
 }
+
+#[test]
+fn alloc_many() {
+  const CAPACITY: u32 = 10_000;
+  let mut allocator = Allocator::new(CAPACITY);
+  allocator.set_drop_check(DropCheck::Off);
+
+  let allocations = allocator
+    .alloc_many(&[(1_000, 1), (2_000, 8), (500, 256)])
+    .unwrap();
+  assert_eq!(allocations.len(), 3);
+  assert_eq!(allocations[0].size(), 1_000);
+  assert_eq!(allocations[1].size(), 2_000);
+  assert_eq!(allocations[1].offset() % 8, 0);
+  assert_eq!(allocations[2].size(), 500);
+  assert_eq!(allocations[2].offset() % 256, 0);
+
+  let available = allocator.total_available();
+  let free_regions: Vec<_> = allocator.report_free_regions().collect();
+
+  assert!(
+    allocator
+      .alloc_many(&[(1_000, 1), (2_000, 1), (CAPACITY, 1)])
+      .is_none(),
+    "Fails if any single request cannot be satisfied"
+  );
+  assert_eq!(
+    allocator.total_available(),
+    available,
+    "A failed alloc_many does not consume any space"
+  );
+  assert_eq!(
+    allocator.report_free_regions().collect::<Vec<_>>(),
+    free_regions,
+    "A failed alloc_many leaves the free regions untouched"
+  );
+}
+
+#[test]
+fn free_many() {
+  const CAPACITY: u32 = 10_000;
+  let mut allocator = Allocator::new(CAPACITY);
+
+  // [a][b][c][d][e][---free---]
+  let allocations: Vec<_> =
+    (0..5).map(|_| allocator.alloc(1_000).unwrap()).collect();
+
+  // free `a`, `b` & `d` in one go, in an arbitrary order
+  // [--free--][c][free][e][---free---]
+  allocator.free_many([allocations[3], allocations[0], allocations[1]]);
+  assert_eq!(
+    allocator.total_available(),
+    CAPACITY - 2_000,
+    "Recovers space when freeing many allocations"
+  );
+  assert_eq!(
+    allocator.report_free_regions().count(),
+    3,
+    "Coalesces neighbouring allocations freed in the same batch"
+  );
+
+  // [-------------------------------free-------------------------------------]
+  allocator.free_many([allocations[2], allocations[4]]);
+  assert_eq!(allocator.total_available(), CAPACITY);
+  assert_eq!(
+    allocator.largest_available(),
+    CAPACITY,
+    "Coalesces freed allocations with existing free regions"
+  );
+
+  allocator.free_many([]);
+  assert_eq!(allocator.total_available(), CAPACITY);
+}
+
+#[test]
+fn alloc_up_to() {
+  const CAPACITY: u32 = 10_000;
+  let mut allocator = Allocator::new(CAPACITY);
+  allocator.set_drop_check(DropCheck::Off);
+
+  let a = allocator.alloc_up_to(4_000, 1_000).unwrap();
+  assert_eq!(a.size(), 4_000, "Allocates max_size when there is room");
+
+  let b = allocator.alloc_up_to(8_000, 1_000).unwrap();
+  assert_eq!(
+    b.size(),
+    CAPACITY - a.size(),
+    "Allocates the largest free region when max_size doesn't fit"
+  );
+  assert_eq!(allocator.total_available(), 0);
+
+  allocator.free(a);
+  assert!(
+    allocator.alloc_up_to(8_000, 5_000).is_none(),
+    "Fails when the largest free region is smaller than min_size"
+  );
+  assert!(
+    allocator.alloc_up_to(1_000, 2_000).is_none(),
+    "Fails when min_size > max_size"
+  );
+  assert_eq!(allocator.total_available(), a.size());
+}
+
+#[test]
+fn alloc_up_to_size_classes() {
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_size_classes(Some(2));
+  let a = allocator.alloc_up_to(700, 1).unwrap();
+  assert_eq!(
+    a.size(),
+    512,
+    "Rounds down to a class no larger than max_size"
+  );
+  allocator.free(a);
+
+  let mut allocator = Allocator::new(1_100);
+  allocator.set_size_classes(Some(2));
+  let a = allocator.alloc(100).unwrap();
+  assert_eq!(allocator.largest_available(), 972);
+  let b = allocator.alloc_up_to(2_000, 1).unwrap();
+  assert_eq!(b.size(), 768, "Rounds down to a class which fits");
+  allocator.free(a);
+  allocator.free(b);
+}
+
+#[test]
+fn alloc_up_to_min_align() {
+  let mut allocator = Allocator::with_min_align(1_000, 256);
+  let a = allocator.alloc(10).unwrap();
+  let b = allocator.alloc_up_to(5_000, 1).unwrap();
+  assert_eq!(
+    (b.offset(), b.size()),
+    (256, 744),
+    "Allocates the aligned part of the largest free region"
+  );
+  assert_eq!(allocator.total_available(), 246);
+  allocator.free(a);
+  allocator.free(b);
+
+  let c = allocator.alloc_up_to(5_000, 1).unwrap();
+  assert_eq!((c.offset(), c.size()), (0, 1_000));
+  allocator.free(c);
+}
+
+#[test]
+fn take_largest_free_region() {
+  const CAPACITY: u32 = 10_000;
+  let mut allocator = Allocator::new(CAPACITY);
+  allocator.set_drop_check(DropCheck::Off);
+
+  // [a][------free------][c][---free---]
+  let a = allocator.alloc(1_000).unwrap();
+  let b = allocator.alloc(5_000).unwrap();
+  let _c = allocator.alloc(1_000).unwrap();
+  allocator.free(b);
+
+  let largest = allocator.take_largest_free_region().unwrap();
+  assert_eq!(largest, b, "Takes the largest free region");
+  assert_eq!(allocator.total_available(), CAPACITY - 7_000);
+
+  let rest = allocator.take_largest_free_region().unwrap();
+  assert_eq!(rest.size(), 3_000);
+  assert_eq!(allocator.total_available(), 0);
+  assert!(
+    allocator.take_largest_free_region().is_none(),
+    "Fails when there are no free regions"
+  );
+  assert_eq!(allocator.stats().failed_allocs, 1);
+
+  allocator.free(a);
+  assert_eq!(allocator.total_available(), a.size());
+}
+
+#[test]
+fn region_state() {
+  const CAPACITY: u32 = 10_000;
+  let mut allocator = Allocator::new(CAPACITY);
+  allocator.set_drop_check(DropCheck::Off);
+
+  // [a][---free---][c][---free---]
+  let a = allocator.alloc(1_000).unwrap();
+  let b = allocator.alloc(2_000).unwrap();
+  let c = allocator.alloc(1_000).unwrap();
+  allocator.free(b);
+
+  assert_eq!(allocator.region_state(0), Some(SpanState::Allocated));
+  assert_eq!(allocator.region_state(999), Some(SpanState::Allocated));
+  assert_eq!(allocator.region_state(1_000), Some(SpanState::Free));
+  assert_eq!(allocator.region_state(2_999), Some(SpanState::Free));
+  assert_eq!(
+    allocator.region_state(c.offset()),
+    Some(SpanState::Allocated)
+  );
+  assert_eq!(allocator.region_state(CAPACITY - 1), Some(SpanState::Free));
+  assert_eq!(
+    allocator.region_state(CAPACITY),
+    None,
+    "Locations past the end of the pool have no state"
+  );
+
+  assert!(allocator.is_range_free(b.offset(), b.size()));
+  assert!(allocator.is_range_free(1_500, 500));
+  assert!(
+    !allocator.is_range_free(b.offset(), b.size() + 1),
+    "A range overlapping an allocation is not free"
+  );
+  assert!(!allocator.is_range_free(a.offset(), 1));
+  assert!(!allocator.is_range_free(b.offset(), 0));
+  assert!(
+    !allocator.is_range_free(CAPACITY - 10, 20),
+    "A range extending past the end of the pool is not free"
+  );
+}
+
+#[test]
+fn peek_best_fit() {
+  const CAPACITY: u32 = 10_000;
+  let mut allocator = Allocator::new(CAPACITY);
+  allocator.set_drop_check(DropCheck::Off);
+
+  // [a][--free--][c][-free-][e][------free------]
+  let a = allocator.alloc(1_000).unwrap();
+  let b = allocator.alloc(2_000).unwrap();
+  let _c = allocator.alloc(1_000).unwrap();
+  let d = allocator.alloc(1_000).unwrap();
+  let _e = allocator.alloc(1_000).unwrap();
+  allocator.free(b);
+  allocator.free(d);
+
+  let available = allocator.total_available();
+  assert_eq!(allocator.peek_best_fit(500, 1), Some(d));
+  assert_eq!(allocator.peek_best_fit(1_500, 1), Some(b));
+  assert_eq!(allocator.peek_best_fit(1_000, 1), Some(d));
+  assert_eq!(
+    allocator.peek_best_fit(1_000, 8),
+    Some(d),
+    "Prefers an aligned exact fit"
+  );
+  allocator.set_exact_fit_first(false);
+  assert_eq!(
+    allocator
+      .peek_best_fit(1_000, 8)
+      .map(|region| region.offset()),
+    Some(b.offset()),
+    "Takes alignment padding into account"
+  );
+  allocator.set_exact_fit_first(true);
+  assert_eq!(allocator.peek_best_fit(CAPACITY, 1), None);
+  assert_eq!(allocator.peek_best_fit(0, 1), None);
+  assert_eq!(
+    allocator.total_available(),
+    available,
+    "Peeking doesn't allocate"
+  );
+
+  let region = allocator.peek_best_fit(1_500, 1).unwrap();
+  let allocation = allocator.alloc(1_500).unwrap();
+  assert_eq!(
+    allocation.offset(),
+    region.offset(),
+    "Allocations are carved from the reported region"
+  );
+  allocator.free(a);
+}
+
+#[test]
+fn free_size_histogram() {
+  const CAPACITY: u32 = 10_000;
+  let mut allocator = Allocator::new(CAPACITY);
+  allocator.set_drop_check(DropCheck::Off);
+
+  // [free:100][a][free:1_000][b][free:2_000][c][------free:5_700------]
+  let sizes = [100, 1_000, 2_000];
+  let mut holes = Vec::new();
+  for size in sizes {
+    holes.push(allocator.alloc(size).unwrap());
+    allocator.alloc(400).unwrap();
+  }
+  for hole in holes {
+    allocator.free(hole);
+  }
+
+  let histogram = allocator.free_size_histogram(&[100, 1_000, 5_000]);
+  let counts: Vec<_> = histogram.iter().map(|bucket| bucket.count).collect();
+  let totals: Vec<_> =
+    histogram.iter().map(|bucket| bucket.total_size).collect();
+  assert_eq!(counts, [0, 1, 2, 1]);
+  assert_eq!(totals, [0, 100, 3_000, 5_700]);
+
+  let histogram = allocator.free_size_histogram(&[]);
+  assert_eq!(histogram.len(), 1, "No edges results in a single bucket");
+  assert_eq!(histogram[0].count, 4);
+  assert_eq!(histogram[0].total_size, allocator.total_available());
+}
+
+#[test]
+fn state_hash() {
+  let mut a = Allocator::new(10_000);
+  a.set_drop_check(DropCheck::Off);
+  let mut b = Allocator::new(10_000);
+  b.set_drop_check(DropCheck::Off);
+  assert_eq!(a.state_hash(), b.state_hash());
+
+  // reach the same state through different sequences of operations
+  let x = a.alloc(1_000).unwrap();
+  let y = a.alloc(2_000).unwrap();
+  a.free(x);
+  let _ = b.alloc(1_000).unwrap();
+  let _ = b.alloc(2_000).unwrap();
+  b.free(Allocation {
+    offset: 0,
+    size: NonZero::new(1_000).unwrap(),
+  });
+  assert_eq!(a.state_hash(), b.state_hash());
+
+  a.free(y);
+  assert_ne!(a.state_hash(), b.state_hash());
+  assert_ne!(
+    Allocator::new(10_000).state_hash(),
+    Allocator::new(10_001).state_hash(),
+    "Capacity is part of the state"
+  );
+}
+
+#[test]
+fn logical_equality() {
+  let mut a = Allocator::new(10_000);
+  a.set_drop_check(DropCheck::Off);
+  let mut b = a.clone();
+  assert_eq!(a, b);
+
+  let x = a.alloc(1_000).unwrap();
+  let _ = a.alloc(2_000).unwrap();
+  a.free(x);
+  b.alloc(3_000).unwrap();
+  b.free(Allocation {
+    offset: 0,
+    size: NonZero::new(1_000).unwrap(),
+  });
+  assert_eq!(a, b, "Same free-regions, reached differently");
+
+  a.grow_capacity(1).unwrap();
+  assert_ne!(a, b);
+}
+
+#[test]
+fn diff() {
+  let mut a = Allocator::new(10_000);
+  a.set_drop_check(DropCheck::Off);
+  let b = a.clone();
+  assert!(a.diff(&b).is_empty());
+
+  let x = a.alloc(1_000).unwrap();
+  let _ = a.alloc(1_000).unwrap();
+  a.free(x);
+
+  let diff = a.diff(&b);
+  let region = |offset, size| Allocation {
+    offset,
+    size: NonZero::new(size).unwrap(),
+  };
+  assert_eq!(diff.only_in_self, [region(0, 1_000), region(2_000, 8_000)]);
+  assert_eq!(diff.only_in_other, [region(0, 10_000)]);
+}
+
+#[test]
+fn seal() {
+  let mut allocator = Allocator::new(10_000);
+  let a = allocator.alloc(1_000).unwrap();
+
+  let sealed = allocator.seal();
+  assert_eq!(sealed.total_available(), 9_000, "Queries still work");
+  assert!(!sealed.is_range_free(a.offset(), a.size()));
+
+  let mut allocator = sealed.unseal();
+  allocator.free(a);
+  assert!(allocator.is_empty());
+}
+
+#[test]
+fn free_report() {
+  let mut allocator = Allocator::new(10_000);
+  allocator.set_drop_check(DropCheck::Off);
+  let a = allocator.alloc(1_000).unwrap();
+  let b = allocator.alloc(1_000).unwrap();
+  let _c = allocator.alloc(1_000).unwrap();
+
+  let region = allocator.free_report(b);
+  assert_eq!(region, b, "Nothing to coalesce with");
+
+  let region = allocator.free_report(a);
+  assert_eq!(region.offset(), 0);
+  assert_eq!(region.size(), 2_000, "Coalesced with the freed `b`");
+}
+
+#[test]
+fn alloc_with_align_report() {
+  let mut allocator = Allocator::new(10_000);
+  allocator.set_drop_check(DropCheck::Off);
+  allocator.alloc(100).unwrap();
+
+  let (allocation, report) =
+    allocator.alloc_with_align_report(1_000, 256).unwrap();
+  assert_eq!(allocation.offset(), 256);
+  assert_eq!(report.padding, 156);
+  assert_eq!(report.leftover, 10_000 - 1_256);
+}
+
+#[test]
+fn stats() {
+  let mut allocator = Allocator::new(10_000);
+  allocator.set_drop_check(DropCheck::Off);
+  allocator.alloc(100).unwrap();
+  allocator.alloc_with_align(1_000, 256).unwrap();
+  allocator.alloc_with_align(1_000, 256).unwrap();
+
+  let stats = allocator.stats();
+  assert_eq!(stats.capacity, 10_000);
+  assert_eq!(stats.total_available, 10_000 - 2_100);
+  assert_eq!(stats.free_regions, 3);
+  assert_eq!(stats.alignment_padding, 156 + 24);
+}
+
+#[test]
+fn min_align() {
+  let mut allocator = Allocator::with_min_align(10_000, 256);
+  allocator.set_drop_check(DropCheck::Off);
+  assert_eq!(allocator.min_align(), 256);
+
+  for _ in 0..4 {
+    let allocation = allocator.alloc(100).unwrap();
+    assert_eq!(allocation.offset() % 256, 0, "Plain allocs are aligned");
+  }
+  let allocation = allocator.alloc_with_align(100, 1_024).unwrap();
+  assert_eq!(
+    allocation.offset() % 1_024,
+    0,
+    "Larger alignments are honoured"
+  );
+  let allocation = allocator.alloc_with_align(100, 3).unwrap();
+  assert_eq!(allocation.offset() % 768, 0, "Alignments are combined");
+}
+
+#[test]
+fn alloc_with_retry() {
+  let mut allocator = Allocator::new(3_000);
+  allocator.set_drop_check(DropCheck::Off);
+  let a = allocator.alloc(1_000).unwrap();
+  let b = allocator.alloc(1_000).unwrap();
+
+  // not enough space in total, so the emergency pass is pointless
+  let mut called = false;
+  let result = allocator.alloc_with_retry(2_000, 1, |_| called = true);
+  assert!(result.is_none());
+  assert!(!called);
+
+  // enough space in total, but fragmented
+  allocator.free(a);
+  let result = allocator.alloc_with_retry(2_000, 1, |allocator| {
+    allocator.free(b);
+  });
+  assert!(result.is_some(), "Succeeds after the emergency pass");
+}
+
+#[test]
+fn huge_alignment() {
+  const ALIGN: u32 = 1 << 31;
+  let mut allocator = Allocator::new(u32::MAX);
+  allocator.set_drop_check(DropCheck::Off);
+  allocator.alloc(1_000).unwrap();
+
+  // `size + align - 1` overflows, but the allocation does fit at `ALIGN`
+  let allocation = allocator.alloc_with_align(ALIGN - 1_000, ALIGN).unwrap();
+  assert_eq!(allocation.offset(), ALIGN);
+
+  assert!(
+    allocator.alloc_with_align(ALIGN, ALIGN).is_none(),
+    "Nothing left at an aligned location"
+  );
+}
+
+#[test]
+fn full_u32_range() {
+  let mut allocator = Allocator::new(u32::MAX);
+  let all = allocator.alloc(u32::MAX).unwrap();
+  assert_eq!(all.range(), 0..u32::MAX as usize);
+  allocator.free(all);
+
+  // allocations touching the top of the range
+  let low = allocator.alloc(u32::MAX - 2).unwrap();
+  let top = allocator.alloc(2).unwrap();
+  assert_eq!(top.range().end, u32::MAX as usize);
+  assert!(allocator.try_reallocate(top, 3).is_err());
+  let top = allocator.try_reallocate(top, 1).unwrap();
+  let top = allocator.try_reallocate(top, 2).unwrap();
+  assert_eq!(
+    allocator.region_state(u32::MAX - 1),
+    Some(SpanState::Allocated)
+  );
+  assert_eq!(allocator.region_state(u32::MAX), None);
+
+  allocator.free(top);
+  allocator.free(low);
+  assert!(allocator.is_empty());
+  assert!(allocator.grow_capacity(1).is_err());
+
+  let mut allocator = Allocator::new(u32::MAX - 1);
+  allocator.grow_capacity(1).unwrap();
+  assert_eq!(allocator.largest_available(), u32::MAX);
+}
+
+#[test]
+fn random_placement() {
+  let layout = |seed| {
+    let mut allocator = Allocator::new(1_000);
+    allocator.set_drop_check(DropCheck::Off);
+    let allocations: Vec<_> =
+      (0..10).map(|_| allocator.alloc(10).unwrap()).collect();
+    for allocation in allocations.into_iter().step_by(2) {
+      allocator.free(allocation);
+    }
+    allocator.set_placement(Placement::Random { seed });
+    assert_eq!(allocator.placement(), Placement::Random { seed });
+    let offsets: Vec<_> = (0..4)
+      .map(|_| {
+        let peeked = allocator.peek_best_fit(5, 1).unwrap();
+        let allocation = allocator.alloc(5).unwrap();
+        assert_eq!(allocation.offset, peeked.offset);
+        allocation.offset
+      })
+      .collect();
+    offsets
+  };
+
+  // the same seed always produces the same layout
+  assert_eq!(layout(1), layout(1));
+  assert!((0..10).any(|seed| layout(seed) != layout(seed + 1)));
+
+  // best-fit fills the lowest small free-region first
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_drop_check(DropCheck::Off);
+  let a = allocator.alloc(10).unwrap();
+  allocator.alloc(10).unwrap();
+  allocator.free(a);
+  assert_eq!(allocator.placement(), Placement::BestFit);
+  assert_eq!(allocator.alloc(5).unwrap().offset, 0);
+}
+
+#[test]
+fn layout() {
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_drop_check(DropCheck::Off);
+  assert_eq!(
+    allocator.layout().collect::<Vec<_>>(),
+    [(0..1_000, SpanState::Free)]
+  );
+
+  let a = allocator.alloc(100).unwrap();
+  let b = allocator.alloc(100).unwrap();
+  allocator.alloc(100).unwrap();
+  allocator.alloc(700).unwrap();
+  allocator.free(a);
+  allocator.free(b);
+  allocator.alloc(100).unwrap();
+
+  assert_eq!(
+    allocator.layout().collect::<Vec<_>>(),
+    [
+      (0..100, SpanState::Allocated),
+      (100..200, SpanState::Free),
+      (200..1_000, SpanState::Allocated),
+    ]
+  );
+}
+
+#[test]
+fn report_free_regions_from_both_ends() {
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_drop_check(DropCheck::Off);
+  let a = allocator.alloc(100).unwrap();
+  allocator.alloc(100).unwrap();
+  let c = allocator.alloc(300).unwrap();
+  allocator.alloc(100).unwrap();
+  allocator.free(a);
+  allocator.free(c);
+
+  let regions = allocator.report_free_regions();
+  assert_eq!(regions.len(), 3);
+  let sizes: Vec<_> = regions.rev().map(|region| region.size()).collect();
+  assert_eq!(sizes, [400, 300, 100]);
+}
+
+#[test]
+fn free_regions_at_least() {
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_drop_check(DropCheck::Off);
+  let a = allocator.alloc(100).unwrap();
+  allocator.alloc(100).unwrap();
+  let c = allocator.alloc(300).unwrap();
+  allocator.alloc(100).unwrap();
+  allocator.free(a);
+  allocator.free(c);
+
+  let offsets = |size| {
+    allocator
+      .free_regions_at_least(size)
+      .map(|region| region.offset)
+      .collect::<Vec<_>>()
+  };
+  assert_eq!(offsets(0), [0, 200, 600]);
+  assert_eq!(offsets(300), [200, 600]);
+  assert_eq!(offsets(301), [600]);
+  assert_eq!(offsets(401), []);
+}
+
+#[test]
+fn next_free_region_after() {
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_drop_check(DropCheck::Off);
+  let a = allocator.alloc(100).unwrap();
+  allocator.alloc(100).unwrap();
+  allocator.free(a);
+
+  let next = |offset| allocator.next_free_region_after(offset);
+  assert_eq!(next(0).unwrap().range(), 0..100);
+  assert_eq!(next(1).unwrap().range(), 200..1_000);
+  assert_eq!(
+    next(50).unwrap().range(),
+    200..1_000,
+    "A free-region which starts before the offset is skipped"
+  );
+  assert_eq!(next(200).unwrap().range(), 200..1_000);
+  assert_eq!(next(201), None);
+}
+
+#[test]
+fn preserve_largest_placement() {
+  let setup = || {
+    let mut allocator = Allocator::new(1_000);
+    allocator.set_drop_check(DropCheck::Off);
+    // a 64 byte free-region which is already aligned to 64
+    allocator.alloc(64).unwrap();
+    let hole = allocator.alloc(64).unwrap();
+    allocator.alloc(64).unwrap();
+    allocator.free(hole);
+    allocator
+  };
+
+  // best-fit needs `size + align - 1` bytes, so splits the largest region
+  let mut allocator = setup();
+  allocator.set_exact_fit_first(false);
+  assert_eq!(allocator.alloc_with_align(64, 64).unwrap().offset, 192);
+
+  // ..unless it finds the aligned exact fit first
+  let mut allocator = setup();
+  assert_eq!(allocator.alloc_with_align(64, 64).unwrap().offset, 64);
+
+  let mut allocator = setup();
+  allocator.set_placement(Placement::PreserveLargest);
+  assert_eq!(allocator.alloc_with_align(64, 64).unwrap().offset, 64);
+  assert_eq!(allocator.largest_available(), 1_000 - 3 * 64);
+  // the largest region is still used when nothing else fits
+  assert_eq!(allocator.alloc(100).unwrap().offset, 192);
+}
+
+#[test]
+fn watermark() {
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_drop_check(DropCheck::Off);
+  assert_eq!(allocator.watermark(), 100);
+  allocator.set_watermark(80);
+
+  let a = allocator.alloc(800).unwrap();
+  assert_eq!(allocator.alloc(1), None);
+  assert_eq!(allocator.peek_best_fit(1, 1), None);
+  let b = allocator.alloc_privileged(200, 1).unwrap();
+  allocator.free(b);
+
+  allocator.set_watermark(100);
+  assert!(allocator.alloc(200).is_some());
+  allocator.free(a);
+}
+
+#[test]
+#[should_panic]
+fn drop_check_panic() {
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_drop_check(DropCheck::Panic);
+  allocator.alloc(100).unwrap();
+}
+
+#[test]
+#[cfg_attr(debug_assertions, should_panic)]
+fn drop_check_default() {
+  let mut allocator = Allocator::new(1_000);
+  allocator.alloc(100).unwrap();
+}
+
+#[test]
+fn drop_check_off() {
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_drop_check(DropCheck::Panic);
+  let a = allocator.alloc(100).unwrap();
+  allocator.free(a);
+  drop(allocator);
+
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_drop_check(DropCheck::Off);
+  allocator.alloc(100).unwrap();
+}
+
+#[test]
+fn drop_check_deferred() {
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_drop_check(DropCheck::Panic);
+  allocator.set_deferred_coalescing(true);
+  let a = allocator.alloc(100).unwrap();
+  allocator.free(a);
+  assert_eq!(allocator.pending_frees(), 1);
+  // pending frees count as freed
+  drop(allocator);
+}
+
+#[test]
+fn clear_and_resize() {
+  let mut allocator = Allocator::with_min_align(1_000, 16);
+  allocator.alloc(600).unwrap();
+
+  allocator.clear_and_resize(500);
+  assert!(allocator.is_empty());
+  assert_eq!(allocator.capacity(), 500);
+  assert_eq!(allocator.largest_available(), 500);
+  assert_eq!(allocator.alloc(600), None);
+
+  allocator.clear_and_resize(2_000);
+  assert_eq!(allocator.min_align(), 16);
+  allocator.alloc(1).unwrap();
+  assert_eq!(allocator.alloc(1_500).unwrap().offset, 16);
+  allocator.reset();
+}
+
+#[test]
+fn rebuild() {
+  let mut allocator = Allocator::new(10_000);
+  allocator.set_drop_check(DropCheck::Off);
+  let allocations: Vec<_> =
+    (0..100).map(|_| allocator.alloc(100).unwrap()).collect();
+  for &allocation in allocations.iter().step_by(2) {
+    allocator.free(allocation);
+  }
+
+  let before = allocator.clone();
+  allocator.rebuild();
+  assert_eq!(allocator, before);
+  assert_eq!(allocator.stats(), before.stats());
+  assert!(allocator.diff(&before).is_empty());
+  allocator.reset();
+}
+
+#[test]
+fn peak_free_regions() {
+  let mut allocator = Allocator::new(1_000);
+  let allocations: Vec<_> =
+    (0..10).map(|_| allocator.alloc(100).unwrap()).collect();
+  for &allocation in allocations.iter().step_by(2) {
+    allocator.free(allocation);
+  }
+  assert_eq!(allocator.stats().peak_free_regions, 5);
+  assert_eq!(allocator.stats().peak_btree_nodes, 2);
+
+  allocator.reset();
+  let stats = allocator.stats();
+  assert_eq!(stats.free_regions, 1);
+  assert_eq!(stats.peak_free_regions, 5);
+  assert_eq!(stats.peak_btree_nodes, 2);
+}
+
+#[test]
+fn free_then_alloc_same_size() {
+  let mut allocator = Allocator::new(10_000);
+  let allocations: Vec<_> =
+    (0..10).map(|_| allocator.alloc(100).unwrap()).collect();
+  for &allocation in allocations.iter().step_by(2) {
+    allocator.free(allocation);
+  }
+
+  let a = allocator.alloc(100).unwrap();
+  assert_eq!(a.size(), 100);
+  assert!(allocations.iter().step_by(2).any(|&free| free == a));
+  allocator.free(a);
+
+  let b = allocator.alloc(100).unwrap();
+  assert_eq!(b, a);
+  assert_eq!(allocator.stats().free_regions, 5);
+  allocator.reset();
+}
+
+#[test]
+fn alloc_near() {
+  let mut allocator = Allocator::new(10_000);
+  let allocations: Vec<_> =
+    (0..10).map(|_| allocator.alloc(1_000).unwrap()).collect();
+  // free-regions at 1_000..2_000, 5_000..6_000 & 8_000..10_000
+  for i in [1, 5, 8, 9] {
+    allocator.free(allocations[i]);
+  }
+
+  // inside a free-region, as close as possible to the offset
+  assert_eq!(allocator.alloc_near(5_500, 100).unwrap().offset, 5_500);
+  assert_eq!(allocator.alloc_near(5_950, 100).unwrap().offset, 5_900);
+  // adjacent to the offset, from either side
+  assert_eq!(allocator.alloc_near(7_000, 100).unwrap().offset, 8_000);
+  assert_eq!(allocator.alloc_near(3_000, 100).unwrap().offset, 1_900);
+  // skips regions which are too small
+  assert_eq!(allocator.alloc_near(5_000, 1_500).unwrap().offset, 8_100);
+  assert_eq!(allocator.alloc_near(0, 5_000), None);
+  assert_eq!(allocator.alloc_near(0, 0), None);
+  allocator.reset();
+
+  let mut allocator = Allocator::with_min_align(1_000, 64);
+  assert_eq!(allocator.alloc_near(500, 10).unwrap().offset, 448);
+  assert_eq!(allocator.alloc_near(995, 10).unwrap().offset, 960);
+  allocator.reset();
+}
+
+#[test]
+fn pre_aligned_regions() {
+  let mut allocator = Allocator::new(1 << 20);
+  allocator.alloc(4_096).unwrap();
+  let b = allocator.alloc(4_096).unwrap();
+  allocator.alloc(1).unwrap();
+  allocator.free(b);
+
+  // best-fit would need `4_096 + 4_095` bytes, and split the large region
+  let stats = allocator.stats();
+  let c = allocator.alloc_with_align(4_096, 4_096).unwrap();
+  assert_eq!(c, b);
+  assert_eq!(allocator.stats().alignment_padding, stats.alignment_padding);
+
+  allocator.reset();
+
+  // ...but a best-fit region smaller than the pre-aligned one is preferred
+  allocator.alloc(1_000).unwrap();
+  let y = allocator.alloc(400).unwrap();
+  allocator.alloc(648).unwrap();
+  allocator.free(y);
+  assert_eq!(allocator.alloc_with_align(100, 256).unwrap().offset, 1_024);
+  allocator.reset();
+}
+
+#[test]
+fn usage_ratio_and_is_full() {
+  let mut allocator = Allocator::new(1_000);
+  assert_eq!(allocator.usage_ratio(), 0.);
+  assert!(!allocator.is_full());
+
+  allocator.alloc(250).unwrap();
+  assert_eq!(allocator.usage_ratio(), 0.25);
+  allocator.alloc(750).unwrap();
+  assert_eq!(allocator.usage_ratio(), 1.);
+  assert!(allocator.is_full());
+  allocator.reset();
+
+  // free space which can't be used at the minimum alignment
+  let mut allocator = Allocator::with_min_align(1_000, 512);
+  allocator.alloc(512).unwrap();
+  assert!(allocator.is_full());
+  allocator.reset();
+}
+
+#[test]
+fn size_classes() {
+  let mut allocator = Allocator::new(100_000);
+  assert_eq!(allocator.size_class(1_000), 1_000);
+
+  allocator.set_size_classes(Some(8));
+  assert_eq!(allocator.size_class(7), 7);
+  assert_eq!(allocator.size_class(17), 18);
+  assert_eq!(allocator.size_class(1_000), 1_024);
+  assert_eq!(allocator.size_class(1_025), 1_152);
+  assert_eq!(allocator.size_class(u32::MAX), u32::MAX);
+
+  // a freed block exactly fits a slightly different request
+  let a = allocator.alloc(1_000).unwrap();
+  assert_eq!(a.size(), 1_024);
+  allocator.alloc(1).unwrap();
+  allocator.free(a);
+  assert_eq!(allocator.alloc(1_020).unwrap(), a);
+
+  allocator.set_size_classes(None);
+  assert_eq!(allocator.alloc(1_000).unwrap().size(), 1_000);
+  allocator.reset();
+}
+
+#[test]
+fn fragmentation_report() {
+  let mut allocator = Allocator::new(10_000);
+  let report = allocator.fragmentation_report(64);
+  assert_eq!(report.free_regions, 1);
+  assert_eq!(report.median, 10_000);
+  assert_eq!(report.fragmentation(), 0.);
+
+  // leave free-regions of sizes 10, 20, .., 100 & the remainder
+  let mut keep = vec![];
+  let mut free = vec![];
+  for size in (10..=100).step_by(10) {
+    free.push(allocator.alloc(size).unwrap());
+    keep.push(allocator.alloc(1).unwrap());
+  }
+  allocator.free_many(free);
+  let report = allocator.fragmentation_report(64);
+  assert_eq!(report.free_regions, 11);
+  assert_eq!(report.total, 10_000 - 10);
+  assert_eq!(report.largest, 10_000 - 560);
+  assert_eq!(report.median, 60);
+  assert_eq!(report.p95, report.largest);
+  assert_eq!(report.unusable, 10 + 20 + 30 + 40 + 50 + 60);
+  assert!((report.mean - 9_990. / 11.).abs() < 1e-9);
+  assert!(report.fragmentation() > 0.);
+  assert!(report.to_string().starts_with("11 free-regions"));
+
+  while let Some(allocation) = allocator.take_largest_free_region() {
+    keep.push(allocation);
+  }
+  let report = allocator.fragmentation_report(64);
+  assert_eq!(report.free_regions, 0);
+  assert_eq!(report.total, 0);
+  allocator.reset();
+}
+
+#[test]
+fn split_and_coalesce_counters() {
+  let mut allocator = Allocator::new(1_000);
+  let a: Vec<_> = (0..5).map(|_| allocator.alloc(100).unwrap()).collect();
+  assert_eq!(allocator.stats().splits, 5);
+
+  allocator.free(a[1]);
+  allocator.free(a[0]);
+  allocator.free(a[3]);
+  allocator.free(a[2]);
+  allocator.free(a[4]);
+  let stats = allocator.stats();
+  assert_eq!(stats.coalesced_left, 0);
+  assert_eq!(stats.coalesced_right, 1);
+  assert_eq!(stats.coalesced_both, 2);
+  assert_eq!(stats.alignment_fragments, 0);
+
+  allocator.alloc(1).unwrap();
+  allocator.alloc_with_align(1, 256).unwrap();
+  let stats = allocator.stats();
+  assert_eq!(stats.splits, 7);
+  assert_eq!(stats.alignment_fragments, 1);
+  allocator.reset();
+}
+
+#[test]
+fn assert_disjoint_from() {
+  let mut allocator = Allocator::new(1_000);
+  let a: Vec<_> = (0..5).map(|_| allocator.alloc(100).unwrap()).collect();
+  allocator.free(a[2]);
+  allocator.assert_disjoint_from(&[a[4], a[0], a[3], a[1]]);
+  allocator.assert_disjoint_from(&[]);
+  allocator.reset();
+}
+
+#[test]
+#[should_panic(expected = "overlaps free-region")]
+fn assert_disjoint_from_freed() {
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_drop_check(DropCheck::Off);
+  let a = allocator.alloc(100).unwrap();
+  let b = allocator.alloc(100).unwrap();
+  allocator.free(a);
+  allocator.assert_disjoint_from(&[a, b]);
+}
+
+#[test]
+#[should_panic(expected = "overlaps")]
+fn assert_disjoint_from_overlapping() {
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_drop_check(DropCheck::Off);
+  let a = allocator.alloc(100).unwrap();
+  allocator.assert_disjoint_from(&[a, a]);
+}
+
+#[test]
+#[should_panic(expected = "past the end")]
+fn assert_disjoint_from_out_of_bounds() {
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_drop_check(DropCheck::Off);
+  let a = allocator.alloc(100).unwrap();
+  let mut other = Allocator::new(2_000);
+  other.alloc(1_000).unwrap();
+  let b = other.alloc(100).unwrap();
+  other.reset();
+  allocator.assert_disjoint_from(&[a, b]);
+}
+
+#[test]
+fn free_subrange() {
+  let mut allocator = Allocator::new(1_000);
+  let a = allocator.alloc(500).unwrap();
+
+  // trim the tail
+  let (head, tail) = allocator.free_subrange(a, 400, 100);
+  assert_eq!(tail, None);
+  let head = head.unwrap();
+  assert_eq!((head.offset(), head.size()), (0, 400));
+  assert_eq!(allocator.total_available(), 600);
+  assert!(allocator.is_range_free(400, 600));
+
+  // punch a hole in the middle
+  let (before, after) = allocator.free_subrange(head, 100, 50);
+  let (before, after) = (before.unwrap(), after.unwrap());
+  assert_eq!((before.offset(), before.size()), (0, 100));
+  assert_eq!((after.offset(), after.size()), (150, 250));
+  assert!(allocator.is_range_free(100, 50));
+  allocator.assert_disjoint_from(&[before, after]);
+
+  // the remainders are freed independently
+  allocator.free(before);
+  allocator.free(after);
+  assert!(allocator.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "is not within")]
+fn free_subrange_outside() {
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_drop_check(DropCheck::Off);
+  let a = allocator.alloc(500).unwrap();
+  allocator.free_subrange(a, 400, 101);
+}
+
+#[test]
+fn alloc_usize() {
+  let mut allocator = Allocator::new(1_000);
+  let data = [0_u16; 50];
+  let a = allocator.alloc_usize(data.len() * 2).unwrap();
+  assert_eq!(a.range().len(), data.len() * 2);
+  let b = allocator.alloc_with_align_usize(100, 64).unwrap();
+  assert_eq!(b.range().start % 64, 0);
+
+  assert_eq!(allocator.alloc_usize(u32::MAX as usize + 1), None);
+  assert_eq!(
+    allocator.alloc_with_align_usize(1, u32::MAX as usize + 1),
+    None
+  );
+  allocator.free_many([a, b]);
+}
+
+#[test]
+fn free_hooks() {
+  use ::std::sync::{Arc, Mutex};
+
+  let freed = Arc::new(Mutex::new(Vec::new()));
+  let mut allocator = Allocator::new(1_000);
+  let sink = Arc::clone(&freed);
+  allocator.set_hooks(move |range| sink.lock().unwrap().push(range));
+
+  let [a, b, c, d] =
+    [100, 100, 100, 100].map(|size| allocator.alloc(size).unwrap());
+  allocator.free(a);
+  allocator.free_many([b]);
+  let (_, c) = allocator.free_subrange(c, 200, 50);
+  let d = allocator.try_reallocate(d, 60).unwrap();
+  assert_eq!(
+    *freed.lock().unwrap(),
+    [0..100, 100..200, 200..250, 360..400]
+  );
+
+  // deferred frees invoke the hooks straight away
+  freed.lock().unwrap().clear();
+  allocator.set_deferred_coalescing(true);
+  allocator.free(c.unwrap());
+  assert_eq!(freed.lock().unwrap().pop(), Some(250..300));
+
+  allocator.clear_hooks();
+  allocator.free(d);
+  assert!(freed.lock().unwrap().is_empty());
+}
+
+#[test]
+fn split_allocation() {
+  let mut allocator = Allocator::new(1_000);
+  let a = allocator.alloc(100).unwrap();
+  let (first, second) = allocator.split_allocation(a, 40);
+  assert_eq!((first.offset(), first.size()), (0, 40));
+  assert_eq!((second.offset(), second.size()), (40, 60));
+
+  allocator.free(second);
+  assert_eq!(allocator.try_reallocate(first, 100).unwrap(), a);
+  allocator.free(a);
+  assert!(allocator.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "cannot split")]
+fn split_allocation_at_end() {
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_drop_check(DropCheck::Off);
+  let a = allocator.alloc(100).unwrap();
+  allocator.split_allocation(a, 100);
+}
+
+#[test]
+fn merge_adjacent() {
+  let mut allocator = Allocator::new(1_000);
+  let a = allocator.alloc(100).unwrap();
+  let (first, second) = allocator.split_allocation(a, 40);
+  assert_eq!(allocator.merge_adjacent(first, second), Some(a));
+  assert_eq!(allocator.merge_adjacent(second, first), Some(a));
+  assert_eq!(allocator.merge_adjacent(first, first), None);
+
+  let b = allocator.alloc(100).unwrap();
+  assert_eq!(allocator.merge_adjacent(first, b), None);
+  allocator.free(allocator.merge_adjacent(a, b).unwrap());
+  assert!(allocator.is_empty());
+}
+
+#[test]
+fn exact_fit_first() {
+  let setup = || {
+    let mut allocator = Allocator::new(10_000);
+    allocator.set_drop_check(DropCheck::Off);
+    // 256 byte holes at 1 (misaligned) & 260 (aligned to 4)
+    allocator.alloc(1).unwrap();
+    let misaligned = allocator.alloc(256).unwrap();
+    allocator.alloc(3).unwrap();
+    let aligned = allocator.alloc(256).unwrap();
+    allocator.alloc(1).unwrap();
+    allocator.free(misaligned);
+    allocator.free(aligned);
+    (allocator, aligned)
+  };
+
+  let (mut allocator, aligned) = setup();
+  assert_eq!(allocator.alloc_with_align(256, 4), Some(aligned));
+  assert_eq!(allocator.stats().free_regions, 2);
+
+  // the padded search splits the tail instead
+  let (mut allocator, _) = setup();
+  allocator.set_exact_fit_first(false);
+  let a = allocator.alloc_with_align(256, 4).unwrap();
+  assert_eq!(a.offset(), 520);
+  assert_eq!(allocator.stats().free_regions, 4);
+}
+
+#[test]
+fn builder() {
+  let mut allocator = Allocator::builder(10_000)
+    .min_align(16)
+    .granularity(16)
+    .placement(Placement::PreserveLargest)
+    .watermark(50)
+    .drop_check(DropCheck::Off)
+    .size_classes(Some(4))
+    .exact_fit_first(false)
+    .build();
+  assert_eq!(allocator.capacity(), 10_000);
+  assert_eq!(allocator.min_align(), 16);
+  assert_eq!(allocator.placement(), Placement::PreserveLargest);
+  assert_eq!(allocator.watermark(), 50);
+  assert_eq!(allocator.size_class(1), 16);
+  assert_eq!(allocator.size_class(100), 112);
+
+  let a = allocator.alloc(1).unwrap();
+  assert_eq!(a.size(), 16);
+  assert_eq!(allocator.alloc(1).unwrap().offset(), 16);
+  assert_eq!(allocator.alloc(5_000), None);
+
+  let allocator = Allocator::builder(10_000).build();
+  assert_eq!(allocator.min_align(), 1);
+  assert_eq!(allocator.placement(), Placement::BestFit);
+  assert_eq!(allocator.watermark(), 100);
+  assert_eq!(allocator.size_class(100), 100);
+}
+
+#[test]
+fn granularity() {
+  let mut allocator = Allocator::with_min_align(1_000, 64);
+  allocator.set_granularity(64);
+  let a = allocator.alloc(1).unwrap();
+  let b = allocator.alloc(65).unwrap();
+  assert_eq!((a.size(), b.offset(), b.size()), (64, 64, 128));
+  allocator.free(a);
+  allocator.free(b);
+  assert!(allocator.is_empty());
+}
+
+#[test]
+fn alloc_extent() {
+  const PAGE: u32 = 8_192;
+  let mut allocator = Allocator::new(100 * PAGE + 100);
+  allocator.set_granularity(1_000);
+  assert_eq!(allocator.available_extents(PAGE), 100);
+
+  let misalign = allocator.alloc(100).unwrap();
+  let a = allocator.alloc_extent(3, PAGE).unwrap();
+  assert_eq!((a.offset() % PAGE, a.size()), (0, 3 * PAGE));
+  assert_eq!(allocator.available_extents(PAGE), 96);
+
+  allocator.free(misalign);
+  assert_eq!(allocator.available_extents(PAGE), 97);
+  assert_eq!(allocator.alloc_extent(98, PAGE), None);
+  assert_eq!(allocator.alloc_extent(0, PAGE), None);
+  assert_eq!(allocator.alloc_extent(1, 0), None);
+  assert_eq!(allocator.alloc_extent(u32::MAX, 2), None);
+  assert_eq!(allocator.available_extents(0), 0);
+  allocator.free(a);
+  assert!(allocator.is_empty());
+}
+
+#[test]
+fn alloc_pages() {
+  let mut allocator = Allocator::new(1_000);
+  let a = allocator.alloc(100).unwrap();
+  let b = allocator.alloc(150).unwrap();
+  let c = allocator.alloc(200).unwrap();
+  let d = allocator.alloc(100).unwrap();
+  allocator.free(a);
+  allocator.free(c);
+  assert_eq!(allocator.available_extents(100), 6);
+
+  // the smallest free-regions are used up first
+  let pages = allocator.alloc_pages(4, 100).unwrap();
+  let offsets: Vec<_> = pages.iter().map(|page| page.offset()).collect();
+  assert_eq!(offsets, [0, 300, 600, 700]);
+  assert!(pages.iter().all(|page| page.size() == 100));
+  assert_eq!(allocator.largest_available(), 200);
+
+  // all-or-nothing
+  assert_eq!(allocator.alloc_pages(3, 100), None);
+  assert_eq!(allocator.available_extents(100), 2);
+  assert_eq!(allocator.alloc_pages(0, 100), None);
+  assert_eq!(allocator.alloc_pages(1, 0), None);
+  assert_eq!(allocator.alloc_pages(u32::MAX, 2), None);
+
+  allocator.free_many(pages);
+  allocator.free(b);
+  allocator.free(d);
+  assert!(allocator.is_empty());
+}
+
+#[test]
+fn clone_from() {
+  let mut allocator = Allocator::new(10_000);
+  allocator.set_drop_check(DropCheck::Off);
+  let allocations: Vec<_> =
+    (0..10).map(|_| allocator.alloc(100).unwrap()).collect();
+  for &allocation in allocations.iter().step_by(2) {
+    allocator.free(allocation);
+  }
+
+  let mut snapshot = allocator.clone();
+  for frame in 0..3 {
+    let a = allocator.alloc(100 + frame).unwrap();
+    allocator.free(allocations[1]);
+    assert_ne!(allocator, snapshot);
+
+    // roll back
+    allocator.clone_from(&snapshot);
+    assert_eq!(allocator, snapshot);
+    assert_eq!(allocator.stats(), snapshot.stats());
+    assert!(allocator.is_range_free(a.offset(), a.size()));
+    assert!(!allocator.is_range_free(allocations[1].offset(), 1));
+  }
+
+  // copying into an allocator with a different shape
+  let mut other = Allocator::new(50);
+  other.set_drop_check(DropCheck::Off);
+  other.alloc(10).unwrap();
+  other.copy_state_from(&allocator);
+  assert_eq!(other, allocator);
+  assert_eq!(other.alloc(100), allocator.clone().alloc(100));
+
+  snapshot.reset();
+  allocator.reset();
+  other.reset();
+}
+
+#[test]
+fn deferred_coalescing() {
+  let mut allocator = Allocator::new(10_000);
+  let allocations: Vec<_> =
+    (0..50).map(|_| allocator.alloc(100).unwrap()).collect();
+  let mut immediate = allocator.clone();
+
+  allocator.set_deferred_coalescing(true);
+  for &allocation in allocations.iter().step_by(2).rev() {
+    allocator.free(allocation);
+  }
+  allocator.free_many(allocations.iter().skip(1).step_by(2).copied());
+  assert_eq!(allocator.pending_frees(), 50);
+  assert_eq!(allocator.total_available(), 5_000);
+  assert_eq!(allocator.alloc(5_001), None);
+
+  allocator.coalesce_all();
+  assert_eq!(allocator.pending_frees(), 0);
+  assert!(allocator.is_empty());
+  assert_eq!(allocator.stats().free_regions, 1);
+
+  // the same frees, coalesced immediately, reach the same state
+  for &allocation in allocations.iter().step_by(2).rev() {
+    immediate.free(allocation);
+  }
+  immediate.free_many(allocations.iter().skip(1).step_by(2).copied());
+  assert_eq!(allocator, immediate);
+  let (stats, expected) = (allocator.stats(), immediate.stats());
+  assert_eq!(
+    stats.coalesced_left + stats.coalesced_right + 2 * stats.coalesced_both,
+    expected.coalesced_left
+      + expected.coalesced_right
+      + 2 * expected.coalesced_both,
+  );
+
+  // switching deferral off flushes pending frees
+  let a = allocator.alloc(100).unwrap();
+  allocator.free(a);
+  assert_eq!(allocator.pending_frees(), 1);
+  allocator.set_deferred_coalescing(false);
+  assert!(allocator.is_empty());
+  let b = allocator.alloc(100).unwrap();
+  allocator.free(b);
+  assert_eq!(allocator.pending_frees(), 0);
+}
+
+#[test]
+fn deferred_alloc_many() {
+  let mut allocator = Allocator::new(100);
+  allocator.set_deferred_coalescing(true);
+  let free_regions: Vec<_> = allocator.report_free_regions().collect();
+
+  // rolling back bypasses the deferral, so nothing is left pending
+  assert_eq!(allocator.alloc_many(&[(40, 1); 3]), None);
+  assert_eq!(allocator.total_available(), 100);
+  assert_eq!(allocator.pending_frees(), 0);
+  assert!(allocator.report_free_regions().eq(free_regions));
+}
+
+#[test]
+#[should_panic(expected = "Double free")]
+fn deferred_double_free() {
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_drop_check(DropCheck::Off);
+  allocator.set_deferred_coalescing(true);
+  let a = allocator.alloc(100).unwrap();
+  allocator.free(a);
+  allocator.free(a);
+  allocator.coalesce_all();
+}
+
+#[test]
+fn pressure() {
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_drop_check(DropCheck::Off);
+  let pressure = allocator.pressure();
+  assert_eq!(pressure.level, PressureLevel::Low);
+  assert_eq!(pressure.action, PressureAction::None);
+
+  let allocations: Vec<_> =
+    (0..10).map(|_| allocator.alloc(100).unwrap()).collect();
+  let pressure = allocator.pressure();
+  assert_eq!(pressure.level, PressureLevel::High);
+  assert_eq!(pressure.action, PressureAction::Grow);
+
+  // [a][free][c][free][e][free][g][h][i][j]
+  for &allocation in allocations.iter().skip(1).step_by(2).take(3) {
+    allocator.free(allocation);
+  }
+  let pressure = allocator.pressure();
+  assert_eq!(pressure.occupancy, 0.7);
+  assert_eq!(pressure.level, PressureLevel::Medium);
+  assert_eq!(pressure.action, PressureAction::Compact);
+
+  // [a][free][c][free][e][free][g][h][------free------]
+  allocator.free(allocations[8]);
+  allocator.free(allocations[9]);
+  let pressure = allocator.pressure();
+  assert_eq!(pressure.fragmentation, 0.6);
+  assert_eq!(pressure.level, PressureLevel::Medium);
+  assert_eq!(pressure.action, PressureAction::Compact);
+
+  // [a][free][c][free][e][free][g][--------free--------]
+  allocator.free(allocations[7]);
+  assert_eq!(allocator.pressure().level, PressureLevel::Low);
+
+  // failures raise the pressure, until enough requests succeed
+  for _ in 0..4 {
+    assert_eq!(allocator.alloc(400), None);
+  }
+  let pressure = allocator.pressure();
+  assert_eq!(pressure.recent_failures, 4);
+  assert_eq!(pressure.level, PressureLevel::High);
+  assert_eq!(allocator.stats().failed_allocs, 4);
+  for _ in 0..64 {
+    let allocation = allocator.alloc(1).unwrap();
+    allocator.free(allocation);
+  }
+  assert_eq!(allocator.pressure().recent_failures, 0);
+  assert_eq!(allocator.stats().failed_allocs, 4);
+}
+
+#[test]
+fn coloring() {
+  let mut allocator = Allocator::new(1 << 16);
+  allocator.set_coloring(Some(Coloring {
+    stride: 64,
+    colors: 4,
+  }));
+
+  let allocations: Vec<_> =
+    (0..6).map(|_| allocator.alloc(1_000).unwrap()).collect();
+  let colors: Vec<_> = allocations
+    .iter()
+    .map(|allocation| allocation.offset() % 256)
+    .collect();
+  assert_eq!(colors, [0, 64, 128, 192, 0, 64]);
+  assert!(allocations
+    .iter()
+    .all(|allocation| allocation.size() == 1_000));
+  let stats = allocator.stats();
+  assert_eq!(
+    (
+      stats.coalesced_left,
+      stats.coalesced_right,
+      stats.coalesced_both
+    ),
+    (0, 0, 0),
+    "The jitter is not counted as a coalesce"
+  );
+
+  let other_size = allocator.alloc(500).unwrap();
+  assert_eq!(other_size.offset() % 256, 0, "A new size starts at color 0");
+  let unaligned = allocator.alloc_with_align(500, 128).unwrap();
+  assert_eq!(
+    unaligned.offset() % 128,
+    0,
+    "Allocations aligned beyond the stride are not colored"
+  );
+
+  for allocation in allocations {
+    allocator.free(allocation);
+  }
+  allocator.free(other_size);
+  allocator.free(unaligned);
+  assert!(allocator.is_empty());
+  assert_eq!(allocator.report_free_regions().count(), 1);
+}
+
+#[test]
+fn hot_and_cold() {
+  let mut allocator = Allocator::new(10_000);
+  allocator.set_drop_check(DropCheck::Off);
+  let cold = allocator.alloc_with_placement(1_000, 1, Placement::Cold);
+  assert_eq!(cold.unwrap().offset(), 9_000, "Cold data goes at the end");
+  let hot = allocator.alloc_with_placement(100, 1, Placement::Hot);
+  assert_eq!(hot.unwrap().offset(), 0, "Hot data goes at the start");
+
+  let aligned = allocator
+    .alloc_with_align_report(100, 256)
+    .map(|(allocation, _)| allocation)
+    .unwrap();
+  assert_eq!(aligned.offset(), 256);
+  allocator.set_placement(Placement::Cold);
+  let (cold, report) = allocator.alloc_with_align_report(100, 256).unwrap();
+  assert_eq!(cold.offset(), 8_704);
+  assert_eq!(report.padding, 196, "The padding is after the allocation");
+  assert_eq!(report.leftover, 8_704 - 356);
+
+  let hot = allocator
+    .alloc_with_placement(50, 1, Placement::Hot)
+    .unwrap();
+  assert_eq!(hot.offset(), 100, "Hot data fills the lowest gaps first");
+  assert_eq!(allocator.total_available(), 10_000 - 1_350);
+}
+
+#[test]
+fn allocate_all_remaining() {
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_drop_check(DropCheck::Off);
+  let a = allocator.alloc(100).unwrap();
+  let b = allocator.alloc(100).unwrap();
+  let _c = allocator.alloc(100).unwrap();
+  allocator.free(b);
+
+  let remaining = allocator.allocate_all_remaining();
+  assert_eq!(
+    remaining
+      .iter()
+      .map(|allocation| (allocation.offset(), allocation.size()))
+      .collect::<Vec<_>>(),
+    [(100, 100), (300, 700)]
+  );
+  assert_eq!(allocator.total_available(), 0);
+  assert_eq!(allocator.alloc(1), None);
+  assert!(allocator.allocate_all_remaining().is_empty());
+
+  for allocation in remaining {
+    allocator.free(allocation);
+  }
+  allocator.free(a);
+  assert_eq!(allocator.total_available(), 900);
+}
+
+#[test]
+fn into_free_regions() {
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_drop_check(DropCheck::Panic);
+  let a = allocator.alloc(100).unwrap();
+  let _b = allocator.alloc(100).unwrap();
+  allocator.set_deferred_coalescing(true);
+  allocator.free(a);
+
+  let regions = allocator.into_free_regions();
+  assert_eq!(
+    regions
+      .iter()
+      .map(|allocation| (allocation.offset(), allocation.size()))
+      .collect::<Vec<_>>(),
+    [(0, 100), (200, 800)],
+    "Skips the drop check, & includes deferred frees"
+  );
+}
+
+#[test]
+fn free_size_percentiles() {
+  let mut allocator = Allocator::new(10_000);
+  allocator.set_drop_check(DropCheck::Off);
+  assert_eq!(allocator.smallest_available(), 10_000);
+  assert_eq!(allocator.free_size_percentile(50), 10_000);
+
+  // free-regions of 10, 20, .., 90, then the rest
+  let allocations: Vec<_> = (1..10)
+    .flat_map(|n| [allocator.alloc(n * 10), allocator.alloc(1)])
+    .map(Option::unwrap)
+    .collect();
+  for allocation in allocations.into_iter().step_by(2) {
+    allocator.free(allocation);
+  }
+  assert_eq!(allocator.smallest_available(), 10);
+  assert_eq!(allocator.free_size_percentile(0), 10);
+  assert_eq!(allocator.free_size_percentile(10), 10);
+  assert_eq!(allocator.free_size_percentile(50), 50);
+  assert_eq!(allocator.free_size_percentile(90), 90);
+  assert_eq!(
+    allocator.free_size_percentile(100),
+    allocator.largest_available()
+  );
+
+  let full = allocator.allocate_all_remaining();
+  assert_eq!(full.len(), 10);
+  assert_eq!(allocator.smallest_available(), 0);
+  assert_eq!(allocator.free_size_percentile(50), 0);
+}
+
+#[test]
+fn validate_allocation() {
+  let mut allocator = Allocator::new(1_000);
+  allocator.set_drop_check(DropCheck::Off);
+  let a = allocator.alloc(100).unwrap();
+  let b = allocator.alloc(100).unwrap();
+  assert_eq!(allocator.validate_allocation(&a), Ok(()));
+
+  allocator.free(a);
+  assert_eq!(
+    allocator.validate_allocation(&a),
+    Err(InvalidAllocation::OverlapsFree(a)),
+    "Detects stale allocations"
+  );
+  assert_eq!(allocator.validate_allocation(&b), Ok(()));
+
+  let region = |offset, size| Allocation {
+    offset,
+    size: NonZero::new(size).unwrap(),
+  };
+  let straddling = region(150, 100);
+  assert_eq!(
+    allocator.validate_allocation(&straddling),
+    Err(InvalidAllocation::OverlapsFree(region(200, 800)))
+  );
+  let out_of_bounds = region(900, 200);
+  assert_eq!(
+    allocator.validate_allocation(&out_of_bounds),
+    Err(InvalidAllocation::OutOfBounds)
+  );
+}
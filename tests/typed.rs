@@ -0,0 +1,134 @@
+use ::orderly_allocator::{Allocator, PoolVec};
+
+#[test]
+fn pool_box() {
+  let mut allocator = Allocator::new(1_024);
+  // `u64`s back the buffer, so that it is well aligned
+  let mut words = vec![0u64; 128];
+  let buffer: &mut [u8] = as_bytes_mut(&mut words);
+
+  let padding = allocator.alloc(3).unwrap();
+  let value = allocator.alloc_typed::<u64>().unwrap();
+  let array = allocator.alloc_typed::<[u16; 3]>().unwrap();
+  assert_eq!(value.offset() % 8, 0, "Allocation align is as for `T`");
+  assert_eq!(
+    value.allocation().size(),
+    8,
+    "Allocation size is as for `T`"
+  );
+  assert_eq!(
+    array.allocation().size(),
+    6,
+    "Allocation size is as for `T`"
+  );
+
+  *value.get_mut(buffer).unwrap() = 0xDEAD_BEEF;
+  *array.get_mut(buffer).unwrap() = [1, 2, 3];
+  assert_eq!(*value.get(buffer).unwrap(), 0xDEAD_BEEF);
+  assert_eq!(*array.get(buffer).unwrap(), [1, 2, 3]);
+
+  assert!(
+    value.get(&buffer[..4]).is_none(),
+    "Fails when the buffer is too short"
+  );
+  assert!(
+    value.get(&buffer[1..]).is_none(),
+    "Fails when the value would be misaligned"
+  );
+
+  assert!(allocator.alloc_typed::<()>().is_none(), "ZSTs are refused");
+
+  allocator.free_typed(value);
+  allocator.free_typed(array);
+  assert_eq!(allocator.total_available(), 1_024 - 3);
+  allocator.free(padding);
+}
+
+fn as_bytes_mut(words: &mut [u64]) -> &mut [u8] {
+  let len = words.len() * 8;
+  unsafe { core::slice::from_raw_parts_mut(words.as_mut_ptr().cast(), len) }
+}
+
+#[test]
+fn pool_vec() {
+  let mut allocator = Allocator::new(1_024);
+  let mut words = vec![0u64; 128];
+  let buffer = as_bytes_mut(&mut words);
+
+  let mut vec = PoolVec::<u32>::new();
+  assert_eq!(vec.capacity(), 0, "A new PoolVec doesn't allocate");
+
+  for i in 0..10 {
+    vec.push(&mut allocator, buffer, i).unwrap();
+  }
+  assert_eq!(vec.len(), 10);
+  assert!(vec.capacity() >= 10);
+  assert_eq!(vec.get(buffer, 3), Some(3));
+  assert_eq!(vec.get(buffer, 10), None);
+
+  // block in-place growth, forcing the elements to move
+  let blocker = allocator.alloc(4).unwrap();
+  let old_offset = vec.allocation().unwrap().offset();
+  while vec.len() < vec.capacity() {
+    vec.push(&mut allocator, buffer, vec.len()).unwrap();
+  }
+  vec.push(&mut allocator, buffer, vec.len()).unwrap();
+  assert_ne!(
+    vec.allocation().unwrap().offset(),
+    old_offset,
+    "Moves the elements when it can't grow in-place"
+  );
+  let expected: Vec<u32> = (0..vec.len()).collect();
+  assert_eq!(
+    vec.as_slice(buffer).unwrap(),
+    &expected[..],
+    "Moving preserves the elements"
+  );
+
+  assert_eq!(vec.pop(buffer), Some(vec.len()));
+  vec.as_mut_slice(buffer).unwrap()[0] = 42;
+  assert_eq!(vec.get(buffer, 0), Some(42));
+
+  vec.free(&mut allocator);
+  allocator.free(blocker);
+  assert!(allocator.is_empty());
+}
+
+#[test]
+fn alloc_elements() {
+  let mut allocator = Allocator::new(1_024);
+  let padding = allocator.alloc(3).unwrap();
+
+  let vertices = allocator.alloc_elements::<[f32; 3]>(10).unwrap();
+  assert_eq!(vertices.offset() % 4, 0);
+  assert_eq!(vertices.size(), 120);
+  assert_eq!(vertices.element_count::<[f32; 3]>(), 10);
+  assert_eq!(vertices.element_count::<u8>(), 120);
+  assert_eq!(vertices.element_count::<()>(), 0);
+
+  assert_eq!(allocator.alloc_elements::<u64>(0), None);
+  assert_eq!(allocator.alloc_elements::<()>(10), None);
+  assert_eq!(allocator.alloc_elements::<u64>(usize::MAX), None);
+  assert_eq!(allocator.alloc_elements::<u8>(usize::MAX / 2), None);
+  allocator.free_many([vertices, padding]);
+}
+
+#[test]
+fn alloc_layout() {
+  use ::core::alloc::Layout;
+
+  let mut allocator = Allocator::new(1_024);
+  let padding = allocator.alloc(3).unwrap();
+
+  let a = allocator.alloc_layout(Layout::new::<[u32; 4]>()).unwrap();
+  assert_eq!((a.offset() % 4, a.size()), (0, 16));
+  let b = allocator
+    .alloc_layout(Layout::from_size_align(100, 64).unwrap())
+    .unwrap();
+  assert_eq!((b.offset() % 64, b.size()), (0, 100));
+
+  assert_eq!(allocator.alloc_layout(Layout::new::<()>()), None);
+  let huge = Layout::from_size_align(1 << 20, 1 << 20).unwrap();
+  assert_eq!(allocator.alloc_layout(huge), None);
+  allocator.free_many([a, b, padding]);
+}
@@ -0,0 +1,28 @@
+#![cfg(feature = "wasm")]
+
+use ::orderly_allocator::{WasmAllocation, WasmAllocator};
+
+#[test]
+fn alloc_and_free() {
+  let mut allocator = WasmAllocator::new(1_000).unwrap();
+
+  let a = allocator.alloc_with_align(100, 16).unwrap();
+  assert_eq!(a.offset % 16, 0);
+  assert_eq!(a.size, 100);
+  assert!(allocator.alloc(2_000).is_none());
+
+  let a = allocator.try_reallocate(a, 200).unwrap();
+  assert_eq!(allocator.total_available(), 800);
+
+  allocator.free(a).unwrap();
+  assert!(allocator.is_empty());
+  let b = allocator.alloc(1_000);
+  assert_eq!(
+    b,
+    Some(WasmAllocation {
+      offset: 0,
+      size: 1_000
+    })
+  );
+  allocator.free(b.unwrap()).unwrap();
+}
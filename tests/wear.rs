@@ -0,0 +1,33 @@
+#![cfg(feature = "wear-stats")]
+
+use ::orderly_allocator::Allocator;
+
+#[test]
+fn wear_stats() {
+  let mut allocator = Allocator::new(1_000);
+  assert!(allocator.wear_stats().is_none());
+  allocator.enable_wear_stats(300);
+
+  let a = allocator.alloc(100).unwrap();
+  allocator.free(a);
+  let a = allocator.alloc(350).unwrap();
+  let wear = allocator.wear_stats().unwrap();
+  assert_eq!(wear.stripe(), 300);
+  assert_eq!(wear.counts(), [2, 1, 0, 0]);
+  assert_eq!(wear.count_at(999), Some(0));
+  assert_eq!(wear.count_at(1_000), None);
+
+  // growing in-place only wears the new part
+  let a = allocator.try_reallocate(a, 700).unwrap();
+  assert_eq!(allocator.wear_stats().unwrap().counts(), [2, 2, 1, 0]);
+  assert_eq!(allocator.wear_stats().unwrap().max(), 2);
+
+  allocator.grow_capacity(500).unwrap();
+  assert_eq!(allocator.wear_stats().unwrap().counts(), [2, 2, 1, 0, 0]);
+
+  allocator.wear_stats_mut().unwrap().clear();
+  assert_eq!(allocator.wear_stats().unwrap().max(), 0);
+  allocator.disable_wear_stats();
+  assert!(allocator.wear_stats().is_none());
+  allocator.free(a);
+}
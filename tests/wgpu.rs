@@ -0,0 +1,33 @@
+#![cfg(feature = "wgpu")]
+
+use {
+  ::orderly_allocator::BufferSuballocator,
+  ::wgpu::{BufferUsages, Limits},
+};
+
+#[test]
+fn required_alignment() {
+  let limits = Limits {
+    min_uniform_buffer_offset_alignment: 256,
+    min_storage_buffer_offset_alignment: 64,
+    ..Limits::default()
+  };
+
+  assert_eq!(
+    BufferSuballocator::required_alignment(BufferUsages::VERTEX, &limits),
+    4,
+    "Copies require 4-byte alignment"
+  );
+  assert_eq!(
+    BufferSuballocator::required_alignment(BufferUsages::STORAGE, &limits),
+    64
+  );
+  assert_eq!(
+    BufferSuballocator::required_alignment(
+      BufferUsages::UNIFORM | BufferUsages::STORAGE,
+      &limits
+    ),
+    256,
+    "The strictest alignment wins"
+  );
+}
@@ -0,0 +1,70 @@
+use ::orderly_allocator::{Allocator, FixedAllocator, Placement};
+
+#[test]
+fn allocator() {
+  let mut allocator = Allocator::new(1_000);
+  let best_fit = allocator.worst_case(100);
+  assert!(best_fit.may_touch_heap);
+  assert_eq!(best_fit.table_shift, 0);
+  assert_eq!(best_fit.tree_depth, 3);
+  assert!(
+    best_fit.alloc.regions_visited < 100,
+    "Best-fit searches are independent of the number of free-regions"
+  );
+  assert_eq!(best_fit.huge_align_alloc.regions_visited, 100);
+  assert_eq!(best_fit.free.lookups, 2);
+
+  assert_eq!(
+    allocator
+      .worst_case(usize::MAX)
+      .huge_align_alloc
+      .regions_visited,
+    500,
+    "There are never more free-regions than half the capacity"
+  );
+
+  allocator.set_placement(Placement::Random { seed: 0 });
+  assert_eq!(allocator.worst_case(100).alloc.regions_visited, 200);
+}
+
+#[test]
+fn tables() {
+  let fixed = FixedAllocator::<16>::new(1_000);
+  let worst_case = fixed.worst_case();
+  assert!(!worst_case.may_touch_heap);
+  assert_eq!(worst_case.tree_depth, 0);
+  assert_eq!(worst_case.table_shift, 15);
+  assert_eq!(worst_case.alloc.regions_visited, 16);
+
+  let locked = Allocator::new(1_000).lock(16);
+  assert_eq!(locked.worst_case(), worst_case);
+}
+
+#[cfg(feature = "op-costs")]
+#[test]
+fn bounds_measured_costs() {
+  let mut allocator = Allocator::new(100_000);
+  let allocations: Vec<_> =
+    (0..200).map(|_| allocator.alloc(100).unwrap()).collect();
+  for allocation in allocations.into_iter().step_by(2) {
+    allocator.free(allocation);
+  }
+  let worst_case = allocator.worst_case(101);
+
+  for align in [1, 64, 4_096] {
+    let before = allocator.op_costs();
+    let allocation = allocator.alloc_with_align(150, align).unwrap();
+    let cost = allocator.op_costs() - before;
+    assert!(cost.lookups <= worst_case.alloc.lookups);
+    assert!(cost.regions_visited <= worst_case.alloc.regions_visited);
+    assert!(cost.tree_updates <= worst_case.alloc.tree_updates);
+
+    let before = allocator.op_costs();
+    allocator.free(allocation);
+    let cost = allocator.op_costs() - before;
+    assert!(cost.lookups <= worst_case.free.lookups);
+    assert!(cost.regions_visited <= worst_case.free.regions_visited);
+    assert!(cost.tree_updates <= worst_case.free.tree_updates);
+  }
+  allocator.reset();
+}